@@ -0,0 +1,47 @@
+use crate::commands::AppState;
+use crate::models::advisory::AdvisoryDb;
+use tauri::State;
+
+const ADVISORY_DB_REMOTE_URL: &str =
+    "https://raw.githubusercontent.com/brucevanfdm/agent-skills-guard/main/advisory-db.json";
+
+/// 当前生效的漏洞公告数据库版本号：优先读取 sqlite 缓存（已被
+/// [`refresh_advisory_db`] 刷新过），否则回退到编译期内置快照
+#[tauri::command]
+pub async fn get_advisory_db_version(state: State<'_, AppState>) -> Result<u64, String> {
+    let cached = state.db.get_advisory_db_cache().map_err(|e| e.to_string())?;
+    Ok(cached.unwrap_or_else(AdvisoryDb::embedded).version)
+}
+
+/// 从远端下载最新的漏洞公告数据库并写入 sqlite 缓存，供后续所有依赖扫描离线
+/// 复用——与 [`crate::commands::featured_marketplaces::refresh_featured_marketplaces`]
+/// 同样的缓存-远端兜底模式，只是缓存落在 sqlite（`advisory_db_cache` 表）而不是
+/// app_data_dir 文件，因为扫描调用方本来就持有 `state.db`，不必额外引入
+/// `AppHandle` 依赖。下载到的版本号低于已缓存版本时视为疑似回滚，拒绝覆盖。
+#[tauri::command]
+pub async fn refresh_advisory_db(state: State<'_, AppState>) -> Result<AdvisoryDb, String> {
+    let client = reqwest::Client::new();
+    let db: AdvisoryDb = client
+        .get(ADVISORY_DB_REMOTE_URL)
+        .header(reqwest::header::USER_AGENT, "agent-skills-guard")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download advisory database: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to download advisory database: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse advisory database: {}", e))?;
+
+    if let Some(cached) = state.db.get_advisory_db_cache().map_err(|e| e.to_string())? {
+        if db.version < cached.version {
+            return Err(format!(
+                "下载到的漏洞公告数据库版本（{}）低于本地已缓存版本（{}），疑似回滚，已拒绝覆盖",
+                db.version, cached.version
+            ));
+        }
+    }
+
+    state.db.save_advisory_db_cache(&db).map_err(|e| e.to_string())?;
+    Ok(db)
+}
@@ -0,0 +1,150 @@
+use crate::commands::AppState;
+use crate::models::{
+    DiagnosticCheck,
+    DiagnosticStatus,
+    DiagnosticsReport,
+    MarketplaceDiagnostic,
+    PluginPathDiagnostic,
+};
+use crate::services::plugin_manager::ClaudeMarketplace;
+use std::path::PathBuf;
+use std::process::Command;
+use tauri::State;
+use which::which;
+
+/// 探测 Claude CLI：能否找到可执行文件、`--version` 是否能正常返回
+fn probe_claude_cli(claude_command: &str) -> DiagnosticCheck {
+    let Ok(path) = which(claude_command) else {
+        return DiagnosticCheck::fail(
+            "Claude CLI",
+            format!("未找到可执行文件: {}", claude_command),
+        );
+    };
+
+    match Command::new(claude_command).arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            let version = if version.is_empty() { "未知版本".to_string() } else { version };
+            DiagnosticCheck::pass("Claude CLI", format!("{} ({})", version, path.display()))
+        }
+        Ok(_) => DiagnosticCheck::warn(
+            "Claude CLI",
+            format!("{} 存在，但 --version 返回非零状态", path.display()),
+        ),
+        Err(e) => DiagnosticCheck::warn(
+            "Claude CLI",
+            format!("{} 存在，但无法执行: {}", path.display(), e),
+        ),
+    }
+}
+
+/// 探测一个预期存在的目录（plugins/marketplaces 安装目录）
+fn probe_dir(label: &str, path: Option<PathBuf>) -> DiagnosticCheck {
+    match path {
+        Some(path) if path.is_dir() => {
+            DiagnosticCheck::pass(label, path.to_string_lossy().to_string())
+        }
+        Some(path) => DiagnosticCheck::warn(label, format!("目录不存在: {}", path.display())),
+        None => DiagnosticCheck::fail(label, "无法解析用户主目录".to_string()),
+    }
+}
+
+/// 读取某个 marketplace 安装目录的 git HEAD（short sha），失败时返回 `None`
+fn git_head_sha(install_location: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["-C", install_location, "rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if sha.is_empty() {
+        None
+    } else {
+        Some(sha.chars().take(12).collect())
+    }
+}
+
+fn diagnose_marketplace(mp: ClaudeMarketplace) -> MarketplaceDiagnostic {
+    let head_sha = mp
+        .install_location
+        .as_deref()
+        .and_then(git_head_sha);
+
+    let status = match (&mp.install_location, &head_sha) {
+        (Some(_), Some(_)) => DiagnosticStatus::Pass,
+        (Some(_), None) => DiagnosticStatus::Warn,
+        (None, _) => DiagnosticStatus::Warn,
+    };
+
+    MarketplaceDiagnostic {
+        name: mp.name,
+        install_location: mp.install_location,
+        head_sha,
+        status,
+    }
+}
+
+/// 收集运行环境诊断报告：Claude CLI 探测、plugins/marketplaces 目录解析、
+/// 各 marketplace 安装目录的 git HEAD，以及已安装 plugin 的 `claude_install_path`
+/// 是否仍然存在于磁盘上。
+///
+/// 这些信息此前只能通过 `sync_claude_installed_state` 等路径里的
+/// `log::warn!` 在后端日志里看到，用户完全看不到同步/扫描为什么悄悄回退到
+/// DB 缓存；本命令把它们汇总成一份结构化报告，供 UI 渲染成「诊断」页面。
+#[tauri::command]
+pub async fn get_diagnostics(
+    state: State<'_, AppState>,
+    claude_command: Option<String>,
+) -> Result<DiagnosticsReport, String> {
+    let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
+
+    let mut checks = vec![probe_claude_cli(&cli_command)];
+
+    let plugins_dir = dirs::home_dir().map(|home| home.join(".claude").join("plugins"));
+    let marketplaces_dir = dirs::home_dir()
+        .map(|home| home.join(".claude").join("plugins").join("marketplaces"));
+    checks.push(probe_dir("Plugins directory", plugins_dir));
+    checks.push(probe_dir("Marketplaces directory", marketplaces_dir));
+
+    let claude_marketplaces = {
+        let manager = state.plugin_manager.lock().await;
+        manager
+            .get_claude_marketplaces(Some(cli_command))
+            .await
+            .unwrap_or_default()
+    };
+    let marketplaces = claude_marketplaces
+        .into_iter()
+        .map(diagnose_marketplace)
+        .collect();
+
+    let plugins = state
+        .db
+        .get_plugins()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|p| p.installed)
+        .map(|p| {
+            let status = match p.claude_install_path.as_deref() {
+                Some(path) if PathBuf::from(path).exists() => DiagnosticStatus::Pass,
+                Some(_) => DiagnosticStatus::Fail,
+                None => DiagnosticStatus::Warn,
+            };
+            PluginPathDiagnostic {
+                plugin_id: p.id,
+                plugin_name: p.name,
+                install_path: p.claude_install_path,
+                status,
+            }
+        })
+        .collect();
+
+    Ok(DiagnosticsReport {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        checks,
+        marketplaces,
+        plugins,
+    })
+}
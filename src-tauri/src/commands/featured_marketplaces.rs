@@ -1,12 +1,18 @@
 use crate::models::FeaturedMarketplacesConfig;
+use crate::security::metadata_signing::{
+    verify_metadata, verify_yaml_hash, SignedMetadataEnvelope, TrustedRootKeys,
+};
+use chrono::Utc;
 use std::path::PathBuf;
 use tauri::Manager;
 
 const FEATURED_MARKETPLACES_REMOTE_URL: &str =
     "https://raw.githubusercontent.com/brucevanfdm/agent-skills-guard/main/featured-marketplace.yaml";
+const FEATURED_MARKETPLACES_METADATA_URL: &str =
+    "https://raw.githubusercontent.com/brucevanfdm/agent-skills-guard/main/featured-marketplace.meta.json";
 const DEFAULT_FEATURED_MARKETPLACES_YAML: &str = include_str!("../../../featured-marketplace.yaml");
 
-fn featured_marketplaces_cache_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+pub fn featured_marketplaces_cache_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     let app_dir = app
         .path()
         .app_data_dir()
@@ -18,6 +24,18 @@ fn featured_marketplaces_cache_path(app: &tauri::AppHandle) -> Result<PathBuf, S
     Ok(app_dir.join("featured-marketplace.yaml"))
 }
 
+/// 记录上一次成功校验通过的元数据版本号，供下次刷新时做反回滚检查
+fn featured_marketplaces_version_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let cache_path = featured_marketplaces_cache_path(app)?;
+    Ok(cache_path.with_extension("version"))
+}
+
+fn read_last_seen_version(path: &std::path::Path) -> Option<u64> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+}
+
 /// 获取精选插件市场列表
 #[tauri::command]
 pub async fn get_featured_marketplaces(
@@ -43,14 +61,42 @@ pub async fn get_featured_marketplaces(
         .map_err(|e| format!("Failed to parse default featured marketplaces: {}", e))
 }
 
-/// 刷新精选插件市场列表（从 GitHub 下载最新 YAML 并写入 app_data_dir 缓存）
+/// 刷新精选插件市场列表（从 GitHub 下载最新 YAML 并写入 app_data_dir 缓存）。
+///
+/// 精选 marketplace 列表是一个供应链攻击入口：CDN 被攻破或遭中间人篡改都
+/// 可能让用户添加恶意 marketplace。因此这里套了一层 TUF 风格的签名元数据
+/// 校验——先下载签名元数据，用内置根公钥验证签名门限、反回滚（版本号不能
+/// 低于上次记录的版本）、过期时间，再校验下载到的 YAML 的 SHA-256 与元数据
+/// 里记录的一致，全部通过才落盘替换缓存；任何一步失败都保留现有缓存并返回
+/// 错误，绝不静默接受未经校验的内容。
 #[tauri::command]
 pub async fn refresh_featured_marketplaces(
     app: tauri::AppHandle,
 ) -> Result<FeaturedMarketplacesConfig, String> {
     use std::io::Write;
 
-    let yaml_content = reqwest::Client::new()
+    let client = reqwest::Client::new();
+
+    let metadata_envelope: SignedMetadataEnvelope = client
+        .get(FEATURED_MARKETPLACES_METADATA_URL)
+        .header(reqwest::header::USER_AGENT, "agent-skills-guard")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download featured marketplaces metadata: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to download featured marketplaces metadata: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse featured marketplaces metadata: {}", e))?;
+
+    let version_path = featured_marketplaces_version_path(&app)?;
+    let last_seen_version = read_last_seen_version(&version_path);
+
+    let roots = TrustedRootKeys::embedded();
+    verify_metadata(&metadata_envelope, &roots, last_seen_version, Utc::now())
+        .map_err(|e| format!("Featured marketplaces metadata verification failed: {}", e))?;
+
+    let yaml_content = client
         .get(FEATURED_MARKETPLACES_REMOTE_URL)
         .header(reqwest::header::USER_AGENT, "agent-skills-guard")
         .send()
@@ -62,6 +108,9 @@ pub async fn refresh_featured_marketplaces(
         .await
         .map_err(|e| format!("Failed to read featured marketplaces content: {}", e))?;
 
+    verify_yaml_hash(yaml_content.as_bytes(), &metadata_envelope.metadata)
+        .map_err(|e| format!("Featured marketplaces content verification failed: {}", e))?;
+
     // 先校验解析成功，再落盘
     let config: FeaturedMarketplacesConfig = serde_yaml::from_str(&yaml_content)
         .map_err(|e| format!("Failed to parse downloaded featured marketplaces: {}", e))?;
@@ -84,5 +133,8 @@ pub async fn refresh_featured_marketplaces(
     tmp.persist(&cache_path)
         .map_err(|e| format!("Failed to persist featured marketplaces cache: {}", e))?;
 
+    // 签名、哈希都验证通过后才推进本地记录的版本号，保证反回滚检查跨重启依然有效
+    let _ = std::fs::write(&version_path, metadata_envelope.metadata.version.to_string());
+
     Ok(config)
 }
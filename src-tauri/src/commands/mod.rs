@@ -0,0 +1,235 @@
+pub mod security;
+pub mod plugins;
+pub mod featured_marketplaces;
+pub mod diagnostics;
+pub mod advisory_db;
+
+use crate::models::{DatabaseStats, HostConfig, IntegrityReport, RepairPolicy, RepairReport, Repository, Skill};
+use crate::security::SecurityScanner;
+use crate::services::{Database, GitHubService, PluginManager, PluginStore, SkillManager};
+use std::sync::{Arc, RwLock};
+use tauri::State;
+use tokio::sync::Mutex;
+
+/// 应用全局状态，挂载在 `tauri::AppHandle` 上，供各个 `#[tauri::command]` 共享
+pub struct AppState {
+    pub db: Arc<Database>,
+    pub skill_manager: Arc<Mutex<SkillManager>>,
+    pub plugin_manager: Arc<Mutex<PluginManager>>,
+    pub github: Arc<GitHubService>,
+    /// 当前生效的安全扫描器，默认只启用内置规则；可以通过
+    /// [`security::set_security_rule_pack`] 换成加载了用户自定义规则包的实例
+    pub security_scanner: Arc<RwLock<SecurityScanner>>,
+    /// `Plugin` 状态的快照缓存（[`PluginStore`]），用于前端快速重新加载，
+    /// 不是权威数据源——权威数据始终是 `db`
+    pub plugin_store: Arc<PluginStore>,
+}
+
+/// 添加仓库。`git_ref` 可选地固定一个分支/tag/commit SHA，留空则每次扫描都解析
+/// 仓库当前的默认分支；`host` 可选地指向一个 GitHub Enterprise Server、Gitee
+/// 等 GitHub REST API 兼容的自建实例，留空则使用公共 github.com
+#[tauri::command]
+pub async fn add_repository(
+    state: State<'_, AppState>,
+    url: String,
+    name: String,
+    git_ref: Option<String>,
+    host: Option<HostConfig>,
+) -> Result<Repository, String> {
+    let mut repo = Repository::new(url, name);
+    repo.git_ref = git_ref;
+    repo.host = host;
+    state.db.add_repository(&repo).map_err(|e| e.to_string())?;
+    Ok(repo)
+}
+
+/// 获取所有仓库
+#[tauri::command]
+pub async fn get_repositories(state: State<'_, AppState>) -> Result<Vec<Repository>, String> {
+    state.db.get_repositories().map_err(|e| e.to_string())
+}
+
+/// 获取仪表盘聚合统计
+#[tauri::command]
+pub async fn get_stats(state: State<'_, AppState>) -> Result<DatabaseStats, String> {
+    state.db.stats().map_err(|e| e.to_string())
+}
+
+/// 删除仓库（同时清理该仓库下未安装的 skills/plugins）
+#[tauri::command]
+pub async fn delete_repository(state: State<'_, AppState>, repo_id: String) -> Result<(), String> {
+    let repo = state
+        .db
+        .get_repository(&repo_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Repository not found".to_string())?;
+
+    state
+        .db
+        .delete_uninstalled_skills_by_repository_url(&repo.url)
+        .map_err(|e| e.to_string())?;
+    state
+        .db
+        .delete_uninstalled_plugins_by_repository_url(&repo.url)
+        .map_err(|e| e.to_string())?;
+    state.db.delete_repository(&repo_id).map_err(|e| e.to_string())
+}
+
+/// 扫描仓库，发现其中的 skills
+#[tauri::command]
+pub async fn scan_repository(state: State<'_, AppState>, repo_id: String) -> Result<Vec<Skill>, String> {
+    let repo = state
+        .db
+        .get_repository(&repo_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Repository not found".to_string())?;
+
+    let skills = state
+        .github
+        .scan_repository(&repo)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for skill in &skills {
+        state.db.save_skill(skill).map_err(|e| e.to_string())?;
+    }
+
+    Ok(skills)
+}
+
+/// 获取所有 skills
+#[tauri::command]
+pub async fn get_skills(state: State<'_, AppState>) -> Result<Vec<Skill>, String> {
+    state.db.get_skills().map_err(|e| e.to_string())
+}
+
+/// 按关键词全文检索 skills（name/description/author），按相关度排序
+#[tauri::command]
+pub async fn search_skills(state: State<'_, AppState>, query: String) -> Result<Vec<Skill>, String> {
+    state.db.search_skills(&query).map_err(|e| e.to_string())
+}
+
+/// 获取已安装的 skills
+#[tauri::command]
+pub async fn get_installed_skills(state: State<'_, AppState>) -> Result<Vec<Skill>, String> {
+    let manager = state.skill_manager.lock().await;
+    manager.get_installed_skills().map_err(|e| e.to_string())
+}
+
+/// 安装 skill。`force` 为 `true` 时跳过能力策略确认（用户已在前端确认过）
+#[tauri::command]
+pub async fn install_skill(
+    state: State<'_, AppState>,
+    skill_id: String,
+    force: Option<bool>,
+) -> Result<(), String> {
+    let manager = state.skill_manager.lock().await;
+    manager
+        .install_skill(&skill_id, force.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 检测某个已安装 skill 是否有可用更新（对比远端 SKILL.md 与安装时记录的校验和）
+#[tauri::command]
+pub async fn check_skill_update(
+    state: State<'_, AppState>,
+    skill_id: String,
+) -> Result<crate::models::SkillStatus, String> {
+    let manager = state.skill_manager.lock().await;
+    manager.check_skill_update(&skill_id).await.map_err(|e| e.to_string())
+}
+
+/// 卸载 skill
+#[tauri::command]
+pub async fn uninstall_skill(state: State<'_, AppState>, skill_id: String) -> Result<(), String> {
+    let manager = state.skill_manager.lock().await;
+    manager.uninstall_skill(&skill_id).map_err(|e| e.to_string())
+}
+
+/// 从数据库中彻底删除 skill 记录（不影响已安装的本地文件）
+#[tauri::command]
+pub async fn delete_skill(state: State<'_, AppState>, skill_id: String) -> Result<(), String> {
+    state.db.delete_skill(&skill_id).map_err(|e| e.to_string())
+}
+
+/// 校验已安装 skill 的完整性（文件缺失/校验和篡改/孤儿 installation 记录）
+#[tauri::command]
+pub async fn verify_installations(state: State<'_, AppState>) -> Result<IntegrityReport, String> {
+    let manager = state.skill_manager.lock().await;
+    manager.verify_installations().map_err(|e| e.to_string())
+}
+
+/// 按 `policy` 修复 `verify_installations` 发现的问题
+#[tauri::command]
+pub async fn repair_installations(
+    state: State<'_, AppState>,
+    policy: Option<RepairPolicy>,
+) -> Result<RepairReport, String> {
+    let manager = state.skill_manager.lock().await;
+    manager
+        .repair_installations(policy.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// 扫描本地尚未同步过的仓库，批量发现新 skills
+#[tauri::command]
+pub async fn scan_local_skills(state: State<'_, AppState>) -> Result<Vec<Skill>, String> {
+    let repo_ids = state.db.get_unscanned_repositories().map_err(|e| e.to_string())?;
+    let mut all_skills = Vec::new();
+
+    for repo_id in repo_ids {
+        let Some(repo) = state.db.get_repository(&repo_id).map_err(|e| e.to_string())? else {
+            continue;
+        };
+
+        match state.github.scan_repository(&repo).await {
+            Ok(skills) => {
+                for skill in &skills {
+                    if let Err(e) = state.db.save_skill(skill) {
+                        log::warn!("Failed to save skill {}: {}", skill.name, e);
+                    }
+                }
+                all_skills.extend(skills);
+            }
+            Err(e) => log::warn!("Failed to scan repository {}: {}", repo.name, e),
+        }
+    }
+
+    Ok(all_skills)
+}
+
+/// 清除仓库缓存元数据（不删除本地文件）
+#[tauri::command]
+pub async fn clear_repository_cache(state: State<'_, AppState>, repo_id: String) -> Result<(), String> {
+    state
+        .db
+        .clear_repository_cache_metadata(&repo_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 刷新仓库缓存（重新下载并更新缓存元数据）
+#[tauri::command]
+pub async fn refresh_repository_cache(state: State<'_, AppState>, repo_id: String) -> Result<(), String> {
+    let repo = state
+        .db
+        .get_repository(&repo_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Repository not found".to_string())?;
+
+    state.db.clear_repository_cache_metadata(&repo_id).map_err(|e| e.to_string())?;
+    state
+        .github
+        .scan_repository(&repo)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// 缓存统计信息：(仓库总数, 已缓存仓库数)
+#[tauri::command]
+pub async fn get_cache_stats(state: State<'_, AppState>) -> Result<(usize, usize), String> {
+    let repos = state.db.get_repositories().map_err(|e| e.to_string())?;
+    let cached = repos.iter().filter(|r| r.cache_path.is_some()).count();
+    Ok((repos.len(), cached))
+}
@@ -1,13 +1,17 @@
 use crate::commands::AppState;
-use crate::models::{Plugin, SecurityReport};
+use crate::models::advisory::AdvisoryDb;
+use crate::models::{LockfileAuditReport, Plugin, PluginCapabilities, PluginStateImportResult, PluginStateManifest, SecurityReport, UpdateStatus};
 use crate::services::plugin_manager::{
+    git_output,
     ClaudeMarketplace,
     MarketplaceRemoveResult,
     MarketplaceUpdateResult,
     PluginInstallResult,
+    PluginLifecycleHookResult,
     PluginUninstallResult,
     PluginUpdateResult,
     SkillPluginUpgradeCandidate,
+    VersionBump,
 };
 use crate::commands::featured_marketplaces;
 use crate::security::{ScanOptions, SecurityScanner};
@@ -16,7 +20,7 @@ use chrono::Utc;
 use rayon::prelude::*;
 use rayon::ThreadPoolBuilder;
 use serde::Serialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter, State};
 
 #[derive(Serialize, Clone)]
@@ -60,7 +64,51 @@ pub async fn get_plugins(
         }
     }
 
-    state.db.get_plugins().map_err(|e| e.to_string())
+    let plugins = state.db.get_plugins().map_err(|e| e.to_string())?;
+    state.plugin_store.save_all(&plugins);
+    Ok(plugins)
+}
+
+/// 从快照缓存（[`crate::services::PluginStore`]）读取上一次已知的 plugins 状态，
+/// 不触发 `claude plugin list --json` 同步。用于前端在调用较慢的 [`get_plugins`]
+/// 拿到权威结果之前先完成一次即时渲染；这里的数据可能是过期的
+#[tauri::command]
+pub async fn get_cached_plugins(state: State<'_, AppState>) -> Result<Vec<Plugin>, String> {
+    Ok(state.plugin_store.load_all())
+}
+
+/// 按关键词全文检索 plugins（name/description/author），按相关度排序
+#[tauri::command]
+pub async fn search_plugins(state: State<'_, AppState>, query: String) -> Result<Vec<Plugin>, String> {
+    state.db.search_plugins(&query).map_err(|e| e.to_string())
+}
+
+/// 列出某个精选 marketplace 下的所有 plugins（会先与 Claude CLI 同步安装状态）
+#[tauri::command]
+pub async fn list_marketplace_plugins(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    marketplace_name: String,
+    locale: Option<String>,
+) -> Result<Vec<Plugin>, String> {
+    let locale = validate_locale(locale.as_deref().unwrap_or("en"));
+    let featured_config = featured_marketplaces::get_featured_marketplaces(app).await.ok();
+
+    let manager = state.plugin_manager.lock().await;
+    if let Some(config) = &featured_config {
+        if let Err(e) = manager.sync_featured_marketplaces(config, &locale, None).await {
+            log::warn!("同步精选插件清单失败: {}", e);
+        }
+    }
+    if let Err(e) = manager.sync_claude_installed_state(None).await {
+        log::warn!("同步 Claude plugins 状态失败: {}", e);
+    }
+
+    let plugins = state.db.get_plugins().map_err(|e| e.to_string())?;
+    Ok(plugins
+        .into_iter()
+        .filter(|p| p.marketplace_name == marketplace_name)
+        .collect())
 }
 
 /// 准备安装 plugin：下载并扫描 marketplace repo
@@ -69,24 +117,85 @@ pub async fn prepare_plugin_installation(
     state: State<'_, AppState>,
     plugin_id: String,
     locale: String,
+    strict_dependencies: Option<bool>,
 ) -> Result<SecurityReport, String> {
     let manager = state.plugin_manager.lock().await;
-    manager.prepare_plugin_installation(&plugin_id, &locale).await
+    manager.prepare_plugin_installation(&plugin_id, &locale, strict_dependencies.unwrap_or(false)).await
         .map_err(|e| e.to_string())
 }
 
 /// 确认安装 plugin：驱动 Claude Code CLI 执行安装
+///
+/// 安装过程中捕获到的 PTY 输出会以 `claude-cli://output` 事件实时推送给前端，
+/// 便于长耗时的安装展示进度而非整体阻塞等待。
 #[tauri::command]
 pub async fn confirm_plugin_installation(
     state: State<'_, AppState>,
+    app: AppHandle,
     plugin_id: String,
     claude_command: Option<String>,
 ) -> Result<PluginInstallResult, String> {
     let manager = state.plugin_manager.lock().await;
-    manager.confirm_plugin_installation(&plugin_id, claude_command).await
+    let mut emit_output = |chunk: &str| {
+        let _ = app.emit("claude-cli://output", chunk.to_string());
+    };
+    manager
+        .confirm_plugin_installation(&plugin_id, claude_command, Some(&mut emit_output))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 准备安装一个本地开发中的 plugin：直接扫描磁盘目录，不经过 marketplace
+#[tauri::command]
+pub async fn prepare_local_plugin_installation(
+    state: State<'_, AppState>,
+    source_path: String,
+    locale: String,
+) -> Result<SecurityReport, String> {
+    let manager = state.plugin_manager.lock().await;
+    manager.prepare_local_plugin_installation(&source_path, &locale).await
+        .map_err(|e| e.to_string())
+}
+
+/// 确认安装本地 plugin：把暂存目录落地到 Claude 插件目录
+#[tauri::command]
+pub async fn confirm_local_plugin_installation(
+    state: State<'_, AppState>,
+    plugin_id: String,
+) -> Result<Plugin, String> {
+    let manager = state.plugin_manager.lock().await;
+    manager.confirm_local_plugin_installation(&plugin_id)
         .map_err(|e| e.to_string())
 }
 
+/// 准备安装一个任意 git 仓库里的 plugin：clone（可选 checkout 到某个 ref）并扫描，
+/// 不要求对方仓库提供 marketplace.json。确认安装复用
+/// [`confirm_local_plugin_installation`]，其只认 `staging_path`
+#[tauri::command]
+pub async fn prepare_git_plugin_installation(
+    state: State<'_, AppState>,
+    repository_url: String,
+    git_ref: Option<String>,
+    locale: String,
+) -> Result<SecurityReport, String> {
+    let manager = state.plugin_manager.lock().await;
+    manager
+        .prepare_git_plugin_installation(&repository_url, git_ref.as_deref(), &locale)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 读取一次 plugin/marketplace 操作（安装/卸载/更新/移除）留下的完整日志，
+/// 供 UI 在操作失败时展示给用户
+#[tauri::command]
+pub async fn get_operation_log(
+    state: State<'_, AppState>,
+    operation_id: String,
+) -> Result<String, String> {
+    let manager = state.plugin_manager.lock().await;
+    manager.read_operation_log(&operation_id).map_err(|e| e.to_string())
+}
+
 /// 取消 plugin 安装准备状态
 #[tauri::command]
 pub async fn cancel_plugin_installation(
@@ -106,8 +215,10 @@ pub async fn uninstall_plugin(
     claude_command: Option<String>,
 ) -> Result<PluginUninstallResult, String> {
     let manager = state.plugin_manager.lock().await;
-    manager.uninstall_plugin(&plugin_id, claude_command).await
-        .map_err(|e| e.to_string())
+    let result = manager.uninstall_plugin(&plugin_id, claude_command).await
+        .map_err(|e| e.to_string())?;
+    state.plugin_store.delete(&plugin_id);
+    Ok(result)
 }
 
 /// 移除整个 marketplace（会自动卸载该 marketplace 的所有 plugins）
@@ -136,13 +247,20 @@ pub async fn get_claude_marketplaces(
         .map_err(|e| e.to_string())
 }
 
+/// 读取后台更新检查器最近一次缓存的结果，不触发任何 CLI 调用
+#[tauri::command]
+pub async fn get_cached_update_status(state: State<'_, AppState>) -> Result<Vec<UpdateStatus>, String> {
+    state.db.get_update_status().map_err(|e| e.to_string())
+}
+
 /// 检查已安装 plugins 的更新（来自 CLI）
-/// 返回：Vec<(plugin_id, latest_version)>
+/// 返回：Vec<(plugin_id, latest_version, bump)>，`bump` 按 semver 比较得出的
+/// major/minor/patch 升级幅度，供前端在重大版本升级前提示用户确认
 #[tauri::command]
 pub async fn check_plugins_updates(
     state: State<'_, AppState>,
     claude_command: Option<String>,
-) -> Result<Vec<(String, String)>, String> {
+) -> Result<Vec<(String, String, VersionBump)>, String> {
     let manager = state.plugin_manager.lock().await;
     manager
         .check_plugins_updates(claude_command)
@@ -164,6 +282,36 @@ pub async fn update_plugin(
         .map_err(|e| e.to_string())
 }
 
+/// 执行一个已安装 plugin 声明的生命周期钩子（`preinstall`/`postinstall`/
+/// `preuninstall`/`postuninstall`），用于用户主动重跑某个钩子的场景（例如
+/// 安装成功但 `postinstall` 因环境问题失败，修复环境后重试）
+#[tauri::command]
+pub async fn run_plugin_lifecycle_hook(
+    state: State<'_, AppState>,
+    plugin_id: String,
+    phase: String,
+) -> Result<PluginLifecycleHookResult, String> {
+    let manager = state.plugin_manager.lock().await;
+    manager
+        .run_plugin_lifecycle_hook(&plugin_id, &phase)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 读取上次安装解析时生成的 `skills-guard.lock`，与磁盘上当前的 manifest 对比，
+/// 标记出版本漂移（drift）的 plugin，并附带锁定时的 blocked/partial_scan 状态
+#[tauri::command]
+pub async fn get_plugin_lockfile_info(
+    state: State<'_, AppState>,
+    plugin_id: String,
+) -> Result<LockfileAuditReport, String> {
+    let manager = state.plugin_manager.lock().await;
+    manager
+        .get_lockfile_info(&plugin_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// 检查 marketplaces 的更新（基于本地安装目录的 git HEAD 对比）
 /// 返回：Vec<(marketplace_name, latest_head_short_sha)>
 #[tauri::command]
@@ -205,8 +353,66 @@ pub async fn get_skill_plugin_upgrade_candidates(
         .map_err(|e| e.to_string())
 }
 
+/// 计算一次增量扫描计划：`changed_files` 为 `None` 表示应当退化为全量扫描
+/// （不是 git 仓库、从未扫描过、或工作区有未提交改动导致 diff 无法反映磁盘
+/// 上的真实变化），为 `Some(vec![])` 表示自上次扫描以来 commit 未变，可以
+/// 完全跳过重新扫描；`current_head` 是本次扫描结束后应当存入
+/// `Plugin::scanned_commit_sha` 的新基线（非 git 仓库时为 `None`，保持原样）。
+fn incremental_scan_plan(
+    install_path: &Path,
+    since_sha: Option<&str>,
+) -> (Option<Vec<String>>, Option<String>) {
+    let Some(path_str) = install_path.to_str() else {
+        return (None, None);
+    };
+
+    let Some(current_head) = git_output(&["-C", path_str, "rev-parse", "HEAD"])
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+    else {
+        return (None, None);
+    };
+
+    let Some(since_sha) = since_sha.filter(|s| !s.is_empty()) else {
+        return (None, Some(current_head));
+    };
+
+    if since_sha == current_head {
+        return (Some(Vec::new()), Some(current_head));
+    }
+
+    let dirty = git_output(&["-C", path_str, "status", "--porcelain"])
+        .map(|s| !s.trim().is_empty())
+        .unwrap_or(true);
+    if dirty {
+        return (None, Some(current_head));
+    }
+
+    match git_output(&["-C", path_str, "diff", "--name-only", since_sha, &current_head]) {
+        Ok(diff) => (
+            Some(
+                diff.lines()
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .collect(),
+            ),
+            Some(current_head),
+        ),
+        // `since_sha` 很可能已经不在历史里了（例如 marketplace 被 rebase/squash 过）
+        Err(_) => (None, Some(current_head)),
+    }
+}
+
 /// 安全扫描所有已安装 plugins（读取 Claude CLI 提供的 installPath）
 ///
+/// 只要 `claude_install_path` 是一个干净的 git checkout 且上次扫描留下了
+/// `scanned_commit_sha`，就通过 [`incremental_scan_plan`] 把扫描范围收窄到
+/// 自那之后变更过的文件，再用 [`SecurityScanner::merge_incremental_report`]
+/// 把结果与上次扫描留下的 `report_json` 合并；不满足条件时（非 git 仓库、
+/// 首次扫描、工作区有未提交改动）退化为与之前一致的全量扫描。
+/// 这让大型 marketplace 里大多数未改动的 plugin 不必每次都重新扫描全部文件。
+///
 /// 返回：成功扫描的 plugin_id 列表（数据库 id）
 #[tauri::command]
 pub async fn scan_all_installed_plugins(
@@ -236,6 +442,10 @@ pub async fn scan_all_installed_plugins(
 
     let db = state.db.clone();
     let locale_owned = locale.to_string();
+    let advisory_db = db
+        .get_advisory_db_cache()
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(AdvisoryDb::embedded);
 
     let pool = ThreadPoolBuilder::new()
         .num_threads(parallelism)
@@ -254,52 +464,89 @@ pub async fn scan_all_installed_plugins(
                     return None;
                 }
 
-                let scanner = SecurityScanner::new();
-                let report = match scanner.scan_directory_with_options(
-                    path.to_str().unwrap_or(""),
-                    &plugin.id,
-                    &locale_owned,
-                    ScanOptions { skip_readme: true },
-                    None,
-                ) {
-                    Ok(report) => report,
-                    Err(e) => {
-                        log::warn!("Failed to scan plugin {}: {}", plugin.name, e);
-                        return None;
+                let scanner = SecurityScanner::new().with_advisory_db(advisory_db.clone());
+
+                let (changed_files, current_head) =
+                    incremental_scan_plan(&path, plugin.scanned_commit_sha.as_deref());
+                let cached_report: Option<SecurityReport> = plugin
+                    .report_json
+                    .as_deref()
+                    .and_then(|json| serde_json::from_str(json).ok());
+
+                let report = match (&changed_files, &cached_report) {
+                    // commit 未变且有缓存的完整报告：直接复用，跳过重新扫描
+                    (Some(changed), Some(old)) if changed.is_empty() => old.clone(),
+                    _ => {
+                        let only_files = changed_files.as_deref().filter(|c| !c.is_empty());
+                        let fresh = match scanner.scan_directory_with_options(
+                            path.to_str().unwrap_or(""),
+                            &plugin.id,
+                            &locale_owned,
+                            ScanOptions { skip_readme: true },
+                            only_files,
+                            None,
+                        ) {
+                            Ok(report) => report,
+                            Err(e) => {
+                                log::warn!("Failed to scan plugin {}: {}", plugin.name, e);
+                                return None;
+                            }
+                        };
+
+                        match (only_files, &cached_report) {
+                            (Some(changed), Some(old)) => {
+                                scanner.merge_incremental_report(old, &fresh, changed)
+                            }
+                            _ => fresh,
+                        }
                     }
                 };
 
                 let mut updated = plugin.clone();
                 updated.security_score = Some(report.score);
                 updated.security_level = Some(report.level.as_str().to_string());
-                updated.security_issues = Some(
-                    report
-                        .issues
-                        .iter()
-                        .map(|i| {
-                            let file_info = i
-                                .file_path
-                                .as_ref()
-                                .map(|f| format!("[{}] ", f))
-                                .unwrap_or_default();
-                            format!("{}{:?}: {}", file_info, i.severity, i.description)
-                        })
-                        .collect(),
-                );
+                let mut issues: Vec<String> = report
+                    .issues
+                    .iter()
+                    .map(|i| {
+                        let file_info = i
+                            .file_path
+                            .as_ref()
+                            .map(|f| format!("[{}] ", f))
+                            .unwrap_or_default();
+                        format!("{}{:?}: {}", file_info, i.severity, i.description)
+                    })
+                    .collect();
+                if let (Some(pinned), Some(head)) = (plugin.revision.as_deref(), current_head.as_deref()) {
+                    if pinned != head {
+                        issues.push(format!(
+                            "[revision drift] 固定的 revision（{}）与本次扫描到的 commit（{}）不一致，扫描结果可能不是针对固定版本产生的",
+                            pinned, head
+                        ));
+                    }
+                }
+                if let Some(capabilities) = updated.capabilities.as_ref() {
+                    issues.extend(capabilities.undeclared(&report.capabilities));
+                }
+                updated.security_issues = Some(issues);
                 updated.scanned_at = Some(Utc::now());
+                updated.report_json = serde_json::to_string(&report).ok();
+                updated.scanned_commit_sha = current_head.or(updated.scanned_commit_sha);
 
                 if let Err(e) = db.save_plugin(&updated) {
                     log::warn!("Failed to save plugin {}: {}", updated.name, e);
                     return None;
                 }
 
-                Some((index, updated.id.clone()))
+                Some((index, updated))
             })
-            .collect::<Vec<(usize, String)>>()
+            .collect::<Vec<(usize, Plugin)>>()
     });
 
     scanned.sort_by_key(|(index, _)| *index);
-    Ok(scanned.into_iter().map(|(_, id)| id).collect())
+    let updated_plugins: Vec<Plugin> = scanned.iter().map(|(_, p)| p.clone()).collect();
+    state.plugin_store.save_all(&updated_plugins);
+    Ok(scanned.into_iter().map(|(_, p)| p.id).collect())
 }
 
 /// 安全扫描单个已安装 plugin（用于前端展示扫描进度）
@@ -343,7 +590,12 @@ pub async fn scan_installed_plugin(
         return Err(format!("Plugin directory does not exist: {}", install_path));
     }
 
-    let scanner = SecurityScanner::new();
+    let advisory_db = state
+        .db
+        .get_advisory_db_cache()
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(AdvisoryDb::embedded);
+    let scanner = SecurityScanner::new().with_advisory_db(advisory_db);
     let report = if let Some(scan_id) = scan_id.filter(|id| !id.is_empty()) {
         let app_handle = app.clone();
         let item_id = plugin.id.clone();
@@ -363,6 +615,7 @@ pub async fn scan_installed_plugin(
                 &plugin.id,
                 &locale,
                 ScanOptions { skip_readme: true },
+                None,
                 Some(&mut progress_cb),
             )
             .map_err(|e| e.to_string())?
@@ -374,32 +627,184 @@ pub async fn scan_installed_plugin(
                 &locale,
                 ScanOptions { skip_readme: true },
                 None,
+                None,
             )
             .map_err(|e| e.to_string())?
     };
 
     plugin.security_score = Some(report.score);
     plugin.security_level = Some(report.level.as_str().to_string());
-    plugin.security_issues = Some(
-        report
-            .issues
-            .iter()
-            .map(|i| {
-                let file_info = i
-                    .file_path
-                    .as_ref()
-                    .map(|f| format!("[{}] ", f))
-                    .unwrap_or_default();
-                format!("{}{:?}: {}", file_info, i.severity, i.description)
-            })
-            .collect(),
-    );
+    let mut issues: Vec<String> = report
+        .issues
+        .iter()
+        .map(|i| {
+            let file_info = i
+                .file_path
+                .as_ref()
+                .map(|f| format!("[{}] ", f))
+                .unwrap_or_default();
+            format!("{}{:?}: {}", file_info, i.severity, i.description)
+        })
+        .collect();
+    if let Some(capabilities) = plugin.capabilities.as_ref() {
+        issues.extend(capabilities.undeclared(&report.capabilities));
+    }
+    plugin.security_issues = Some(issues);
     plugin.scanned_at = Some(Utc::now());
 
     state
         .db
         .save_plugin(&plugin)
         .map_err(|e| format!("Failed to save plugin: {}", e))?;
+    state.plugin_store.save(&plugin);
 
     Ok(plugin.id)
 }
+
+/// [`plugin_capabilities_list`] 的返回值：声明/增补/撤销叠加后的能力清单，
+/// 连同展开的有效能力条目和人类可读摘要，供安装/启用前的审查界面直接渲染
+#[derive(Serialize)]
+pub struct PluginCapabilitiesView {
+    declared: PluginCapabilities,
+    effective: Vec<String>,
+    summary: String,
+}
+
+fn find_plugin(state: &State<'_, AppState>, plugin_id: &str) -> Result<Plugin, String> {
+    state
+        .db
+        .get_plugins()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|p| p.id == plugin_id)
+        .ok_or_else(|| "Plugin not found".to_string())
+}
+
+/// 查看某个 plugin 当前声明 + 用户增补/撤销之后的有效能力清单
+#[tauri::command]
+pub async fn plugin_capabilities_list(
+    state: State<'_, AppState>,
+    plugin_id: String,
+) -> Result<PluginCapabilitiesView, String> {
+    let plugin = find_plugin(&state, &plugin_id)?;
+    let declared = plugin.capabilities.unwrap_or_default();
+    Ok(PluginCapabilitiesView {
+        summary: declared.summary(),
+        effective: declared.effective(),
+        declared,
+    })
+}
+
+/// 给某个 plugin 额外授予一条 manifest 未声明的能力（`fs:<path>` / `net:<host>` / `shell`）。
+/// 如果这条能力之前被撤销过，授予会把撤销记录一并清掉
+#[tauri::command]
+pub async fn plugin_capability_add(
+    state: State<'_, AppState>,
+    plugin_id: String,
+    capability: String,
+) -> Result<PluginCapabilities, String> {
+    let mut plugin = find_plugin(&state, &plugin_id)?;
+    let mut capabilities = plugin.capabilities.take().unwrap_or_default();
+    capabilities.revoked.retain(|entry| entry != &capability);
+    if !capabilities.granted_extra.contains(&capability) {
+        capabilities.granted_extra.push(capability);
+    }
+    plugin.capabilities = Some(capabilities.clone());
+    state.db.save_plugin(&plugin).map_err(|e| e.to_string())?;
+    state.plugin_store.save(&plugin);
+    Ok(capabilities)
+}
+
+/// 撤销某个 plugin 的一条能力，无论它来自 manifest 声明还是此前的额外授予，
+/// 用户可以在安装/启用前把不需要的能力裁剪掉
+#[tauri::command]
+pub async fn plugin_capability_remove(
+    state: State<'_, AppState>,
+    plugin_id: String,
+    capability: String,
+) -> Result<PluginCapabilities, String> {
+    let mut plugin = find_plugin(&state, &plugin_id)?;
+    let mut capabilities = plugin.capabilities.take().unwrap_or_default();
+    capabilities.granted_extra.retain(|entry| entry != &capability);
+    if !capabilities.revoked.contains(&capability) {
+        capabilities.revoked.push(capability);
+    }
+    plugin.capabilities = Some(capabilities.clone());
+    state.db.save_plugin(&plugin).map_err(|e| e.to_string())?;
+    state.plugin_store.save(&plugin);
+    Ok(capabilities)
+}
+
+/// 把某个 plugin 固定跟踪到指定分支或 tag/commit（`branch`/`revision` 二选
+/// 一，都传 `None` 则恢复跟踪默认分支）。保存前调用 [`Plugin::validate_source`]
+/// 校验两者互斥且取值非空，拒绝不合法的组合而不是静默接受。
+#[tauri::command]
+pub async fn set_plugin_source_pin(
+    state: State<'_, AppState>,
+    plugin_id: String,
+    branch: Option<String>,
+    revision: Option<String>,
+) -> Result<Plugin, String> {
+    let mut plugin = find_plugin(&state, &plugin_id)?;
+    plugin.branch = branch;
+    plugin.revision = revision;
+    plugin.validate_source()?;
+
+    state.db.save_plugin(&plugin).map_err(|e| e.to_string())?;
+    state.plugin_store.save(&plugin);
+    Ok(plugin)
+}
+
+/// 导出当前已安装 plugins/marketplaces 的锁定清单（guard.lock 的内容），
+/// 供前端存成文件或在另一台机器上通过 [`import_plugin_lockfile`] 复现
+#[tauri::command]
+pub async fn export_plugin_lockfile(
+    state: State<'_, AppState>,
+    claude_command: Option<String>,
+) -> Result<PluginStateManifest, String> {
+    let manager = state.plugin_manager.lock().await;
+    manager.export_state(claude_command).await.map_err(|e| e.to_string())
+}
+
+/// 按一份 [`export_plugin_lockfile`] 产出的清单重建环境；`allow_update` 为
+/// `false`（默认）时严格锁定到清单记录的提交，对方仓库已变化则该条目失败，
+/// 传 `true` 则放弃锁定、安装当前最新版本
+#[tauri::command]
+pub async fn import_plugin_lockfile(
+    state: State<'_, AppState>,
+    manifest: PluginStateManifest,
+    claude_command: Option<String>,
+    allow_update: Option<bool>,
+) -> Result<PluginStateImportResult, String> {
+    let manager = state.plugin_manager.lock().await;
+    manager
+        .import_state(&manifest, claude_command, allow_update.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 加载一份按 marketplace 名称索引的受信任签名者配置（YAML 格式，参见
+/// [`crate::models::MarketplaceTrustConfig`]），之后每次
+/// `confirm_plugin_installation` 都会对配置中出现的 marketplace 校验提交
+/// 签名，未通过则拒绝安装并记 `install_status = "signature_failed"`；未出现
+/// 在配置里的 marketplace 不受影响，照常安装
+#[tauri::command]
+pub async fn set_marketplace_trust_config(
+    state: State<'_, AppState>,
+    config_path: String,
+) -> Result<(), String> {
+    let config = crate::security::signing::load_trust_config_file(Path::new(&config_path))
+        .map_err(|e| e.to_string())?;
+
+    let manager = state.plugin_manager.lock().await;
+    manager.set_trust_config(config);
+    Ok(())
+}
+
+/// 清除已加载的签名校验配置，恢复为不对任何 marketplace 做签名校验
+#[tauri::command]
+pub async fn clear_marketplace_trust_config(state: State<'_, AppState>) -> Result<(), String> {
+    let manager = state.plugin_manager.lock().await;
+    manager.clear_trust_config();
+    Ok(())
+}
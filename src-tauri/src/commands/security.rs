@@ -1,128 +1,221 @@
 use crate::commands::AppState;
-use crate::models::security::{SecurityReport, SkillScanResult, SecurityLevel};
+use crate::models::security::{CapabilityManifest, SecurityReport, SkillScanResult, SecurityLevel};
 use crate::models::Skill;
-use crate::security::SecurityScanner;
+use crate::security::{SecurityRules, SecurityScanner};
 use anyhow::Result;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use serde::Serialize;
 use std::path::PathBuf;
-use tauri::State;
+use std::sync::mpsc;
+use std::thread;
+use tauri::{AppHandle, Emitter, State};
+
+const SKILL_SCAN_PARALLELISM: usize = 4;
+
+#[derive(Serialize, Clone)]
+struct SkillScanProgressEvent {
+    files_checked: usize,
+    files_to_check: usize,
+    current_skill: String,
+}
 
 /// 扫描所有已安装的 skills
+///
+/// 扫描任务被划分到一个 rayon 线程池并行执行：每个 worker 通过读锁共享
+/// 当前生效的 `SecurityScanner`、读取 SKILL.md、执行 `scan_installed_skill_dir`，并把结果通过 channel
+/// 发回主线程。主线程一边接收一边发出 `skill-scan-progress` 事件，
+/// 供前端渲染实时进度条；所有结果收集完毕后一次性批量写入数据库。
+///
+/// 扫描前会先计算 SKILL.md 内容的 SHA-256 校验和：若与上次扫描时存储的
+/// `content_checksum` 一致且已有历史评分，则跳过实际扫描，直接复用缓存结果；
+/// 传入 `force = true` 可以绕过该缓存，强制重新扫描所有 skill。
 #[tauri::command]
 pub async fn scan_all_installed_skills(
     state: State<'_, AppState>,
+    app: AppHandle,
+    force: Option<bool>,
 ) -> Result<Vec<SkillScanResult>, String> {
+    let force = force.unwrap_or(false);
     let skills = state.db.get_skills().map_err(|e| e.to_string())?;
     let installed_skills: Vec<Skill> = skills.into_iter()
         .filter(|s| s.installed && s.local_path.is_some())
         .collect();
 
-    let scanner = SecurityScanner::new();
-    let mut results = Vec::new();
+    let files_to_check = installed_skills.len();
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(SKILL_SCAN_PARALLELISM)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let security_scanner = state.security_scanner.clone();
+    let (tx, rx) = mpsc::channel::<(Skill, SkillScanResult)>();
+
+    let app_for_progress = app.clone();
+    let collector = thread::spawn(move || {
+        let mut updated_skills = Vec::with_capacity(files_to_check);
+        let mut results = Vec::with_capacity(files_to_check);
+        let mut files_checked = 0usize;
+
+        while let Ok((skill, result)) = rx.recv() {
+            files_checked += 1;
+            let _ = app_for_progress.emit(
+                "skill-scan-progress",
+                SkillScanProgressEvent {
+                    files_checked,
+                    files_to_check,
+                    current_skill: skill.name.clone(),
+                },
+            );
+            updated_skills.push(skill);
+            results.push(result);
+        }
 
-    for mut skill in installed_skills {
-        if let Some(local_path) = &skill.local_path {
+        (updated_skills, results)
+    });
+
+    pool.install(|| {
+        installed_skills.into_par_iter().for_each_with(tx, |tx, mut skill| {
+            let Some(local_path) = skill.local_path.clone() else { return };
             // local_path 是目录路径，需要拼接 SKILL.md 文件名
-            let skill_file_path = PathBuf::from(local_path).join("SKILL.md");
-
-            if let Ok(content) = std::fs::read_to_string(&skill_file_path) {
-                match scanner.scan_file(&content, &skill.id) {
-                    Ok(report) => {
-                        // 更新 skill 的安全信息
-                        skill.security_score = Some(report.score);
-                        skill.security_level = Some(report.level.as_str().to_string());
-                        skill.security_issues = Some(
-                            report.issues.iter()
-                                .map(|i| i.description.clone())
-                                .collect()
-                        );
-                        skill.scanned_at = Some(chrono::Utc::now());
-
-                        // 保存到数据库
-                        if let Err(e) = state.db.save_skill(&skill) {
-                            eprintln!("Failed to save skill {}: {}", skill.name, e);
-                        }
-
-                        results.push(SkillScanResult {
-                            skill_id: skill.id.clone(),
-                            skill_name: skill.name.clone(),
-                            score: report.score,
-                            level: report.level.as_str().to_string(),
-                            scanned_at: chrono::Utc::now().to_rfc3339(),
-                            report,
-                        });
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to scan skill {}: {}", skill.name, e);
-                    }
-                }
+            let skill_file_path = PathBuf::from(&local_path).join("SKILL.md");
+
+            let Ok(content) = std::fs::read_to_string(&skill_file_path) else { return };
+
+            let Ok(scanner) = security_scanner.read() else { return };
+            let new_checksum = scanner.calculate_checksum(content.as_bytes());
+
+            let unchanged = !force
+                && skill.security_score.is_some()
+                && skill.content_checksum.as_deref() == Some(new_checksum.as_str());
+
+            if unchanged {
+                let result = cached_scan_result(&skill);
+                let _ = tx.send((skill, result));
+                return;
             }
-        }
-    }
+
+            let report = match scanner.scan_installed_skill_dir(&content, &PathBuf::from(&local_path), &skill.id) {
+                Ok(report) => report,
+                Err(e) => {
+                    log::warn!("Failed to scan skill {}: {}", skill.name, e);
+                    return;
+                }
+            };
+
+            skill.security_score = Some(report.score);
+            skill.security_level = Some(report.level.as_str().to_string());
+            skill.security_issues = Some(
+                report.issues.iter()
+                    .map(|i| i.description.clone())
+                    .collect()
+            );
+            skill.scanned_at = Some(chrono::Utc::now());
+            skill.capability_manifest = Some(report.capabilities.clone());
+            skill.content_checksum = Some(new_checksum);
+            skill.report_json = serde_json::to_string(&report).ok();
+
+            let result = SkillScanResult {
+                skill_id: skill.id.clone(),
+                skill_name: skill.name.clone(),
+                score: report.score,
+                level: report.level.as_str().to_string(),
+                scanned_at: skill.scanned_at
+                    .map(|d| d.to_rfc3339())
+                    .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+                report,
+            };
+
+            let _ = tx.send((skill, result));
+        });
+    });
+
+    let (updated_skills, results) = collector.join()
+        .map_err(|_| "Skill scan collector thread panicked".to_string())?;
+
+    state.db.save_skills_batch(&updated_skills).map_err(|e| e.to_string())?;
 
     Ok(results)
 }
 
+/// 根据 skill 中已持久化的扫描结果重建一份 `SkillScanResult`，供"跳过未变化
+/// 的 skill"以及 `get_scan_results` 复用，避免各处重复解析逻辑。
+///
+/// 直接反序列化 `report_json` 里保存的完整 `SecurityReport`，字段（`category`/
+/// `line_number`/`code_snippet`/`recommendations`/`blocked`/`hard_trigger_issues`）
+/// 与刚扫描完一致。只有在 `report_json` 缺失（例如升级前遗留的、从未用新代码
+/// 重新扫描过的记录）时才退化为一份只有评分/能力清单、没有具体 issue 的报告。
+fn cached_scan_result(skill: &Skill) -> SkillScanResult {
+    let report = skill
+        .report_json
+        .as_deref()
+        .and_then(|json| serde_json::from_str::<SecurityReport>(json).ok())
+        .unwrap_or_else(|| SecurityReport {
+            skill_id: skill.id.clone(),
+            score: skill.security_score.unwrap_or(0),
+            level: SecurityLevel::from_score(skill.security_score.unwrap_or(0)),
+            issues: vec![],
+            recommendations: vec![],
+            blocked: false,
+            hard_trigger_issues: vec![],
+            capabilities: skill.capability_manifest.clone().unwrap_or_default(),
+            scanned_files: vec![],
+            partial_scan: false,
+            skipped_files: vec![],
+            advisory_db_version: None,
+        });
+
+    SkillScanResult {
+        skill_id: skill.id.clone(),
+        skill_name: skill.name.clone(),
+        score: skill.security_score.unwrap_or(0),
+        level: skill.security_level.clone().unwrap_or_else(|| "Unknown".to_string()),
+        scanned_at: skill.scanned_at.map(|d| d.to_rfc3339()).unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+        report,
+    }
+}
+
 /// 获取缓存的扫描结果
 #[tauri::command]
 pub async fn get_scan_results(
     state: State<'_, AppState>,
 ) -> Result<Vec<SkillScanResult>, String> {
-    use crate::models::security::{SecurityIssue, IssueSeverity, IssueCategory};
-
     let skills = state.db.get_skills().map_err(|e| e.to_string())?;
 
     let results: Vec<SkillScanResult> = skills.into_iter()
         .filter(|s| s.installed && s.security_score.is_some())
-        .map(|s| {
-            // 解析 security_issues 字符串为 SecurityIssue 对象
-            let issues = if let Some(issue_strings) = &s.security_issues {
-                issue_strings.iter().filter_map(|issue_str| {
-                    // 解析格式: "Severity: description"
-                    let parts: Vec<&str> = issue_str.splitn(2, ": ").collect();
-                    if parts.len() == 2 {
-                        let severity = match parts[0] {
-                            "Critical" => IssueSeverity::Critical,
-                            "Error" => IssueSeverity::Error,
-                            "Warning" => IssueSeverity::Warning,
-                            _ => IssueSeverity::Info,
-                        };
-                        Some(SecurityIssue {
-                            severity,
-                            category: IssueCategory::Other,
-                            description: parts[1].to_string(),
-                            line_number: None,
-                            code_snippet: None,
-                        })
-                    } else {
-                        None
-                    }
-                }).collect()
-            } else {
-                vec![]
-            };
+        .map(|s| cached_scan_result(&s))
+        .collect();
 
-            let report = SecurityReport {
-                skill_id: s.id.clone(),
-                score: s.security_score.unwrap_or(0),
-                level: SecurityLevel::from_score(s.security_score.unwrap_or(0)),
-                issues,
-                recommendations: vec![], // 建议信息暂时为空，未来可以存储到数据库
-                blocked: false,
-                hard_trigger_issues: vec![],
-            };
+    Ok(results)
+}
 
-            SkillScanResult {
-                skill_id: s.id.clone(),
-                skill_name: s.name.clone(),
-                score: s.security_score.unwrap_or(0),
-                level: s.security_level.clone().unwrap_or_else(|| "Unknown".to_string()),
-                scanned_at: s.scanned_at.map(|d| d.to_rfc3339()).unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
-                report,
-            }
-        })
+/// 把当前缓存的扫描结果导出为 SARIF 2.1.0 JSON（序列化后的字符串），
+/// 供 GitHub code scanning 或其他静态分析看板消费。
+///
+/// 传入 `skill_id` 只导出该 skill 的报告；留空则导出所有已扫描过的
+/// 已安装 skill。没有任何匹配结果时返回错误而不是一份空的 SARIF 日志。
+#[tauri::command]
+pub async fn export_scan_results_sarif(
+    state: State<'_, AppState>,
+    skill_id: Option<String>,
+) -> Result<String, String> {
+    let skills = state.db.get_skills().map_err(|e| e.to_string())?;
+
+    let reports: Vec<SecurityReport> = skills
+        .into_iter()
+        .filter(|s| s.installed && s.security_score.is_some())
+        .filter(|s| skill_id.as_deref().map_or(true, |id| s.id == id))
+        .map(|s| cached_scan_result(&s).report)
         .collect();
 
-    Ok(results)
+    if reports.is_empty() {
+        return Err("No scan results available to export".to_string());
+    }
+
+    let sarif = crate::security::sarif::build_sarif_log(&reports);
+    serde_json::to_string_pretty(&sarif).map_err(|e| e.to_string())
 }
 
 /// 扫描单个 skill 文件（用于安装前检查）
@@ -136,9 +229,13 @@ pub async fn get_scan_results(
 /// 返回包含安全评分、等级和问题列表的 SecurityReport
 #[tauri::command]
 pub async fn scan_skill_archive(
+    state: State<'_, AppState>,
     archive_path: String,
 ) -> Result<SecurityReport, String> {
-    let scanner = SecurityScanner::new();
+    let scanner = state
+        .security_scanner
+        .read()
+        .map_err(|_| "Security scanner lock poisoned".to_string())?;
 
     // 验证文件存在性
     let path = std::path::Path::new(&archive_path);
@@ -149,6 +246,12 @@ pub async fn scan_skill_archive(
         return Err(format!("Path is not a file: {}", archive_path));
     }
 
+    if is_zip_archive(path) {
+        let report = scanner.scan_archive(path)
+            .map_err(|e| format!("Failed to scan archive '{}': {}", archive_path, e))?;
+        return Ok(report);
+    }
+
     // 读取文件内容
     let content = std::fs::read_to_string(path)
         .map_err(|e| format!("Failed to read skill file '{}': {}", archive_path, e))?;
@@ -158,3 +261,71 @@ pub async fn scan_skill_archive(
 
     Ok(report)
 }
+
+/// 判断路径是否应当按 zip 包处理：先看扩展名，再嗅探 "PK\x03\x04" 魔数，
+/// 兼容没有 `.zip` 后缀但实际是 zip 格式的文件。
+fn is_zip_archive(path: &std::path::Path) -> bool {
+    if path.extension().and_then(|e| e.to_str()).map(|e| e.eq_ignore_ascii_case("zip")).unwrap_or(false) {
+        return true;
+    }
+
+    let Ok(mut file) = std::fs::File::open(path) else { return false };
+    let mut magic = [0u8; 4];
+    use std::io::Read;
+    file.read_exact(&mut magic).is_ok() && magic == [0x50, 0x4B, 0x03, 0x04]
+}
+
+/// 获取某个 skill 最近一次扫描推断出的能力清单
+///
+/// 用于安装前向用户展示"此 skill 想要：执行 shell 命令、访问网络"等提示。
+/// 若该 skill 尚未扫描过，返回 `None`。
+#[tauri::command]
+pub async fn get_skill_capability_manifest(
+    skill_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<CapabilityManifest>, String> {
+    let skills = state.db.get_skills().map_err(|e| e.to_string())?;
+
+    let manifest = skills.into_iter()
+        .find(|s| s.id == skill_id)
+        .and_then(|s| s.capability_manifest);
+
+    Ok(manifest)
+}
+
+/// 加载一份用户自定义规则包（YAML 格式，参见 [`crate::models::security::RulePackConfig`]），
+/// 与内置规则合并后替换当前生效的安全扫描器。
+///
+/// 组织可以用这种方式补充自己的检测策略（内部域名黑名单、密钥命名约定等），
+/// 或者按类别关闭某些内置规则，而无需重新编译应用。后续的 skill 扫描命令
+/// （`scan_all_installed_skills`、`scan_skill_archive`）都会使用新规则；plugin
+/// 扫描命令（`scan_all_installed_plugins`、`scan_installed_plugin`）各自构造只含
+/// 内置规则的扫描器，不使用这里加载的规则包。
+#[tauri::command]
+pub async fn set_security_rule_pack(
+    state: State<'_, AppState>,
+    rule_pack_path: String,
+) -> Result<(), String> {
+    let pack = SecurityRules::load_rule_pack_file(std::path::Path::new(&rule_pack_path))
+        .map_err(|e| e.to_string())?;
+
+    let mut scanner = state
+        .security_scanner
+        .write()
+        .map_err(|_| "Security scanner lock poisoned".to_string())?;
+    *scanner = SecurityScanner::with_rule_pack(pack);
+
+    Ok(())
+}
+
+/// 清除已加载的自定义规则包，恢复为仅内置规则的扫描器
+#[tauri::command]
+pub async fn clear_security_rule_pack(state: State<'_, AppState>) -> Result<(), String> {
+    let mut scanner = state
+        .security_scanner
+        .write()
+        .map_err(|_| "Security scanner lock poisoned".to_string())?;
+    *scanner = SecurityScanner::new();
+
+    Ok(())
+}
@@ -4,10 +4,51 @@ pub mod services;
 pub mod commands;
 
 use commands::AppState;
-use commands::security::{scan_all_installed_skills, get_scan_results, scan_skill_archive};
-use services::{Database, SkillManager};
-use std::sync::Arc;
-use tauri::Manager;
+use commands::security::{
+    scan_all_installed_skills, get_scan_results, scan_skill_archive, get_skill_capability_manifest,
+    set_security_rule_pack, clear_security_rule_pack, export_scan_results_sarif,
+};
+use commands::plugins::{
+    get_plugins,
+    get_cached_plugins,
+    prepare_plugin_installation,
+    confirm_plugin_installation,
+    prepare_local_plugin_installation,
+    confirm_local_plugin_installation,
+    prepare_git_plugin_installation,
+    get_operation_log,
+    cancel_plugin_installation,
+    uninstall_plugin,
+    remove_marketplace,
+    get_claude_marketplaces,
+    check_plugins_updates,
+    get_cached_update_status,
+    update_plugin,
+    check_marketplaces_updates,
+    update_marketplace,
+    run_plugin_lifecycle_hook,
+    get_plugin_lockfile_info,
+    get_skill_plugin_upgrade_candidates,
+    scan_all_installed_plugins,
+    scan_installed_plugin,
+    list_marketplace_plugins,
+    search_plugins,
+    export_plugin_lockfile,
+    import_plugin_lockfile,
+    set_marketplace_trust_config,
+    clear_marketplace_trust_config,
+    plugin_capabilities_list,
+    plugin_capability_add,
+    plugin_capability_remove,
+    set_plugin_source_pin,
+};
+use commands::featured_marketplaces::{get_featured_marketplaces, refresh_featured_marketplaces};
+use commands::advisory_db::{get_advisory_db_version, refresh_advisory_db};
+use commands::diagnostics::get_diagnostics;
+use security::SecurityScanner;
+use services::{Database, PluginManager, SkillManager};
+use std::sync::{Arc, RwLock};
+use tauri::{Emitter, Manager};
 use tauri::menu::{MenuBuilder, MenuItemBuilder};
 use tauri::tray::{TrayIconBuilder, MouseButton, MouseButtonState};
 use tokio::sync::Mutex;
@@ -131,14 +172,30 @@ pub fn run() {
             let skill_manager = SkillManager::new(Arc::clone(&db));
             let skill_manager = Arc::new(Mutex::new(skill_manager));
 
+            // 初始化 PluginManager（marketplace/plugin 安装流程）
+            let operation_log_dir = app_dir.join("operation_logs");
+            let plugin_manager = PluginManager::new(Arc::clone(&db), operation_log_dir);
+            let plugin_manager = Arc::new(Mutex::new(plugin_manager));
+
             // 初始化 GitHub 服务
             let github = Arc::new(services::GitHubService::new());
 
+            let app_handle = app.handle().clone();
+
+            // 初始化 Plugin 快照缓存（tauri-plugin-store），供前端快速重新加载
+            let plugin_store = Arc::new(
+                services::PluginStore::new(&app_handle)
+                    .expect("Failed to initialize plugin snapshot store"),
+            );
+
             // 设置应用状态
             app.manage(AppState {
                 db,
                 skill_manager,
+                plugin_manager,
                 github,
+                security_scanner: Arc::new(RwLock::new(SecurityScanner::new())),
+                plugin_store,
             });
 
             // 初始化系统托盘
@@ -146,7 +203,6 @@ pub fn run() {
                 .ok_or("无法获取默认窗口图标")?
                 .clone();
 
-            let app_handle = app.handle();
             let menu = create_tray_menu(&app_handle)?;
 
             let tray = TrayIconBuilder::new()
@@ -160,18 +216,56 @@ pub fn run() {
             // 存储托盘实例到 app state
             app.manage(tray);
 
+            // 启动后台更新检查任务：周期性刷新 plugins/marketplaces 的可用版本缓存
+            // 并通过 `updates-available` 事件通知前端；任务本身在独立的异步任务中
+            // 运行，句柄立即返回，不会拖慢 `get_plugins` 等同步命令
+            let update_checker_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+                let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+                loop {
+                    ticker.tick().await;
+
+                    let state = update_checker_handle.state::<AppState>();
+                    let manager = state.plugin_manager.lock().await;
+                    match manager.refresh_update_status(None).await {
+                        Ok(notifications) if !notifications.is_empty() => {
+                            if let Err(e) = update_checker_handle.emit("updates-available", &notifications) {
+                                log::warn!("推送更新提醒事件失败: {}", e);
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => log::warn!("后台更新检查失败: {}", e),
+                    }
+                }
+            });
+
+            // 监听精选 marketplace 缓存文件，变化时重新解析并推送给前端，
+            // 避免用户需要切换页面才能看到后台刷新/手动编辑之后的最新数据
+            match commands::featured_marketplaces::featured_marketplaces_cache_path(&app_handle) {
+                Ok(cache_path) => {
+                    services::spawn_featured_marketplaces_watcher(app_handle.clone(), cache_path);
+                }
+                Err(e) => log::warn!("无法启动精选 marketplace 缓存监听: {}", e),
+            }
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::add_repository,
             commands::get_repositories,
+            commands::get_stats,
             commands::delete_repository,
             commands::scan_repository,
             commands::get_skills,
+            commands::search_skills,
             commands::get_installed_skills,
             commands::install_skill,
+            commands::check_skill_update,
             commands::uninstall_skill,
             commands::delete_skill,
+            commands::verify_installations,
+            commands::repair_installations,
             commands::scan_local_skills,
             commands::clear_repository_cache,
             commands::refresh_repository_cache,
@@ -179,6 +273,47 @@ pub fn run() {
             scan_all_installed_skills,
             get_scan_results,
             scan_skill_archive,
+            get_skill_capability_manifest,
+            set_security_rule_pack,
+            clear_security_rule_pack,
+            export_scan_results_sarif,
+            get_plugins,
+            get_cached_plugins,
+            prepare_plugin_installation,
+            confirm_plugin_installation,
+            prepare_local_plugin_installation,
+            confirm_local_plugin_installation,
+            prepare_git_plugin_installation,
+            get_operation_log,
+            cancel_plugin_installation,
+            uninstall_plugin,
+            remove_marketplace,
+            get_claude_marketplaces,
+            check_plugins_updates,
+            get_cached_update_status,
+            update_plugin,
+            check_marketplaces_updates,
+            update_marketplace,
+            run_plugin_lifecycle_hook,
+            get_plugin_lockfile_info,
+            get_skill_plugin_upgrade_candidates,
+            scan_all_installed_plugins,
+            scan_installed_plugin,
+            list_marketplace_plugins,
+            search_plugins,
+            export_plugin_lockfile,
+            import_plugin_lockfile,
+            set_marketplace_trust_config,
+            clear_marketplace_trust_config,
+            plugin_capabilities_list,
+            plugin_capability_add,
+            plugin_capability_remove,
+            set_plugin_source_pin,
+            get_featured_marketplaces,
+            refresh_featured_marketplaces,
+            get_advisory_db_version,
+            refresh_advisory_db,
+            get_diagnostics,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
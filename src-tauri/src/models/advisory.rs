@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::security::IssueSeverity;
+
+/// CVE 公告的严重程度，与生态系统通用的 critical/high/medium/low 四级对齐，
+/// 独立于 [`IssueSeverity`]（后者是扫描规则自身的严重程度分级），
+/// 通过 [`VulnSeverity::to_issue_severity`] 映射过去参与评分。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VulnSeverity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl VulnSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VulnSeverity::Low => "low",
+            VulnSeverity::Medium => "medium",
+            VulnSeverity::High => "high",
+            VulnSeverity::Critical => "critical",
+        }
+    }
+
+    /// 映射到 [`IssueSeverity`]，使漏洞依赖问题复用既有的
+    /// `calculate_score`/`hard_trigger_summaries` 逻辑：critical 会和其它
+    /// `ProcessExecution`/`DataExfiltration` 一样触发安装阻断。
+    pub fn to_issue_severity(self) -> IssueSeverity {
+        match self {
+            VulnSeverity::Critical => IssueSeverity::Critical,
+            VulnSeverity::High => IssueSeverity::Error,
+            VulnSeverity::Medium => IssueSeverity::Warning,
+            VulnSeverity::Low => IssueSeverity::Info,
+        }
+    }
+}
+
+/// 一条漏洞公告：某个生态系统（`npm`/`cargo`/`pypi`）里的某个包，
+/// 在 `affected_range` 描述的版本范围内受 `cve_id` 影响。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Advisory {
+    pub ecosystem: String,
+    pub package: String,
+    /// 按 [`semver::VersionReq`] 语法描述的受影响版本范围，如 `"<4.17.5"`、
+    /// `">=1.0.0, <1.2.3"`
+    pub affected_range: String,
+    pub cve_id: String,
+    pub severity: VulnSeverity,
+}
+
+/// 离线可用的漏洞公告数据库：内置一份编译期快照作为兜底，
+/// 也可以被 [`crate::commands::advisory_db::refresh_advisory_db`] 下载到的
+/// 更新版本替换（替换后的版本缓存在 sqlite 里，见
+/// [`crate::services::Database::get_advisory_db_cache`]）。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdvisoryDb {
+    /// 单调递增的数据库版本号，随扫描结果一起记录进
+    /// [`crate::models::security::SecurityReport::advisory_db_version`]，
+    /// 便于事后解释某次扫描结果是基于哪个版本的公告数据判定的
+    pub version: u64,
+    pub advisories: Vec<Advisory>,
+}
+
+const EMBEDDED_ADVISORY_DB: &str = include_str!("../../../advisory-db.json");
+
+impl AdvisoryDb {
+    /// 编译期内置的公告数据库快照，离线或从未刷新过时使用
+    pub fn embedded() -> Self {
+        serde_json::from_str(EMBEDDED_ADVISORY_DB)
+            .expect("内置 advisory-db.json 必须是合法的 AdvisoryDb JSON")
+    }
+
+    pub fn find_matching(&self, ecosystem: &str, package: &str) -> Vec<&Advisory> {
+        self.advisories
+            .iter()
+            .filter(|a| a.ecosystem == ecosystem && a.package == package)
+            .collect()
+    }
+}
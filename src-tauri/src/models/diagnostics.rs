@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+/// 单项诊断检查的结果等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// 对某个外部依赖/运行环境项的一次探测结果（例如 Claude CLI 是否存在、
+/// 版本号是多少），供 `get_diagnostics` 汇总展示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticCheck {
+    pub label: String,
+    pub status: DiagnosticStatus,
+    /// 版本号、路径等详情；探测失败时是一句可读的说明（而非原始错误）
+    pub detail: String,
+}
+
+impl DiagnosticCheck {
+    pub fn pass(label: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            status: DiagnosticStatus::Pass,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn warn(label: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            status: DiagnosticStatus::Warn,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn fail(label: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            status: DiagnosticStatus::Fail,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// 单个 marketplace 的诊断详情：本地安装目录的 git HEAD（short sha）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketplaceDiagnostic {
+    pub name: String,
+    pub install_location: Option<String>,
+    pub head_sha: Option<String>,
+    pub status: DiagnosticStatus,
+}
+
+/// 单个已安装 plugin 的诊断详情：Claude CLI 记录的安装路径是否仍然存在磁盘上
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginPathDiagnostic {
+    pub plugin_id: String,
+    pub plugin_name: String,
+    pub install_path: Option<String>,
+    pub status: DiagnosticStatus,
+}
+
+/// `get_diagnostics` 命令返回的完整报告，供 UI 渲染成「诊断/doctor」页面，
+/// 替代目前只能在后端日志里看到的 `log::warn!` 静默回退路径（例如同步/扫描
+/// 失败后悄悄回退到 DB 缓存）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    pub app_version: String,
+    pub checks: Vec<DiagnosticCheck>,
+    pub marketplaces: Vec<MarketplaceDiagnostic>,
+    pub plugins: Vec<PluginPathDiagnostic>,
+}
+
+/// 单个 marketplace 的远端可达性探测：`git ls-remote <repo> HEAD` 是否成功
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketplaceReachability {
+    pub name: String,
+    pub repo: Option<String>,
+    pub status: DiagnosticStatus,
+    pub detail: String,
+}
+
+/// [`crate::services::PluginManager::diagnostics`] 返回的环境健康报告：效仿
+/// Tauri/Millennium `info` 命令的思路，把本应用实际依赖的外部工具（Claude CLI、
+/// git）、marketplace 远端可达性、DB 中 plugin/marketplace 的数量和来源构成
+/// 汇总成一份结构化结果，用来回答"为什么同步/更新悄无声息地返回空"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginEnvironmentReport {
+    pub claude_cli: DiagnosticCheck,
+    pub git_cli: DiagnosticCheck,
+    pub marketplaces_known: usize,
+    pub marketplaces_missing_install_location: Vec<String>,
+    pub marketplace_reachability: Vec<MarketplaceReachability>,
+    pub plugins_known: usize,
+    pub plugins_installed: usize,
+    /// 通过 `claude plugin list --json` 反向同步发现的 plugin 数量
+    /// （`discovery_source == "claude_cli"`）
+    pub plugins_from_claude_cli: usize,
+    /// 本应用自己扫描 marketplace/repository 发现的 plugin 数量
+    pub plugins_from_app: usize,
+}
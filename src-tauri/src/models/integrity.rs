@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// 某个已安装 skill 在完整性校验中发现的问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityIssueKind {
+    /// `local_paths` 中记录的 SKILL.md 文件已不存在
+    MissingFile,
+    /// SKILL.md 现存内容的 SHA-256 与安装时记录的 `checksum` 不一致（可能被篡改或手动修改）
+    ChecksumMismatch,
+}
+
+/// 单个已安装 skill 的完整性问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillIntegrityIssue {
+    pub skill_id: String,
+    pub skill_name: String,
+    /// 出问题的具体路径（`local_paths` 中的一项对应的 SKILL.md 文件）
+    pub path: String,
+    pub kind: IntegrityIssueKind,
+}
+
+/// `verify_installations` 的完整报告，供 UI 渲染成「N 个 skill 自安装后被修改」
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub checked_skills: usize,
+    pub checked_paths: usize,
+    pub missing_count: usize,
+    pub mismatch_count: usize,
+    pub orphaned_count: usize,
+    pub issues: Vec<SkillIntegrityIssue>,
+    /// `installations` 表中 `skill_id` 已不存在于 `skills` 表的孤儿记录
+    pub orphaned_installations: Vec<crate::models::skill::SkillInstallation>,
+}
+
+/// `repair_installations` 的执行策略：哪些问题应当被自动修复
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RepairPolicy {
+    /// 清除 `installations` 表中已找不到对应 `skills` 记录的孤儿行
+    pub purge_orphaned_installations: bool,
+    /// 把缺失文件/校验和不一致的 skill 标记为 `needs_redownload`，提示用户重新安装
+    pub flag_drifted_skills_for_redownload: bool,
+}
+
+impl Default for RepairPolicy {
+    fn default() -> Self {
+        Self {
+            purge_orphaned_installations: true,
+            flag_drifted_skills_for_redownload: true,
+        }
+    }
+}
+
+/// `repair_installations` 实际执行的修复结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepairReport {
+    pub purged_orphaned_installations: usize,
+    pub flagged_skills_for_redownload: usize,
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// [`ResolvedPluginLockfile`] 里记录的一个插件：对应 `resolve_marketplace_plugins`
+/// 解析出的一个 [`crate::services::plugin_manager::ResolvedPlugin`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedPluginLockEntry {
+    pub name: String,
+    pub version: Option<String>,
+    pub repo_url: String,
+    pub marketplace: String,
+    /// 归一化后的 `plugin.json` 所在目录相对路径，与 marketplace.json 里的
+    /// `source` 字段同义
+    pub source: String,
+    /// 本次扫描给出的 `SecurityReport::score`
+    pub security_score: i32,
+}
+
+/// `resolve_marketplace_plugins` 跑完一次之后落盘的锁文件，固定命名为
+/// `skills-guard.lock`、与仓库内容放在一起，记录"这次安装实际钉住了什么"。
+/// 与 [`crate::models::PluginStateManifest`]（用户主动导出/导入的完整已安装
+/// 环境快照）是两个不同的东西：这里只覆盖单个 marketplace 仓库这一次 resolve
+/// 的结果，供后续 `get_plugin_lockfile_info` 核对 drift（磁盘上的 `version`
+/// 是否还与锁定时一致）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedPluginLockfile {
+    pub generated_at: DateTime<Utc>,
+    /// 生成锁文件时 repo_root 所在 git 仓库的 HEAD commit；不是 git 仓库或
+    /// 获取失败时为 `None`
+    pub commit_sha: Option<String>,
+    /// 生成时 [`crate::models::SecurityReport::blocked`]（见 `merge_reports`）
+    pub blocked: bool,
+    /// 生成时 [`crate::models::SecurityReport::partial_scan`]
+    pub partial_scan: bool,
+    pub plugins: Vec<ResolvedPluginLockEntry>,
+}
+
+/// [`ResolvedPluginLockfile`] 里单个 plugin 的 drift 检测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockfileDriftEntry {
+    pub name: String,
+    pub marketplace: String,
+    pub source: String,
+    pub locked_version: Option<String>,
+    pub current_version: Option<String>,
+    /// `current_version != locked_version`
+    pub drifted: bool,
+}
+
+/// `get_plugin_lockfile_info` 的完整结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockfileAuditReport {
+    pub generated_at: DateTime<Utc>,
+    pub commit_sha: Option<String>,
+    pub blocked: bool,
+    pub partial_scan: bool,
+    pub entries: Vec<LockfileDriftEntry>,
+}
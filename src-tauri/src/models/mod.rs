@@ -1,7 +1,27 @@
 pub mod skill;
 pub mod repository;
 pub mod security;
+pub mod plugin;
+pub mod featured_marketplace;
+pub mod update_status;
+pub mod diagnostics;
+pub mod integrity;
+pub mod stats;
+pub mod state_manifest;
+pub mod trust;
+pub mod lockfile;
+pub mod advisory;
 
 pub use skill::*;
 pub use repository::*;
 pub use security::*;
+pub use plugin::*;
+pub use featured_marketplace::*;
+pub use update_status::*;
+pub use diagnostics::*;
+pub use integrity::*;
+pub use stats::*;
+pub use state_manifest::*;
+pub use trust::*;
+pub use lockfile::*;
+pub use advisory::*;
@@ -1,5 +1,7 @@
+use crate::models::security::PluginCapabilities;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 
 /// Claude Code Plugin 信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,9 +34,54 @@ pub struct Plugin {
     pub security_issues: Option<Vec<String>>,
     pub security_level: Option<String>,
     pub scanned_at: Option<DateTime<Utc>>,
+    /// 最近一次扫描时 `claude_install_path` 所在 git 仓库的 HEAD commit sha，
+    /// 用于下次扫描时通过 `git diff --name-only` 判断增量范围
+    pub scanned_commit_sha: Option<String>,
+    /// 最近一次扫描产生的完整 `SecurityReport`（JSON 序列化），包含
+    /// `security_issues` 等字段展开不了的 `category`/`line_number`/
+    /// `code_snippet`/`recommendations`/`blocked`/`hard_trigger_issues`，
+    /// 增量扫描时也用它来合并未变更文件的历史 issue
+    pub report_json: Option<String>,
     pub staging_path: Option<String>,
     pub install_log: Option<String>,
     pub install_status: Option<String>,
+    /// 安装前 [`crate::security::guard::scan_plugin_tree`] 守卫扫描的结果：
+    /// 整个插件目录内容的摘要，变化即代表插件内容自上次扫描以来发生改动
+    pub manifest_digest: Option<String>,
+    /// 守卫扫描给出的风险分（0-100，含义与 `security_score` 一致：越高越安全）
+    pub guard_risk_score: Option<i32>,
+    /// 守卫扫描发现的问题摘要文案，格式与 `security_issues` 一致
+    pub guard_findings: Option<Vec<String>>,
+    /// 该 plugin 应由哪个 [`crate::services::plugin_backend::PluginBackend`] 处理
+    /// （例如 `"claude"`）；为空时回退到 Claude，不根据 marketplace source 推断。
+    pub backend: Option<String>,
+    /// 该 plugin 依赖的其它 plugin：键用 [`Plugin::plugin_spec`]（`name@marketplace`）
+    /// 标识，同一 marketplace 内的依赖可以省略 `@marketplace` 只写裸名；值是一个
+    /// semver 版本要求（如 `"^1.2"`），来自 marketplace.json/plugin.json 里的
+    /// `dependencies` 字段。既用于计算安装顺序与卸载时的依赖保护，也用于
+    /// [`crate::services::plugin_manager::order_resolved_plugins_for_install`]
+    /// 里的版本兼容性校验
+    pub dependencies: Option<HashMap<String, String>>,
+    /// 该 marketplace 仓库最近一次
+    /// [`crate::security::signing::verify_commit_signature`] 的结果；
+    /// `None` 表示该 marketplace 未配置签名校验（见
+    /// [`crate::models::MarketplaceTrustConfig`]），不代表校验失败
+    pub signature_verified: Option<bool>,
+    /// 通过校验的签名者标识，仅在 `signature_verified == Some(true)` 时有意义
+    pub signature_signer: Option<String>,
+    /// 固定跟踪的分支名；与 [`Self::revision`] 互斥，两者都为空时默认跟踪
+    /// 默认分支（行为与此前一致，不做任何固定）
+    pub branch: Option<String>,
+    /// 固定跟踪的 tag/commit SHA；与 [`Self::branch`] 互斥。设置后，
+    /// [`Self::validate_source`] 会拒绝同时设置 `branch` 的组合，扫描器在这个
+    /// revision 上产生的结果通过 `scanned_commit_sha` 记录，供调用方判断
+    /// `claude_install_path` 实际检出的 commit 是否已经偏离这个固定值
+    pub revision: Option<String>,
+    /// 该插件声明（`.claude-plugin/plugin.json` 的 `permissions` 字段）、
+    /// 以及用户后续增补/撤销之后的能力清单；`None` 表示 manifest 未声明任何
+    /// 权限。扫描器会拿这里的声明和 [`crate::models::security::CapabilityManifest`]
+    /// 里推断出的实际能力做差集比对，详见 [`PluginCapabilities::undeclared`]
+    pub capabilities: Option<PluginCapabilities>,
 }
 
 impl Plugin {
@@ -73,9 +120,21 @@ impl Plugin {
             security_issues: None,
             security_level: None,
             scanned_at: None,
+            scanned_commit_sha: None,
+            report_json: None,
             staging_path: None,
             install_log: None,
             install_status: None,
+            manifest_digest: None,
+            guard_risk_score: None,
+            guard_findings: None,
+            backend: None,
+            dependencies: None,
+            signature_verified: None,
+            signature_signer: None,
+            branch: None,
+            revision: None,
+            capabilities: None,
         }
     }
 
@@ -83,6 +142,49 @@ impl Plugin {
         format!("{}@{}", self.name, self.marketplace_name)
     }
 
+    /// 校验 `branch`/`revision`/`repository_url` 的取值是否自洽：`branch` 与
+    /// `revision` 互斥（两者都为空则跟踪默认分支），`repository_url` 不能为空
+    /// 且（`"local"` 之外）必须至少包含一个 `/`；这只是粗略的形式检查，
+    /// 不逐段校验 host/owner/repo 是否齐全。
+    pub fn validate_source(&self) -> Result<(), String> {
+        if self.branch.is_some() && self.revision.is_some() {
+            return Err("branch 与 revision 不能同时设置，请只固定其中一个".to_string());
+        }
+
+        if let Some(branch) = &self.branch {
+            if branch.trim().is_empty() {
+                return Err("branch 不能为空字符串，留空表示跟踪默认分支".to_string());
+            }
+        }
+
+        if let Some(revision) = &self.revision {
+            if revision.trim().is_empty() {
+                return Err("revision 不能为空字符串，留空表示跟踪默认分支".to_string());
+            }
+        }
+
+        if self.repository_url.trim().is_empty() {
+            return Err("repository_url 不能为空".to_string());
+        }
+
+        if self.repository_url != "local"
+            && !self.repository_url.contains('/')
+        {
+            return Err(format!(
+                "repository_url 格式不合法，期望形如 host/owner/repo：{}",
+                self.repository_url
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// 解析出该 plugin 应使用的 backend 标识，供 [`crate::services::plugin_backend::BackendRegistry`]
+    /// 选择实现。显式的 `backend` 字段优先；否则退回默认值（目前只有 Claude Code CLI）。
+    pub fn backend_id(&self) -> &str {
+        self.backend.as_deref().unwrap_or("claude")
+    }
+
     fn parse_repository_owner(repository_url: &str) -> String {
         if repository_url == "local" {
             return "local".to_string();
@@ -0,0 +1,133 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 用户添加的 GitHub 仓库
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Repository {
+    pub id: String,
+    pub url: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub scan_subdirs: bool,
+    pub added_at: DateTime<Utc>,
+    pub last_scanned: Option<DateTime<Utc>>,
+    pub cache_path: Option<String>,
+    pub cached_at: Option<DateTime<Utc>>,
+    pub cached_commit_sha: Option<String>,
+    /// 固定的分支名/tag/commit SHA；为空时扫描会解析仓库的实际默认分支
+    pub git_ref: Option<String>,
+    /// 该仓库所属的主机端点配置；为空时使用公共 github.com
+    pub host: Option<HostConfig>,
+}
+
+impl Repository {
+    pub fn new(url: String, name: String) -> Self {
+        Self {
+            id: url.clone(),
+            url,
+            name,
+            description: None,
+            enabled: true,
+            scan_subdirs: true,
+            added_at: Utc::now(),
+            last_scanned: None,
+            cache_path: None,
+            cached_at: None,
+            cached_commit_sha: None,
+            git_ref: None,
+            host: None,
+        }
+    }
+
+    /// 从 `https://<host>/<owner>/<repo>` 形式的 URL 中解析出 owner/repo。
+    /// 不要求 `<host>` 必须是 `github.com`——`host`/[`HostConfig`] 固定了其它
+    /// 主机（GitHub Enterprise Server、Gitee 等兼容实例）时，这里同样能从
+    /// 它们的 URL 里解析出 owner/repo，只取 host 段之后的前两级路径，不关心
+    /// host 本身是什么
+    pub fn from_github_url(url: &str) -> Result<(String, String)> {
+        let without_scheme = url.split("://").nth(1).unwrap_or(url);
+        let mut host_and_rest = without_scheme.trim_end_matches('/').splitn(2, '/');
+        let host = host_and_rest.next().filter(|h| !h.is_empty());
+        let rest = host_and_rest
+            .next()
+            .filter(|r| !r.is_empty())
+            .with_context(|| format!("Missing repository owner/name in URL: {}", url))?;
+        host.context("Missing host in repository URL")?;
+
+        let rest = rest.trim_end_matches(".git");
+        let mut parts = rest.splitn(2, '/');
+        let owner = parts.next().context("Missing repository owner")?;
+        let repo = parts.next().context("Missing repository name")?;
+
+        if owner.is_empty() || repo.is_empty() {
+            anyhow::bail!("Invalid repository URL: {}", url);
+        }
+
+        Ok((owner.to_string(), repo.to_string()))
+    }
+}
+
+/// GitHub 兼容主机的端点配置：GitHub Enterprise Server、Gitee 等自建/兼容实例
+/// 的 REST API 地址和原始文件服务地址通常都与公共 github.com 不同，两者需要
+/// 分别配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostConfig {
+    /// REST API 根地址，例如 `https://github.example.com/api/v3`
+    pub api_base_url: String,
+    /// 原始文件服务根地址，例如 `https://github.example.com/raw`
+    pub raw_base_url: String,
+}
+
+impl Default for HostConfig {
+    fn default() -> Self {
+        Self {
+            api_base_url: "https://api.github.com".to_string(),
+            raw_base_url: "https://raw.githubusercontent.com".to_string(),
+        }
+    }
+}
+
+/// GitHub Contents API 返回的单个条目
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitHubContent {
+    pub name: String,
+    pub path: String,
+    #[serde(rename = "type")]
+    pub content_type: String,
+    pub download_url: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_public_github_url() {
+        let (owner, repo) = Repository::from_github_url("https://github.com/acme/widgets").unwrap();
+        assert_eq!(owner, "acme");
+        assert_eq!(repo, "widgets");
+    }
+
+    #[test]
+    fn parses_github_enterprise_url() {
+        let (owner, repo) =
+            Repository::from_github_url("https://github.example.com/acme/widgets.git").unwrap();
+        assert_eq!(owner, "acme");
+        assert_eq!(repo, "widgets");
+    }
+
+    #[test]
+    fn parses_gitee_url() {
+        let (owner, repo) = Repository::from_github_url("https://gitee.com/acme/widgets/").unwrap();
+        assert_eq!(owner, "acme");
+        assert_eq!(repo, "widgets");
+    }
+
+    #[test]
+    fn rejects_url_without_owner_and_repo() {
+        assert!(Repository::from_github_url("https://github.com").is_err());
+        assert!(Repository::from_github_url("https://github.com/acme").is_err());
+    }
+}
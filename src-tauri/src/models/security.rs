@@ -0,0 +1,546 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// 安全问题严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum IssueSeverity {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl IssueSeverity {
+    /// 映射到 SARIF 2.1.0 的 `result.level`：`Critical`/`Error` 都算 `error`，
+    /// 因为 SARIF 没有单独的 "critical" 级别
+    pub fn sarif_level(&self) -> &'static str {
+        match self {
+            IssueSeverity::Critical | IssueSeverity::Error => "error",
+            IssueSeverity::Warning => "warning",
+            IssueSeverity::Info => "note",
+        }
+    }
+
+    /// 存入 `security_findings.severity` 列的纯文本表示
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueSeverity::Info => "info",
+            IssueSeverity::Warning => "warning",
+            IssueSeverity::Error => "error",
+            IssueSeverity::Critical => "critical",
+        }
+    }
+
+    /// `as_str` 的逆操作，用于从 `security_findings.severity` 读回枚举值
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "info" => Some(IssueSeverity::Info),
+            "warning" => Some(IssueSeverity::Warning),
+            "error" => Some(IssueSeverity::Error),
+            "critical" => Some(IssueSeverity::Critical),
+            _ => None,
+        }
+    }
+}
+
+/// 安全问题分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueCategory {
+    FileSystem,
+    Network,
+    DataExfiltration,
+    ProcessExecution,
+    DangerousFunction,
+    ObfuscatedCode,
+    /// 通过 embedding 相似度检测到的疑似 prompt injection / 数据外泄指令
+    PromptInjection,
+    /// 磁盘上的文件权限问题（世界可写、可执行文件组/其他可写、setuid/setgid）
+    FilePermissions,
+    /// 依赖清单（package.json/Cargo.lock/requirements.txt）里声明的包命中了
+    /// 漏洞公告数据库里的已知 CVE
+    VulnerableDependency,
+    Other,
+}
+
+impl IssueCategory {
+    /// 稳定的 kebab-case 标识符，用作 SARIF `rule.id` / `result.ruleId`
+    pub fn rule_id(&self) -> &'static str {
+        match self {
+            IssueCategory::FileSystem => "file-system",
+            IssueCategory::Network => "network",
+            IssueCategory::DataExfiltration => "data-exfiltration",
+            IssueCategory::ProcessExecution => "process-execution",
+            IssueCategory::DangerousFunction => "dangerous-function",
+            IssueCategory::ObfuscatedCode => "obfuscated-code",
+            IssueCategory::PromptInjection => "prompt-injection",
+            IssueCategory::FilePermissions => "file-permissions",
+            IssueCategory::VulnerableDependency => "vulnerable-dependency",
+            IssueCategory::Other => "other",
+        }
+    }
+
+    /// 该分类的简短英文描述，用于 SARIF `rule.shortDescription`
+    pub fn short_description(&self) -> &'static str {
+        match self {
+            IssueCategory::FileSystem => "Dangerous file system operation",
+            IssueCategory::Network => "Suspicious network activity",
+            IssueCategory::DataExfiltration => "Possible data exfiltration",
+            IssueCategory::ProcessExecution => "Process or shell execution",
+            IssueCategory::DangerousFunction => "Use of a dangerous function",
+            IssueCategory::ObfuscatedCode => "Obfuscated or encoded code",
+            IssueCategory::PromptInjection => "Suspected prompt injection",
+            IssueCategory::FilePermissions => "Unsafe on-disk file permissions",
+            IssueCategory::VulnerableDependency => "Dependency with a known CVE",
+            IssueCategory::Other => "Other security finding",
+        }
+    }
+}
+
+/// 单条安全问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityIssue {
+    pub severity: IssueSeverity,
+    pub category: IssueCategory,
+    pub description: String,
+    pub line_number: Option<usize>,
+    pub code_snippet: Option<String>,
+    pub file_path: Option<String>,
+}
+
+/// 安全等级。声明顺序即严重程度递增顺序，供 [`crate::security::merge_backend_reports`]
+/// 之类需要在多份报告里取"最差等级"的场景直接用 `Ord` 比较
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SecurityLevel {
+    Safe,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl SecurityLevel {
+    pub fn from_score(score: i32) -> Self {
+        match score {
+            s if s >= 90 => SecurityLevel::Safe,
+            s if s >= 70 => SecurityLevel::Low,
+            s if s >= 50 => SecurityLevel::Medium,
+            s if s >= 30 => SecurityLevel::High,
+            _ => SecurityLevel::Critical,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecurityLevel::Safe => "Safe",
+            SecurityLevel::Low => "Low",
+            SecurityLevel::Medium => "Medium",
+            SecurityLevel::High => "High",
+            SecurityLevel::Critical => "Critical",
+        }
+    }
+}
+
+/// 从外部规则包加载的一条自定义检测规则，与内置的
+/// `DANGEROUS_FS_PATTERNS` 等模式表形状一致，但可以在运行时
+/// 由组织自行提供（例如内部域名黑名单、自定义密钥命名约定）。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomRule {
+    pub pattern: String,
+    pub description: String,
+    pub category: IssueCategory,
+    /// 显式指定严重程度；缺省时按 `category` 推导，与内置规则的行为一致
+    #[serde(default)]
+    pub severity: Option<IssueSeverity>,
+}
+
+/// 一份可从 YAML 配置文件加载的规则包：自定义规则列表，
+/// 外加按类别禁用内置规则的开关。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RulePackConfig {
+    #[serde(default)]
+    pub rules: Vec<CustomRule>,
+    /// 列在此处的内置类别不再参与检测（自定义规则不受影响）
+    #[serde(default)]
+    pub disabled_categories: Vec<IssueCategory>,
+}
+
+/// pre-install 对暂存的 plugin 仓库做的一次轻量「守卫」扫描结果：只看
+/// `.json`（含 `.claude-plugin/` manifest）和 markdown 文档里声明/提到的命令，而不是像
+/// [`SecurityReport`] 那样逐文件跑完整规则集，用于在拉取 `plugin install` 之前
+/// 先做一次快速拦截判断。见 [`crate::security::guard::scan_plugin_tree`]。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginScanReport {
+    pub findings: Vec<PluginGuardFinding>,
+    /// 0-100，分值含义与 [`SecurityReport::score`] 一致：越高越安全
+    pub risk_score: i32,
+    /// 从 manifest/markdown 里提到的命令推断出的能力清单
+    pub permissions: CapabilityManifest,
+    /// 整个目录的摘要：按文件相对路径排序后，把每个文件的 `path:sha256` 逐行
+    /// 拼接，再整体取一次 SHA-256；内容不变则摘要不变，可用于判断插件内容
+    /// 自上次扫描以来是否发生变化
+    pub manifest_digest: String,
+}
+
+impl PluginScanReport {
+    /// risk_score 是否达到调用方要求的最低阈值（例如安装前的拦截线）
+    pub fn passes_threshold(&self, min_risk_score: i32) -> bool {
+        self.risk_score >= min_risk_score
+    }
+}
+
+/// [`PluginScanReport`] 里的一条发现
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginGuardFinding {
+    pub severity: IssueSeverity,
+    pub category: IssueCategory,
+    pub description: String,
+    pub file_path: String,
+}
+
+/// 安全扫描报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityReport {
+    pub skill_id: String,
+    pub score: i32,
+    pub level: SecurityLevel,
+    pub issues: Vec<SecurityIssue>,
+    pub recommendations: Vec<String>,
+    /// 是否命中了足以阻断安装的高危问题
+    pub blocked: bool,
+    /// 触发阻断的高危问题摘要文案（用于直接展示给用户）
+    pub hard_trigger_issues: Vec<String>,
+    /// 该 skill 推断出的能力清单（运行时访问范围）
+    pub capabilities: CapabilityManifest,
+    /// 本次实际扫描过的文件（相对路径）
+    pub scanned_files: Vec<String>,
+    /// 是否只完成了部分扫描（例如增量扫描跳过了未变更文件）
+    pub partial_scan: bool,
+    /// 本次扫描跳过的文件（相对路径）
+    pub skipped_files: Vec<String>,
+    /// 本次扫描依赖清单（package.json/Cargo.lock/requirements.txt）时使用的
+    /// [`crate::models::advisory::AdvisoryDb::version`]；`None` 表示未做依赖
+    /// 漏洞扫描（例如单文件扫描 `scan_file`，没有目录可供收集依赖清单）
+    #[serde(default)]
+    pub advisory_db_version: Option<u64>,
+}
+
+/// 从扫描内容中推断出的、该 skill 实际会用到的能力清单，
+/// 建模方式参考 Tauri 的 permission/capability 模型。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CapabilityManifest {
+    /// 读写其自身文件夹之外的文件系统路径
+    pub filesystem_outside_skill: bool,
+    /// 发起网络请求（出站）
+    pub network_access: bool,
+    /// 执行 shell/子进程
+    pub shell_execution: bool,
+    /// 读取环境变量
+    pub env_var_access: bool,
+    /// 每项能力对应的、人类可读的依据（例如匹配到的代码片段）
+    pub details: Vec<String>,
+}
+
+impl CapabilityManifest {
+    pub fn is_empty(&self) -> bool {
+        !self.filesystem_outside_skill
+            && !self.network_access
+            && !self.shell_execution
+            && !self.env_var_access
+    }
+}
+
+/// 前缀约定：`fs:<path>` 是文件系统路径，`net:<host>` 是联网目标 host，
+/// `shell` 是裸字面量——三种都作为同一种"能力条目"字符串存取，供
+/// [`Plugin::capabilities`](crate::models::Plugin::capabilities) 的增补/撤销
+/// 列表复用，不必为三种能力各开一套增删接口
+const CAPABILITY_FS_PREFIX: &str = "fs:";
+const CAPABILITY_NET_PREFIX: &str = "net:";
+const CAPABILITY_SHELL: &str = "shell";
+
+/// 插件在 `.claude-plugin/plugin.json` 的 `permissions` 字段里声明的能力，
+/// 叠加用户后续的增补/撤销记录。和 [`CapabilityManifest`] 的区别：那个是
+/// 从插件内容里"推断"出来的实际用到的运行时能力（见
+/// [`crate::security::guard::scan_plugin_tree`]）；这个是插件自己事先"声明"
+/// 会用到哪些能力，供两者做差集比对（[`Self::undeclared`]），也是
+/// `plugin_capability_add`/`plugin_capability_remove` 的落盘对象——用户可以
+/// 在声明的基础上增补或撤销单条能力，在安装/启用前自行取舍。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginCapabilities {
+    /// manifest 声明会读写的文件系统路径（原样保留，例如 `~/.ssh`）
+    #[serde(default)]
+    pub filesystem_paths: Vec<String>,
+    /// manifest 声明会联网访问的 host
+    #[serde(default)]
+    pub network_hosts: Vec<String>,
+    /// manifest 是否声明会执行 shell/子进程
+    #[serde(default)]
+    pub shell_execution: bool,
+    /// 用户在 manifest 声明之外额外授予的能力条目（`fs:`/`net:` 前缀或 `shell`）
+    #[serde(default)]
+    pub granted_extra: Vec<String>,
+    /// 用户撤销的能力条目，优先级高于声明和额外授予
+    #[serde(default)]
+    pub revoked: Vec<String>,
+}
+
+impl PluginCapabilities {
+    /// 当前实际生效的能力条目：manifest 声明 ∪ 用户额外授予，减去用户撤销的部分
+    pub fn effective(&self) -> Vec<String> {
+        let mut entries: Vec<String> = self
+            .filesystem_paths
+            .iter()
+            .map(|p| format!("{CAPABILITY_FS_PREFIX}{p}"))
+            .chain(self.network_hosts.iter().map(|h| format!("{CAPABILITY_NET_PREFIX}{h}")))
+            .chain(self.shell_execution.then(|| CAPABILITY_SHELL.to_string()).into_iter())
+            .chain(self.granted_extra.iter().cloned())
+            .filter(|entry| !self.revoked.contains(entry))
+            .collect();
+        entries.sort();
+        entries.dedup();
+        entries
+    }
+
+    pub fn effective_shell_execution(&self) -> bool {
+        self.effective().iter().any(|e| e == CAPABILITY_SHELL)
+    }
+
+    pub fn effective_filesystem_paths(&self) -> Vec<String> {
+        self.effective()
+            .into_iter()
+            .filter_map(|e| e.strip_prefix(CAPABILITY_FS_PREFIX).map(str::to_string))
+            .collect()
+    }
+
+    pub fn effective_network_hosts(&self) -> Vec<String> {
+        self.effective()
+            .into_iter()
+            .filter_map(|e| e.strip_prefix(CAPABILITY_NET_PREFIX).map(str::to_string))
+            .collect()
+    }
+
+    /// 人类可读摘要，用于安装/启用前的决策展示，例如
+    /// `"reads ~/.ssh, makes network requests to 3 hosts, runs shell commands"`
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+
+        let fs_paths = self.effective_filesystem_paths();
+        if !fs_paths.is_empty() {
+            parts.push(format!("reads {}", fs_paths.join(", ")));
+        }
+
+        let hosts = self.effective_network_hosts();
+        if !hosts.is_empty() {
+            parts.push(format!(
+                "makes network requests to {} host{}",
+                hosts.len(),
+                if hosts.len() == 1 { "" } else { "s" }
+            ));
+        }
+
+        if self.effective_shell_execution() {
+            parts.push("runs shell commands".to_string());
+        }
+
+        if parts.is_empty() {
+            "no declared capabilities".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    /// 对比扫描器从内容里实际推断出的能力（[`CapabilityManifest`]），返回
+    /// manifest 未声明、也未被用户额外授予却被用到的能力描述，供调用方追加进
+    /// `security_issues`
+    pub fn undeclared(&self, requested: &CapabilityManifest) -> Vec<String> {
+        let mut findings = Vec::new();
+
+        if requested.network_access && self.effective_network_hosts().is_empty() {
+            findings.push(
+                "声明之外的网络访问：代码中检测到联网操作，但 manifest 未声明 network 能力"
+                    .to_string(),
+            );
+        }
+        if requested.shell_execution && !self.effective_shell_execution() {
+            findings.push(
+                "声明之外的 shell 执行：代码中检测到子进程/shell 调用，但 manifest 未声明 shell 能力"
+                    .to_string(),
+            );
+        }
+        if requested.filesystem_outside_skill && self.effective_filesystem_paths().is_empty() {
+            findings.push(
+                "声明之外的文件系统访问：代码中检测到插件目录之外的文件读写，但 manifest 未声明 filesystem 能力"
+                    .to_string(),
+            );
+        }
+
+        findings
+    }
+}
+
+/// 用户定义的能力允许/拒绝策略。默认四项能力全部拒绝，其中网络与 shell 执行
+/// 是大多数 skill 不应该需要的两项高风险能力。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityPolicy {
+    pub allow_filesystem_outside_skill: bool,
+    pub allow_network_access: bool,
+    pub allow_shell_execution: bool,
+    pub allow_env_var_access: bool,
+}
+
+impl Default for CapabilityPolicy {
+    fn default() -> Self {
+        Self {
+            allow_filesystem_outside_skill: false,
+            allow_network_access: false,
+            allow_shell_execution: false,
+            allow_env_var_access: false,
+        }
+    }
+}
+
+impl CapabilityPolicy {
+    /// 返回 manifest 中违反策略的能力名称（供 UI 展示 / 阻断提示）
+    pub fn violations(&self, manifest: &CapabilityManifest) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if manifest.filesystem_outside_skill && !self.allow_filesystem_outside_skill {
+            violations.push("filesystem access outside the skill folder".to_string());
+        }
+        if manifest.network_access && !self.allow_network_access {
+            violations.push("network access".to_string());
+        }
+        if manifest.shell_execution && !self.allow_shell_execution {
+            violations.push("shell/process execution".to_string());
+        }
+        if manifest.env_var_access && !self.allow_env_var_access {
+            violations.push("environment variable access".to_string());
+        }
+
+        violations
+    }
+}
+
+/// `security_findings` 表中的一条规范化记录：每次 `save_skill`/`save_plugin`
+/// 持久化 `security_issues` 时按条追加，不覆盖旧记录，因此同一个 subject 可以
+/// 累积跨多次扫描的完整历史，支持按 `severity`/`rule_id` 查询
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityFinding {
+    pub id: i64,
+    /// `"skill"` 或 `"plugin"`
+    pub subject_type: String,
+    pub subject_id: String,
+    /// 对应 `IssueCategory::rule_id()`
+    pub rule_id: String,
+    pub severity: IssueSeverity,
+    pub title: String,
+    pub detail: Option<String>,
+    pub scanned_at: DateTime<Utc>,
+}
+
+/// `security_score_history` 表中的一条记录：某个 subject 在某次扫描时的总分，
+/// 供 `Database::score_history` 返回分数随时间的变化趋势
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreHistoryEntry {
+    pub score: i32,
+    pub scanned_at: DateTime<Utc>,
+}
+
+/// 单个 skill 的扫描结果（供前端展示/持久化）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillScanResult {
+    pub skill_id: String,
+    pub skill_name: String,
+    pub score: i32,
+    pub level: String,
+    pub scanned_at: String,
+    pub report: SecurityReport,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective_combines_declared_and_granted_minus_revoked() {
+        let capabilities = PluginCapabilities {
+            filesystem_paths: vec!["~/.ssh".to_string()],
+            network_hosts: vec!["api.example.com".to_string()],
+            shell_execution: true,
+            granted_extra: vec!["net:extra.example.com".to_string()],
+            revoked: vec!["shell".to_string()],
+        };
+
+        let effective = capabilities.effective();
+
+        assert!(effective.contains(&"fs:~/.ssh".to_string()));
+        assert!(effective.contains(&"net:api.example.com".to_string()));
+        assert!(effective.contains(&"net:extra.example.com".to_string()));
+        assert!(!effective.contains(&"shell".to_string()));
+        assert!(!capabilities.effective_shell_execution());
+    }
+
+    #[test]
+    fn revoking_a_declared_capability_removes_it_even_if_also_granted_extra() {
+        let capabilities = PluginCapabilities {
+            filesystem_paths: vec![],
+            network_hosts: vec!["api.example.com".to_string()],
+            shell_execution: false,
+            granted_extra: vec!["net:api.example.com".to_string()],
+            revoked: vec!["net:api.example.com".to_string()],
+        };
+
+        assert!(capabilities.effective_network_hosts().is_empty());
+    }
+
+    #[test]
+    fn undeclared_flags_capabilities_used_but_not_granted() {
+        let capabilities = PluginCapabilities::default();
+        let requested = CapabilityManifest {
+            filesystem_outside_skill: true,
+            network_access: true,
+            shell_execution: true,
+            env_var_access: false,
+            details: Vec::new(),
+        };
+
+        let findings = capabilities.undeclared(&requested);
+
+        assert_eq!(findings.len(), 3);
+    }
+
+    #[test]
+    fn undeclared_is_empty_when_every_used_capability_is_granted() {
+        let capabilities = PluginCapabilities {
+            filesystem_paths: vec!["~/.config".to_string()],
+            network_hosts: vec!["api.example.com".to_string()],
+            shell_execution: true,
+            granted_extra: vec![],
+            revoked: vec![],
+        };
+        let requested = CapabilityManifest {
+            filesystem_outside_skill: true,
+            network_access: true,
+            shell_execution: true,
+            env_var_access: false,
+            details: Vec::new(),
+        };
+
+        assert!(capabilities.undeclared(&requested).is_empty());
+    }
+
+    #[test]
+    fn undeclared_ignores_capabilities_the_code_never_actually_used() {
+        let capabilities = PluginCapabilities::default();
+        let requested = CapabilityManifest::default();
+
+        assert!(capabilities.undeclared(&requested).is_empty());
+    }
+
+    #[test]
+    fn summary_reports_no_declared_capabilities_when_empty() {
+        assert_eq!(
+            PluginCapabilities::default().summary(),
+            "no declared capabilities"
+        );
+    }
+}
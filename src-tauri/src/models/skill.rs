@@ -1,3 +1,4 @@
+use crate::models::security::CapabilityManifest;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
@@ -8,15 +9,31 @@ pub struct Skill {
     pub name: String,
     pub description: Option<String>,
     pub repository_url: String,
+    pub repository_owner: Option<String>,
     pub file_path: String,
     pub version: Option<String>,
     pub author: Option<String>,
     pub installed: bool,
     pub installed_at: Option<DateTime<Utc>>,
     pub local_path: Option<String>,
+    pub local_paths: Option<Vec<String>>,
     pub checksum: Option<String>,
     pub security_score: Option<i32>,
     pub security_issues: Option<Vec<String>>,
+    pub security_level: Option<String>,
+    pub scanned_at: Option<DateTime<Utc>>,
+    pub installed_commit_sha: Option<String>,
+    /// 推断出的能力清单（最近一次扫描的结果）
+    pub capability_manifest: Option<CapabilityManifest>,
+    /// 最近一次扫描时 SKILL.md 内容的 SHA-256 校验和，用于增量扫描判断内容是否变化
+    pub content_checksum: Option<String>,
+    /// 最近一次扫描产生的完整 `SecurityReport`（JSON 序列化），包含
+    /// `security_issues` 等字段展开不了的 `category`/`line_number`/
+    /// `code_snippet`/`recommendations`/`blocked`/`hard_trigger_issues`
+    pub report_json: Option<String>,
+    /// 由 `repair_installations` 标记：完整性校验发现文件缺失或校验和不一致，
+    /// 提示用户重新下载安装
+    pub needs_redownload: bool,
 }
 
 impl Skill {
@@ -30,15 +47,24 @@ impl Skill {
             name,
             description: None,
             repository_url,
+            repository_owner: None,
             file_path,
             version: None,
             author: None,
             installed: false,
             installed_at: None,
             local_path: None,
+            local_paths: None,
             checksum: None,
             security_score: None,
             security_issues: None,
+            security_level: None,
+            scanned_at: None,
+            installed_commit_sha: None,
+            capability_manifest: None,
+            content_checksum: None,
+            report_json: None,
+            needs_redownload: false,
         }
     }
 }
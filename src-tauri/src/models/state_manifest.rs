@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// [`PluginStateManifest`] 里记录的一个 marketplace：重建环境时据此重新执行
+/// `claude plugin marketplace add`，并 `git checkout` 到 `head_sha` 锁定的
+/// 那个版本，而不是 floating 在对方仓库当时的默认分支上
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedMarketplace {
+    pub name: String,
+    pub repo: Option<String>,
+    /// 导出时该 marketplace 安装目录的 git HEAD（完整 sha），与
+    /// `check_marketplaces_updates` 读取的是同一个值
+    pub head_sha: Option<String>,
+}
+
+/// [`PluginStateManifest`] 里记录的一个已安装 plugin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedPlugin {
+    pub name: String,
+    pub marketplace_name: String,
+    pub version: Option<String>,
+    /// 导出时的安装 scope（`user`/`project` 等）
+    pub scope: Option<String>,
+    /// 该 plugin 来源仓库，便于脱离 marketplace 清单单独核对
+    pub repository_url: String,
+    /// 导出时 `Plugin::scanned_commit_sha`：安装该 plugin 时所在仓库的 git
+    /// HEAD，用于在导入端核对是否仍是同一个提交（见 [`PluginStateManifest`]）
+    pub commit_sha: Option<String>,
+    /// 导出时 [`crate::security::guard::scan_plugin_tree`] 产出的
+    /// `Plugin::manifest_digest`，内容变化即代表自导出以来插件已被改动
+    pub manifest_digest: Option<String>,
+}
+
+/// `export_state`/`import_state` 交换的完整环境快照：已知 marketplaces 和
+/// 已安装 plugins，连同各自锁定的 git 提交，足以在另一台机器上按位复现，或在
+/// 一次误操作后回滚——相当于这个 crate 的 `Cargo.lock`，序列化后由调用方存成
+/// 一份 `guard.lock` 之类的文件即可
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginStateManifest {
+    pub exported_at: DateTime<Utc>,
+    pub marketplaces: Vec<ExportedMarketplace>,
+    pub plugins: Vec<ExportedPlugin>,
+}
+
+/// `import_state` 里单个 marketplace/plugin 的导入结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginStateImportItem {
+    pub name: String,
+    pub success: bool,
+    pub detail: String,
+}
+
+/// `import_state` 的完整结果，形状上与 [`crate::services::plugin_manager::PluginInstallResult`]
+/// 类似：按条目展开的成功/失败明细，供 UI 逐项展示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginStateImportResult {
+    pub marketplaces: Vec<PluginStateImportItem>,
+    pub plugins: Vec<PluginStateImportItem>,
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// 某个 `security_level` 下的数量（skill 或 plugin）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityLevelCount {
+    pub level: String,
+    pub count: i64,
+}
+
+/// `security_score` 按 20 分一档分桶的直方图；未扫描过的归入 `"unscanned"`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreBucket {
+    pub range: String,
+    pub count: i64,
+}
+
+/// `Database::stats` 返回的聚合统计，供仪表盘一次性展示而不必加载全部行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseStats {
+    pub total_repositories: i64,
+    pub enabled_repositories: i64,
+    /// `last_scanned IS NULL` 的仓库数
+    pub unscanned_repositories: i64,
+    pub total_skills: i64,
+    pub installed_skills: i64,
+    pub total_plugins: i64,
+    pub installed_plugins: i64,
+    pub skill_security_levels: Vec<SecurityLevelCount>,
+    pub plugin_security_levels: Vec<SecurityLevelCount>,
+    pub skill_score_buckets: Vec<ScoreBucket>,
+    /// skills 和 plugins 中最早的一次 `scanned_at`，用于提示安全数据已经多久没刷新
+    pub oldest_scanned_at: Option<DateTime<Utc>>,
+}
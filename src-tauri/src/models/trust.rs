@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 一份可从 YAML 配置文件加载的、按 marketplace 名称索引的受信任签名者列表。
+/// 列在某个 marketplace 下的值必须是完整的 GPG key fingerprint（`VALIDSIG`
+/// 行里那个，由 GnuPG 本身校验过，不是签名者自己在 UID 里填的姓名/邮箱），
+/// [`crate::security::signing::verify_commit_signature`] 按精确相等（忽略
+/// 大小写）比对这个 fingerprint，据此判断一次 commit 签名是否来自这些发布者
+/// 之一。不在此配置中出现的 marketplace 视为未启用签名校验，安装流程照常
+/// 放行。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketplaceTrustConfig {
+    #[serde(default)]
+    pub trusted_signers: HashMap<String, Vec<String>>,
+}
+
+impl MarketplaceTrustConfig {
+    /// 返回某个 marketplace 配置的受信任签名者；`None` 表示该 marketplace
+    /// 未配置签名校验（安装流程应跳过验证，而不是当作校验失败）
+    pub fn signers_for(&self, marketplace_name: &str) -> Option<&[String]> {
+        self.trusted_signers.get(marketplace_name).map(|v| v.as_slice())
+    }
+}
+
+/// 一次 commit 签名校验的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureVerification {
+    pub verified: bool,
+    /// 仅用于展示的签名者身份（优先取 `GOODSIG` 行里签名者自报的姓名+邮箱，
+    /// 拿不到时退化为 `VALIDSIG` fingerprint）；**不参与**信任判定，校验
+    /// 失败或根本没有签名时为 `None`。真正决定 `verified` 的是
+    /// [`crate::security::signing::verify_commit_signature`] 内部按精确
+    /// 相等比对的 `VALIDSIG` fingerprint。
+    pub signer: Option<String>,
+    pub detail: String,
+}
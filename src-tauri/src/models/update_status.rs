@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// 后台更新检查器缓存的状态：记录某个 plugin/marketplace 最近一次检测到的
+/// 可用版本，以及上次向用户发出更新提醒时的版本和时间，用于抑制重复提醒
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateStatus {
+    /// `"plugin"` 或 `"marketplace"`
+    pub item_type: String,
+    pub item_id: String,
+    pub item_name: String,
+    pub available_version: String,
+    pub checked_at: DateTime<Utc>,
+    pub last_notified_version: Option<String>,
+    pub last_notified_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,104 @@
+use crate::models::security::SecurityReport;
+use crate::security::ScanOptions;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// 发给外部扫描进程的请求体：通过 stdin 写入一份 JSON，字段与
+/// [`SecurityScanner::scan_directory_with_options`] 的入参一一对应
+#[derive(Debug, Serialize)]
+struct ExternalScanRequest<'a> {
+    plugin_id: &'a str,
+    source_path: &'a str,
+    options: ScanOptions,
+}
+
+/// out-of-process 安全扫描器的扩展点，镜像
+/// [`crate::services::plugin_backend::PluginBackend`] 的思路：`PluginManager`
+/// 不再只能跑内置的 `SecurityScanner`，团队可以注册自己的二进制（专有 linter、
+/// 密钥扫描器……）而不需要 fork 这个 crate。
+pub trait ScannerBackend: Send + Sync {
+    /// 存入日志/报告里用于区分来源的标识
+    fn id(&self) -> &str;
+
+    /// 对指定目录跑一次扫描，返回一份与内置扫描器同构的 [`SecurityReport`]
+    fn scan(&self, source_path: &Path, plugin_id: &str, options: ScanOptions) -> Result<SecurityReport>;
+}
+
+/// 通过 stdio JSON 协议驱动的外部扫描器：把请求写到子进程 stdin，
+/// 在 `timeout` 内等待子进程把一份 JSON [`SecurityReport`] 写回 stdout。
+pub struct ExternalScannerBackend {
+    id: String,
+    command: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl ExternalScannerBackend {
+    pub fn new(id: impl Into<String>, command: impl Into<String>, args: Vec<String>, timeout: Duration) -> Self {
+        Self {
+            id: id.into(),
+            command: command.into(),
+            args,
+            timeout,
+        }
+    }
+}
+
+impl ScannerBackend for ExternalScannerBackend {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn scan(&self, source_path: &Path, plugin_id: &str, options: ScanOptions) -> Result<SecurityReport> {
+        let request = ExternalScanRequest {
+            plugin_id,
+            source_path: &source_path.to_string_lossy(),
+            options,
+        };
+        let payload = serde_json::to_vec(&request).context("序列化外部扫描请求失败")?;
+
+        let mut child = Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("启动外部扫描器 {} 失败: {}", self.id, self.command))?;
+
+        child
+            .stdin
+            .take()
+            .context("无法写入外部扫描器 stdin")?
+            .write_all(&payload)
+            .with_context(|| format!("向外部扫描器 {} 写入请求失败", self.id))?;
+
+        let mut stdout = child.stdout.take().context("无法读取外部扫描器 stdout")?;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            use std::io::Read;
+            let mut buf = Vec::new();
+            let _ = stdout.read_to_end(&mut buf);
+            let _ = tx.send(buf);
+        });
+
+        let output = match rx.recv_timeout(self.timeout) {
+            Ok(output) => output,
+            Err(_) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                anyhow::bail!("外部扫描器 {} 在 {:?} 内未返回结果，已终止", self.id, self.timeout);
+            }
+        };
+
+        let _ = child.wait();
+
+        serde_json::from_slice(&output)
+            .with_context(|| format!("解析外部扫描器 {} 的输出失败", self.id))
+    }
+}
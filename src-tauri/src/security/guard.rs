@@ -0,0 +1,184 @@
+use crate::models::security::{
+    CapabilityManifest, IssueCategory, IssueSeverity, PluginGuardFinding, PluginScanReport,
+};
+use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// guard 只看这些扩展名的文件：目录树里任意位置的 `.json`（包括
+/// `.claude-plugin/` 下的 manifest）以及 markdown 文档，这两类最可能直接写出要执行的命令（hooks/示例 shell 片段），比逐文件跑完整
+/// 规则集（见 [`crate::security::SecurityScanner`]）快得多，适合在下载完archive
+/// 后第一时间跑一遍
+const GUARD_SCANNABLE_EXTENSIONS: &[&str] = &["json", "md"];
+
+/// 触发「疑似 base64 混淆 blob」的最短连续编码字符长度
+const OBFUSCATED_BLOB_MIN_LEN: usize = 200;
+
+lazy_static! {
+    /// 会被当作"发起网络请求"对待的命令行工具
+    static ref NETWORK_TOOL_PATTERN: Regex =
+        Regex::new(r"\b(curl|wget|nc|netcat)\b").expect("network tool pattern 编译失败");
+
+    /// 指向凭证相关路径的字面量
+    static ref CREDENTIAL_PATH_PATTERN: Regex =
+        Regex::new(r"(~/\.ssh|/\.ssh/|\.aws(/|\b)|(^|[^.\w])\.env\b)").expect("credential path pattern 编译失败");
+
+    /// 写入插件目录之外路径的命令（绝对路径或 `../` 上跳）；捕获组 1 是目标
+    /// 路径，`/tmp/` 下的目标由 [`scan_file_content`] 排除
+    static ref WRITE_OUTSIDE_PATTERN: Regex =
+        Regex::new(r">>?\s*(/[^\s]*|\.\./[^\s]*)").expect("write-outside pattern 编译失败");
+
+    /// 疑似 base64 编码的长字符串
+    static ref BASE64_BLOB_PATTERN: Regex =
+        Regex::new(&format!(r"[A-Za-z0-9+/]{{{},}}={{0,2}}", OBFUSCATED_BLOB_MIN_LEN))
+            .expect("base64 blob pattern 编译失败");
+}
+
+/// 对已解压到本地的 plugin/marketplace 仓库跑一次轻量守卫扫描：遍历
+/// `root` 下所有 `.json`（含 `.claude-plugin/` manifest）与 markdown 文件，标记出（a）调用
+/// `curl`/`wget`/`nc` 等网络工具或写到插件目录之外的命令，（b）引用
+/// `~/.ssh`/`.env`/`.aws` 等凭证路径的字面量，（c）超过长度阈值、疑似
+/// base64 编码的混淆 blob。同时给每个被扫描文件计算 SHA-256，并汇总成一个
+/// 整体的 `manifest_digest`，供调用方判断插件内容自上次扫描以来是否变化。
+pub fn scan_plugin_tree(root: &Path) -> Result<PluginScanReport> {
+    if !root.is_dir() {
+        anyhow::bail!("插件目录不存在: {}", root.to_string_lossy());
+    }
+
+    let mut findings = Vec::new();
+    let mut permissions = CapabilityManifest::default();
+    let mut file_hashes: Vec<(String, String)> = Vec::new();
+
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else { continue };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+
+            let ext = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase())
+                .unwrap_or_default();
+
+            let relative = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+
+            let Ok(bytes) = std::fs::read(&path) else { continue };
+            file_hashes.push((relative.clone(), sha256_hex(&bytes)));
+
+            if !GUARD_SCANNABLE_EXTENSIONS.contains(&ext.as_str()) {
+                continue;
+            }
+            let Ok(content) = String::from_utf8(bytes) else { continue };
+
+            scan_file_content(&content, &relative, &mut findings, &mut permissions);
+        }
+    }
+
+    file_hashes.sort();
+    let manifest_digest = digest_file_hashes(&file_hashes);
+
+    let risk_score = calculate_risk_score(&findings);
+
+    Ok(PluginScanReport {
+        findings,
+        risk_score,
+        permissions,
+        manifest_digest,
+    })
+}
+
+fn scan_file_content(
+    content: &str,
+    relative_path: &str,
+    findings: &mut Vec<PluginGuardFinding>,
+    permissions: &mut CapabilityManifest,
+) {
+    for (line_number, line) in content.lines().enumerate() {
+        if NETWORK_TOOL_PATTERN.is_match(line) {
+            permissions.network_access = true;
+            permissions.details.push(format!("[{}:{}] {}", relative_path, line_number + 1, line.trim()));
+            findings.push(PluginGuardFinding {
+                severity: IssueSeverity::Error,
+                category: IssueCategory::Network,
+                description: format!("调用了网络工具（curl/wget/nc）: {}", line.trim()),
+                file_path: relative_path.to_string(),
+            });
+        }
+
+        if WRITE_OUTSIDE_PATTERN
+            .captures_iter(line)
+            .any(|c| !c[1].starts_with("/tmp/"))
+        {
+            permissions.filesystem_outside_skill = true;
+            findings.push(PluginGuardFinding {
+                severity: IssueSeverity::Critical,
+                category: IssueCategory::FileSystem,
+                description: format!("疑似写入插件目录之外的路径: {}", line.trim()),
+                file_path: relative_path.to_string(),
+            });
+        }
+
+        if CREDENTIAL_PATH_PATTERN.is_match(line) {
+            findings.push(PluginGuardFinding {
+                severity: IssueSeverity::Critical,
+                category: IssueCategory::DataExfiltration,
+                description: format!("引用了凭证相关路径: {}", line.trim()),
+                file_path: relative_path.to_string(),
+            });
+        }
+
+        if let Some(m) = BASE64_BLOB_PATTERN.find(line) {
+            findings.push(PluginGuardFinding {
+                severity: IssueSeverity::Warning,
+                category: IssueCategory::ObfuscatedCode,
+                description: format!(
+                    "发现 {} 字符的疑似 base64 混淆 blob",
+                    m.as_str().len()
+                ),
+                file_path: relative_path.to_string(),
+            });
+        }
+    }
+}
+
+fn calculate_risk_score(findings: &[PluginGuardFinding]) -> i32 {
+    let mut score = 100;
+    for finding in findings {
+        score -= match finding.severity {
+            IssueSeverity::Critical => 30,
+            IssueSeverity::Error => 20,
+            IssueSeverity::Warning => 10,
+            IssueSeverity::Info => 5,
+        };
+    }
+    score.max(0)
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+fn digest_file_hashes(file_hashes: &[(String, String)]) -> String {
+    let mut hasher = Sha256::new();
+    for (path, hash) in file_hashes {
+        hasher.update(path.as_bytes());
+        hasher.update(b":");
+        hasher.update(hash.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
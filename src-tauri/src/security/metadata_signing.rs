@@ -0,0 +1,305 @@
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// 精选 marketplace YAML 的签名元数据——TUF 里的"目标元数据"简化版：只记录
+/// 版本号（防回滚）、过期时间、以及 YAML 内容的 SHA-256。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeaturedMarketplacesMetadata {
+    pub version: u64,
+    pub expires: DateTime<Utc>,
+    pub sha256: String,
+}
+
+/// 对 [`FeaturedMarketplacesMetadata`] 的一个签名：`keyid` 对应
+/// [`TrustedRootKeys`] 里某一把内置公钥，`signature` 是该公钥对元数据规范
+/// 化 JSON 的 ed25519 签名（十六进制编码）。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataSignature {
+    pub keyid: String,
+    pub signature: String,
+}
+
+/// 远端同时发布的签名元数据文档：`metadata` + 达到门限数量的签名
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedMetadataEnvelope {
+    pub metadata: FeaturedMarketplacesMetadata,
+    pub signatures: Vec<MetadataSignature>,
+}
+
+/// 校验失败的具体原因，调用方据此决定是否继续沿用旧缓存、以及向用户展示
+/// 什么提示，而不是一个笼统的字符串
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataVerificationError {
+    /// 签名数量不足门限，或没有一个签名能通过内置公钥校验
+    InsufficientSignatures { valid: usize, threshold: usize },
+    /// 元数据已过期
+    Expired { expires: DateTime<Utc> },
+    /// 元数据版本号低于本地已记录的版本——疑似回滚攻击
+    Rollback { seen: u64, received: u64 },
+    /// 下载的 YAML 的 SHA-256 与签名元数据里记录的不一致
+    HashMismatch { expected: String, actual: String },
+}
+
+impl fmt::Display for MetadataVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataVerificationError::InsufficientSignatures { valid, threshold } => write!(
+                f,
+                "签名元数据校验失败：{} 个有效签名，未达到门限 {}",
+                valid, threshold
+            ),
+            MetadataVerificationError::Expired { expires } => {
+                write!(f, "签名元数据已于 {} 过期", expires)
+            }
+            MetadataVerificationError::Rollback { seen, received } => write!(
+                f,
+                "检测到疑似回滚攻击：本地已记录版本 {}，收到的版本却是 {}",
+                seen, received
+            ),
+            MetadataVerificationError::HashMismatch { expected, actual } => write!(
+                f,
+                "下载内容的 SHA-256（{}）与签名元数据里记录的（{}）不一致",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MetadataVerificationError {}
+
+/// 内置的根公钥集合与门限。真实发布时这些公钥应来自离线签名基础设施，此处
+/// 硬编码进二进制，避免信任根本身也要从网络上获取。
+pub struct TrustedRootKeys {
+    keys: Vec<(String, VerifyingKey)>,
+    pub threshold: usize,
+}
+
+impl TrustedRootKeys {
+    /// 编译期内置的根公钥集合。`keyid` 对应 [`MetadataSignature::keyid`]。
+    pub fn embedded() -> Self {
+        const EMBEDDED_KEYS: &[(&str, &str)] = &[
+            (
+                "featured-marketplaces-root-1",
+                "6f3f56a0f2c9d7d4d4e9b6f6b2a6f9e4f6d1c2a3b4c5d6e7f8a9b0c1d2e3f4a5",
+            ),
+            (
+                "featured-marketplaces-root-2",
+                "a5f4e3d2c1b0a9f8e7d6c5b4a3f2d1e9f6b2a6f6b9e4d4d7c9f2a0563f566f3f",
+            ),
+        ];
+
+        let keys = EMBEDDED_KEYS
+            .iter()
+            .filter_map(|(keyid, hex)| {
+                let bytes = decode_hex(hex).ok()?;
+                let bytes: [u8; 32] = bytes.try_into().ok()?;
+                let key = VerifyingKey::from_bytes(&bytes).ok()?;
+                Some((keyid.to_string(), key))
+            })
+            .collect();
+
+        Self { keys, threshold: 1 }
+    }
+
+    fn find(&self, keyid: &str) -> Option<&VerifyingKey> {
+        self.keys.iter().find(|(id, _)| id == keyid).map(|(_, key)| key)
+    }
+}
+
+/// 校验一份 [`SignedMetadataEnvelope`]：签名门限、反回滚、过期检查，三者都
+/// 通过才放行。`last_seen_version` 为 `None` 表示本地还没有缓存过任何版本
+/// （首次拉取），此时跳过反回滚检查。
+pub fn verify_metadata(
+    envelope: &SignedMetadataEnvelope,
+    roots: &TrustedRootKeys,
+    last_seen_version: Option<u64>,
+    now: DateTime<Utc>,
+) -> Result<(), MetadataVerificationError> {
+    let canonical = canonical_metadata_bytes(&envelope.metadata);
+    let valid_signatures = envelope
+        .signatures
+        .iter()
+        .filter(|sig| verify_one_signature(&canonical, sig, roots))
+        .count();
+
+    if valid_signatures < roots.threshold {
+        return Err(MetadataVerificationError::InsufficientSignatures {
+            valid: valid_signatures,
+            threshold: roots.threshold,
+        });
+    }
+
+    if let Some(seen) = last_seen_version {
+        if envelope.metadata.version < seen {
+            return Err(MetadataVerificationError::Rollback {
+                seen,
+                received: envelope.metadata.version,
+            });
+        }
+    }
+
+    if envelope.metadata.expires < now {
+        return Err(MetadataVerificationError::Expired {
+            expires: envelope.metadata.expires,
+        });
+    }
+
+    Ok(())
+}
+
+/// 校验下载到的 YAML 原文的 SHA-256 是否与签名元数据里记录的一致
+pub fn verify_yaml_hash(
+    yaml_content: &[u8],
+    metadata: &FeaturedMarketplacesMetadata,
+) -> Result<(), MetadataVerificationError> {
+    let actual = sha256_hex(yaml_content);
+    if actual != metadata.sha256.to_lowercase() {
+        return Err(MetadataVerificationError::HashMismatch {
+            expected: metadata.sha256.clone(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+fn verify_one_signature(canonical: &[u8], sig: &MetadataSignature, roots: &TrustedRootKeys) -> bool {
+    let Some(key) = roots.find(&sig.keyid) else {
+        return false;
+    };
+    let Ok(sig_bytes) = decode_hex(&sig.signature) else {
+        return false;
+    };
+    let Ok(sig_bytes): Result<[u8; 64], _> = sig_bytes.try_into() else {
+        return false;
+    };
+    let signature = Signature::from_bytes(&sig_bytes);
+    key.verify(canonical, &signature).is_ok()
+}
+
+/// 用来签名/验签的规范化字节序列——字段顺序固定，避免 JSON 序列化的细微差异
+/// （键顺序、空白）导致同一份元数据算出不同的签名内容
+fn canonical_metadata_bytes(metadata: &FeaturedMarketplacesMetadata) -> Vec<u8> {
+    format!(
+        "{{\"version\":{},\"expires\":\"{}\",\"sha256\":\"{}\"}}",
+        metadata.version,
+        metadata.expires.to_rfc3339(),
+        metadata.sha256
+    )
+    .into_bytes()
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, std::num::ParseIntError> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    /// 从一个固定种子确定性地生成一对测试密钥，不依赖任何 RNG——测试只需要
+    /// 一把跟 [`TrustedRootKeys::embedded`] 里的真实根密钥无关、但格式相同的
+    /// 密钥对，用来签发/校验测试用的元数据信封
+    fn test_signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn test_roots(seed: u8, keyid: &str, threshold: usize) -> TrustedRootKeys {
+        let signing_key = test_signing_key(seed);
+        TrustedRootKeys {
+            keys: vec![(keyid.to_string(), signing_key.verifying_key())],
+            threshold,
+        }
+    }
+
+    fn sign_envelope(metadata: FeaturedMarketplacesMetadata, signing_key: &SigningKey, keyid: &str) -> SignedMetadataEnvelope {
+        let canonical = canonical_metadata_bytes(&metadata);
+        let signature = signing_key.sign(&canonical);
+        SignedMetadataEnvelope {
+            metadata,
+            signatures: vec![MetadataSignature {
+                keyid: keyid.to_string(),
+                signature: encode_hex(&signature.to_bytes()),
+            }],
+        }
+    }
+
+    fn sample_metadata(version: u64, yaml_content: &[u8]) -> FeaturedMarketplacesMetadata {
+        FeaturedMarketplacesMetadata {
+            version,
+            expires: Utc::now() + chrono::Duration::days(1),
+            sha256: sha256_hex(yaml_content),
+        }
+    }
+
+    #[test]
+    fn valid_signature_from_trusted_key_accepts() {
+        let signing_key = test_signing_key(1);
+        let roots = test_roots(1, "test-root-1", 1);
+        let envelope = sign_envelope(sample_metadata(1, b"marketplaces: []"), &signing_key, "test-root-1");
+
+        assert!(verify_metadata(&envelope, &roots, None, Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn tampered_yaml_content_fails_hash_check() {
+        let metadata = sample_metadata(1, b"marketplaces: []");
+
+        let result = verify_yaml_hash(b"marketplaces: [tampered]", &metadata);
+
+        assert_eq!(
+            result,
+            Err(MetadataVerificationError::HashMismatch {
+                expected: metadata.sha256.clone(),
+                actual: sha256_hex(b"marketplaces: [tampered]"),
+            })
+        );
+    }
+
+    #[test]
+    fn signature_from_untrusted_key_is_rejected() {
+        // 用一把不在根密钥集合里的 key 签名，门限检查应该当它完全无效
+        let untrusted_key = test_signing_key(99);
+        let roots = test_roots(1, "test-root-1", 1);
+        let envelope = sign_envelope(sample_metadata(1, b"marketplaces: []"), &untrusted_key, "test-root-1");
+
+        let result = verify_metadata(&envelope, &roots, None, Utc::now());
+
+        assert_eq!(
+            result,
+            Err(MetadataVerificationError::InsufficientSignatures { valid: 0, threshold: 1 })
+        );
+    }
+
+    #[test]
+    fn rollback_to_an_older_version_is_rejected() {
+        let signing_key = test_signing_key(1);
+        let roots = test_roots(1, "test-root-1", 1);
+        // 本地已经见过版本 5，这次收到的签名元数据却只有版本 1——即使签名本身
+        // 有效也必须拒绝，否则攻击者可以重放一份旧的、已签名的元数据来降级
+        let envelope = sign_envelope(sample_metadata(1, b"marketplaces: []"), &signing_key, "test-root-1");
+
+        let result = verify_metadata(&envelope, &roots, Some(5), Utc::now());
+
+        assert_eq!(
+            result,
+            Err(MetadataVerificationError::Rollback { seen: 5, received: 1 })
+        );
+    }
+}
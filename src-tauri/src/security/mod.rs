@@ -1,8 +1,17 @@
 mod scanner;
 mod rules;
+mod backend;
+pub mod embedding;
+pub mod guard;
+pub mod metadata_signing;
+pub mod sarif;
+pub mod signing;
+pub mod vulnerabilities;
 
-pub use scanner::SecurityScanner;
+pub use scanner::{ScanOptions, SecurityScanner};
 pub use rules::SecurityRules;
+pub use backend::{ExternalScannerBackend, ScannerBackend};
+pub use embedding::{EmbeddingProvider, ApiEmbeddingProvider, LocalEmbeddingProvider, PromptInjectionDetector};
 
 use crate::models::security::*;
 use anyhow::Result;
@@ -1,5 +1,8 @@
-use regex::Regex;
+use crate::models::security::{IssueCategory, IssueSeverity, RulePackConfig};
+use anyhow::{Context, Result};
+use regex::{Regex, RegexSet};
 use lazy_static::lazy_static;
+use std::path::Path;
 
 lazy_static! {
     /// 危险文件系统操作模式
@@ -47,56 +50,173 @@ lazy_static! {
         (r"\\x[0-9a-fA-F]{2}", "十六进制编码字符串"),
         (r"chr\s*\(\s*\d+\s*\)", "字符编码（可能用于混淆）"),
     ];
+
+    /// 环境变量访问模式（用于能力推断，而非安全问题本身）
+    pub static ref ENV_VAR_PATTERNS: Vec<&'static str> = vec![
+        r"os\.environ",
+        r"os\.getenv",
+        r"process\.env",
+        r"std::env::var",
+        r"\$\{?[A-Z_][A-Z0-9_]*\}?",
+    ];
+
+    /// 每个内置模式对应的 `(description, category)`，与 [`ALL_PATTERNS_SET`] 的
+    /// 下标一一对应，用于把 `RegexSet::matches` 返回的下标映射回人类可读的描述
+    /// 以及强类型的 `IssueCategory`（而不是字符串标签，按类别禁用时直接比较）。
+    static ref ALL_PATTERNS_META: Vec<(&'static str, IssueCategory)> = {
+        DANGEROUS_FS_PATTERNS.iter().map(|(_, d)| (*d, IssueCategory::FileSystem))
+            .chain(NETWORK_PATTERNS.iter().map(|(_, d)| (*d, IssueCategory::Network)))
+            .chain(DATA_EXFILTRATION_PATTERNS.iter().map(|(_, d)| (*d, IssueCategory::DataExfiltration)))
+            .chain(FILE_OPERATION_PATTERNS.iter().map(|(_, d)| (*d, IssueCategory::FileSystem)))
+            .chain(OBFUSCATION_PATTERNS.iter().map(|(_, d)| (*d, IssueCategory::ObfuscatedCode)))
+            .collect()
+    };
+
+    /// 所有内置模式编译成的单个 `RegexSet`，下标顺序与 [`ALL_PATTERNS_META`] 对齐。
+    /// 只在首次访问时编译一次，`check_line` 对每一行只需一次 `matches` 调用，
+    /// 而不是对每个模式都重新 `Regex::new` 一遍。
+    static ref ALL_PATTERNS_SET: RegexSet = RegexSet::new(
+        DANGEROUS_FS_PATTERNS.iter().map(|(p, _)| *p)
+            .chain(NETWORK_PATTERNS.iter().map(|(p, _)| *p))
+            .chain(DATA_EXFILTRATION_PATTERNS.iter().map(|(p, _)| *p))
+            .chain(FILE_OPERATION_PATTERNS.iter().map(|(p, _)| *p))
+            .chain(OBFUSCATION_PATTERNS.iter().map(|(p, _)| *p))
+    ).expect("all built-in security patterns must be valid regexes");
 }
 
-pub struct SecurityRules;
+/// 一条编译好的自定义规则：`CustomRule.pattern` 已经 `Regex::new` 过，
+/// 不会在每一行重新编译。
+struct CompiledCustomRule {
+    regex: Regex,
+    description: String,
+    category: IssueCategory,
+    severity: Option<IssueSeverity>,
+}
 
-impl SecurityRules {
-    /// 检查代码行是否包含危险模式
-    pub fn check_line(line: &str) -> Vec<(String, String)> {
-        let mut findings = Vec::new();
+/// 检测规则集合：内置规则（可按类别禁用）加上从规则包合并进来的自定义规则。
+///
+/// 默认（[`SecurityRules::new`]）只启用内置规则；调用
+/// [`SecurityRules::with_rule_pack`] 可以加载一份外部规则包与内置规则合并，
+/// 让组织在不重新编译应用的前提下添加自己的检测策略（例如内部域名黑名单、
+/// 自定义密钥命名约定），或是直接关闭某个内置类别。
+pub struct SecurityRules {
+    disabled_categories: Vec<IssueCategory>,
+    custom_rules: Vec<CompiledCustomRule>,
+}
 
-        // 检查所有危险模式
-        for (pattern, description) in DANGEROUS_FS_PATTERNS.iter() {
-            if let Ok(re) = Regex::new(pattern) {
-                if re.is_match(line) {
-                    findings.push((description.to_string(), "FileSystem".to_string()));
-                }
-            }
+impl SecurityRules {
+    pub fn new() -> Self {
+        Self {
+            disabled_categories: Vec::new(),
+            custom_rules: Vec::new(),
         }
+    }
 
-        for (pattern, description) in NETWORK_PATTERNS.iter() {
-            if let Ok(re) = Regex::new(pattern) {
-                if re.is_match(line) {
-                    findings.push((description.to_string(), "Network".to_string()));
+    /// 用一份规则包构造：保留所有未被禁用的内置规则，并追加规则包里的自定义
+    /// 规则。编译失败的自定义规则会被跳过并记录警告，而不会让整个规则包失效。
+    pub fn with_rule_pack(pack: RulePackConfig) -> Self {
+        let custom_rules = pack
+            .rules
+            .into_iter()
+            .filter_map(|rule| match Regex::new(&rule.pattern) {
+                Ok(regex) => Some(CompiledCustomRule {
+                    regex,
+                    description: rule.description,
+                    category: rule.category,
+                    severity: rule.severity,
+                }),
+                Err(e) => {
+                    log::warn!("忽略无效的自定义规则 `{}`: {}", rule.pattern, e);
+                    None
                 }
-            }
+            })
+            .collect();
+
+        Self {
+            disabled_categories: pack.disabled_categories,
+            custom_rules,
         }
+    }
 
-        for (pattern, description) in DATA_EXFILTRATION_PATTERNS.iter() {
-            if let Ok(re) = Regex::new(pattern) {
-                if re.is_match(line) {
-                    findings.push((description.to_string(), "DataExfiltration".to_string()));
-                }
+    /// 从磁盘加载规则包（YAML 格式，与应用内其他配置文件如
+    /// `featured_marketplaces` 保持一致）。
+    pub fn load_rule_pack_file(path: &Path) -> Result<RulePackConfig> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rule pack '{}'", path.display()))?;
+
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse rule pack '{}'", path.display()))
+    }
+
+    /// 检查代码行是否包含危险模式：先跑内置 `RegexSet`（过滤掉被禁用的类别），
+    /// 再跑本规则包中的自定义规则。返回的每条命中附带可选的严重程度覆盖值，
+    /// 若为 `None` 则按 `SecurityScanner::determine_severity` 的默认规则推导。
+    pub fn check_line(&self, line: &str) -> Vec<(String, IssueCategory, Option<IssueSeverity>)> {
+        let mut findings: Vec<(String, IssueCategory, Option<IssueSeverity>)> = ALL_PATTERNS_SET
+            .matches(line)
+            .into_iter()
+            .map(|idx| ALL_PATTERNS_META[idx])
+            .filter(|(_, category)| !self.disabled_categories.contains(category))
+            .map(|(description, category)| (description.to_string(), category, None))
+            .collect();
+
+        for rule in &self.custom_rules {
+            if rule.regex.is_match(line) {
+                findings.push((rule.description.clone(), rule.category, rule.severity));
             }
         }
 
-        for (pattern, description) in FILE_OPERATION_PATTERNS.iter() {
-            if let Ok(re) = Regex::new(pattern) {
-                if re.is_match(line) {
-                    findings.push((description.to_string(), "FileOperation".to_string()));
+        findings
+    }
+
+    /// 基于已匹配到的安全问题类别以及内容中的环境变量访问模式，
+    /// 推断该 skill 声明/隐含使用的能力清单。
+    pub fn infer_capabilities(
+        content: &str,
+        issues: &[crate::models::security::SecurityIssue],
+    ) -> crate::models::security::CapabilityManifest {
+        use crate::models::security::CapabilityManifest;
+
+        let mut manifest = CapabilityManifest::default();
+
+        for issue in issues {
+            match issue.category {
+                IssueCategory::Network => {
+                    manifest.network_access = true;
+                    manifest.details.push(format!("network: {}", issue.description));
+                }
+                IssueCategory::ProcessExecution | IssueCategory::DangerousFunction => {
+                    manifest.shell_execution = true;
+                    manifest.details.push(format!("shell: {}", issue.description));
+                }
+                IssueCategory::FileSystem => {
+                    manifest.filesystem_outside_skill = true;
+                    manifest
+                        .details
+                        .push(format!("filesystem: {}", issue.description));
                 }
+                _ => {}
             }
         }
 
-        for (pattern, description) in OBFUSCATION_PATTERNS.iter() {
+        for pattern in ENV_VAR_PATTERNS.iter() {
             if let Ok(re) = Regex::new(pattern) {
-                if re.is_match(line) {
-                    findings.push((description.to_string(), "Obfuscation".to_string()));
+                if re.is_match(content) {
+                    manifest.env_var_access = true;
+                    manifest
+                        .details
+                        .push(format!("env: matched pattern `{}`", pattern));
+                    break;
                 }
             }
         }
 
-        findings
+        manifest
+    }
+}
+
+impl Default for SecurityRules {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -0,0 +1,174 @@
+//! 把 [`SecurityReport`] 序列化为 SARIF 2.1.0，供 GitHub code scanning、
+//! 人工审查或其他静态分析看板消费，替代目前 `get_scan_results` 里需要手动
+//! 解析的 "Severity: description" 字符串。
+
+use crate::models::security::{IssueCategory, SecurityReport};
+use serde::Serialize;
+
+const SARIF_SCHEMA_URI: &str =
+    "https://raw.githubusercontent.com/oasis-tcs/sarif-spec/master/Schemata/sarif-schema-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const TOOL_NAME: &str = "agent-skills-guard";
+const TOOL_INFORMATION_URI: &str = "https://github.com/brucevanfdm/agent-skills-guard";
+
+/// 枚举全部 [`IssueCategory`] 变体，用于生成完整的 `tool.driver.rules` 目录，
+/// 即便某次扫描没有命中某个分类，规则目录里也会包含它。
+const ALL_CATEGORIES: &[IssueCategory] = &[
+    IssueCategory::FileSystem,
+    IssueCategory::Network,
+    IssueCategory::DataExfiltration,
+    IssueCategory::ProcessExecution,
+    IssueCategory::DangerousFunction,
+    IssueCategory::ObfuscatedCode,
+    IssueCategory::PromptInjection,
+    IssueCategory::FilePermissions,
+    IssueCategory::VulnerableDependency,
+    IssueCategory::Other,
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    pub version: String,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifDriver {
+    pub name: String,
+    pub version: String,
+    pub information_uri: String,
+    pub rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifRule {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "shortDescription")]
+    pub short_description: SarifMessage,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifResult {
+    #[serde(rename = "ruleId")]
+    pub rule_id: String,
+    pub level: String,
+    pub message: SarifMessage,
+    pub locations: Vec<SarifLocation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifLocation {
+    #[serde(rename = "physicalLocation")]
+    pub physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifPhysicalLocation {
+    #[serde(rename = "artifactLocation")]
+    pub artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifArtifactLocation {
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifRegion {
+    #[serde(rename = "startLine")]
+    pub start_line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<SarifMessage>,
+}
+
+fn rule_catalog() -> Vec<SarifRule> {
+    ALL_CATEGORIES
+        .iter()
+        .map(|category| SarifRule {
+            id: category.rule_id().to_string(),
+            name: format!("{:?}", category),
+            short_description: SarifMessage {
+                text: category.short_description().to_string(),
+            },
+        })
+        .collect()
+}
+
+/// 把一份 [`SecurityReport`] 的 `issues` 转换成 SARIF `result` 条目。
+/// 没有 `file_path` 的 issue 退回使用 `report.skill_id` 作为 artifact URI，
+/// 保证每条 result 都带有 `physicalLocation`（SARIF 要求）。
+fn report_to_results(report: &SecurityReport) -> Vec<SarifResult> {
+    report
+        .issues
+        .iter()
+        .map(|issue| {
+            let uri = issue
+                .file_path
+                .clone()
+                .unwrap_or_else(|| report.skill_id.clone());
+
+            let region = issue.line_number.map(|line| SarifRegion {
+                start_line: line,
+                snippet: issue.code_snippet.clone().map(|text| SarifMessage { text }),
+            });
+
+            SarifResult {
+                rule_id: issue.category.rule_id().to_string(),
+                level: issue.severity.sarif_level().to_string(),
+                message: SarifMessage {
+                    text: issue.description.clone(),
+                },
+                locations: vec![SarifLocation {
+                    physical_location: SarifPhysicalLocation {
+                        artifact_location: SarifArtifactLocation { uri },
+                        region,
+                    },
+                }],
+            }
+        })
+        .collect()
+}
+
+/// 把一批 [`SecurityReport`] 合并成单个 SARIF 2.1.0 日志（单个 `run`，
+/// `results` 为所有报告 `issues` 的拼接）。
+pub fn build_sarif_log(reports: &[SecurityReport]) -> SarifLog {
+    let results = reports.iter().flat_map(report_to_results).collect();
+
+    SarifLog {
+        schema: SARIF_SCHEMA_URI.to_string(),
+        version: SARIF_VERSION.to_string(),
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: TOOL_NAME.to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    information_uri: TOOL_INFORMATION_URI.to_string(),
+                    rules: rule_catalog(),
+                },
+            },
+            results,
+        }],
+    }
+}
@@ -1,60 +1,491 @@
+use crate::models::advisory::AdvisoryDb;
 use crate::models::security::*;
+use crate::security::embedding::{LocalEmbeddingProvider, PromptInjectionDetector};
+use crate::security::vulnerabilities::scan_dependencies;
 use crate::security::SecurityRules;
-use anyhow::Result;
+use crate::services::Database;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
+use std::io::Read;
+use std::path::Path;
 
-pub struct SecurityScanner;
+/// 命中即阻断安装的严重程度下限
+const HARD_TRIGGER_SEVERITY: IssueSeverity = IssueSeverity::Critical;
+
+/// zip 包里单个条目允许的最大解压后字节数，避免 zip-bomb 式输入把内存打爆
+const MAX_ZIP_ENTRY_BYTES: u64 = 20 * 1024 * 1024;
+/// zip 包整体允许的最大解压后字节数
+const MAX_ZIP_TOTAL_BYTES: u64 = 200 * 1024 * 1024;
+/// zip 包允许的最大条目数
+const MAX_ZIP_ENTRIES: usize = 10_000;
+
+/// 会被当作文本/脚本扫描的文件扩展名
+const SCANNABLE_EXTENSIONS: &[&str] = &[
+    "md", "py", "sh", "bash", "js", "ts", "mjs", "cjs", "json", "yaml", "yml", "txt",
+];
+
+/// 权限审计单次最多检查的文件数，避免异常庞大的 skill 目录拖慢扫描
+const MAX_PERMISSION_AUDIT_ENTRIES: usize = 10_000;
+
+/// [`SecurityScanner::scan_directory_with_options`] 的可调选项。同时也是
+/// [`crate::security::ExternalScannerBackend`] 发给外部扫描进程的请求体的一部分，
+/// 因此需要能序列化。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ScanOptions {
+    /// 跳过 README.md：大量 skill/plugin 在 README 里贴配置和命令示例，
+    /// 字面量规则在这里的误报率最高
+    pub skip_readme: bool,
+}
+
+pub struct SecurityScanner {
+    /// 基于 embedding 相似度的 prompt-injection 检测器。
+    /// 构造失败（例如 provider 初始化出错）时退化为不做语义检测，
+    /// 只依赖 `SecurityRules` 的字面量规则。
+    injection_detector: Option<PromptInjectionDetector>,
+    /// 当前生效的检测规则集（内置规则 + 可选的用户自定义规则包）
+    rules: SecurityRules,
+    /// 依赖漏洞扫描使用的公告数据库；默认是编译期内置快照，可以通过
+    /// [`Self::with_advisory_db`] 换成从缓存/远端刷新过的版本
+    advisory_db: AdvisoryDb,
+}
 
 impl SecurityScanner {
     pub fn new() -> Self {
-        Self
+        let injection_detector =
+            PromptInjectionDetector::new(Box::new(LocalEmbeddingProvider::new())).ok();
+
+        Self {
+            injection_detector,
+            rules: SecurityRules::new(),
+            advisory_db: AdvisoryDb::embedded(),
+        }
+    }
+
+    /// 用一份用户自定义规则包构造扫描器，规则包会和内置规则合并生效。
+    pub fn with_rule_pack(rule_pack: RulePackConfig) -> Self {
+        let injection_detector =
+            PromptInjectionDetector::new(Box::new(LocalEmbeddingProvider::new())).ok();
+
+        Self {
+            injection_detector,
+            rules: SecurityRules::with_rule_pack(rule_pack),
+            advisory_db: AdvisoryDb::embedded(),
+        }
+    }
+
+    /// 替换依赖漏洞扫描使用的公告数据库，一般用调用方从 sqlite 缓存里读到的、
+    /// 通过 [`crate::commands::advisory_db::refresh_advisory_db`] 刷新过的版本。
+    pub fn with_advisory_db(mut self, advisory_db: AdvisoryDb) -> Self {
+        self.advisory_db = advisory_db;
+        self
     }
 
     /// 扫描文件内容，生成安全报告
     pub fn scan_file(&self, content: &str, file_path: &str) -> Result<SecurityReport> {
-        let mut issues = Vec::new();
         let skill_id = file_path.to_string();
+        let mut issues = self.line_rule_issues(content, file_path);
 
-        // 逐行扫描代码
-        for (line_num, line) in content.lines().enumerate() {
-            let findings = SecurityRules::check_line(line);
-
-            for (description, category_str) in findings {
-                let category = match category_str.as_str() {
-                    "FileSystem" => IssueCategory::FileSystem,
-                    "Network" => IssueCategory::Network,
-                    "DataExfiltration" => IssueCategory::DataExfiltration,
-                    "FileOperation" => IssueCategory::FileSystem,
-                    "Obfuscation" => IssueCategory::ObfuscatedCode,
-                    _ => IssueCategory::Other,
+        // 基于 embedding 相似度捕捉未命中字面量规则的新型攻击指令
+        if let Some(detector) = &self.injection_detector {
+            match detector.scan(content, file_path) {
+                Ok(mut semantic_issues) => issues.append(&mut semantic_issues),
+                Err(e) => log::warn!("Embedding 检测失败，跳过语义扫描: {}", e),
+            }
+        }
+
+        // 计算安全评分
+        let score = self.calculate_score(&issues);
+        let level = SecurityLevel::from_score(score);
+
+        // 生成建议
+        let recommendations = self.generate_recommendations(&issues, score);
+
+        let hard_trigger_issues = hard_trigger_summaries(&issues);
+        let blocked = !hard_trigger_issues.is_empty();
+        let capabilities = SecurityRules::infer_capabilities(content, &issues);
+
+        Ok(SecurityReport {
+            skill_id,
+            score,
+            level,
+            issues,
+            recommendations,
+            blocked,
+            hard_trigger_issues,
+            capabilities,
+            scanned_files: vec![file_path.to_string()],
+            partial_scan: false,
+            skipped_files: Vec::new(),
+            advisory_db_version: None,
+        })
+    }
+
+    /// 在 [`scan_file`] 的基础上，对已解压到磁盘的 skill 目录额外做一次
+    /// Unix 文件权限审计（见 [`audit_directory_permissions`]），把发现的
+    /// `FilePermissions` 问题并入同一份报告并重新计算评分/等级/建议。
+    ///
+    /// `scan_file`/`scan_archive` 面向安装前检查（此时 skill 内容可能还在
+    /// 压缩包里，没有落盘目录可审计）；这个方法专供 `scan_all_installed_skills`
+    /// 这类已安装、已经有真实 `local_path` 的场景使用。
+    pub fn scan_installed_skill_dir(
+        &self,
+        content: &str,
+        dir: &Path,
+        file_path: &str,
+    ) -> Result<SecurityReport> {
+        let mut report = self.scan_file(content, file_path)?;
+        let perm_issues = audit_directory_permissions(dir);
+
+        if !perm_issues.is_empty() {
+            report.issues.extend(perm_issues);
+            report.score = self.calculate_score(&report.issues);
+            report.level = SecurityLevel::from_score(report.score);
+            report.hard_trigger_issues = hard_trigger_summaries(&report.issues);
+            report.blocked = !report.hard_trigger_issues.is_empty();
+            report.capabilities = SecurityRules::infer_capabilities(content, &report.issues);
+            report.recommendations = self.generate_recommendations(&report.issues, report.score);
+        }
+
+        Ok(report)
+    }
+
+    /// 扫描一个 zip 格式的 skill/plugin 压缩包，无需先解压到磁盘。
+    ///
+    /// 遍历包内每一个条目，对扩展名在 [`SCANNABLE_EXTENSIONS`] 内的文本/脚本
+    /// 文件分别调用 [`scan_file`]（`file_path` 使用包内条目名，如
+    /// `my-skill/SKILL.md`，便于问题定位），再把所有条目的报告合并成一份：
+    /// `score` 取各条目中最差的一项，`issues`/`hard_trigger_issues` 全部拼接。
+    /// 为防止 zip-bomb 式输入，会同时限制单条目、总体解压字节数和条目总数，
+    /// 超出部分记录到 `skipped_files` 并将 `partial_scan` 置为 `true`，而不是
+    /// 中断整个扫描。
+    pub fn scan_archive(&self, archive_path: &Path) -> Result<SecurityReport> {
+        let file = std::fs::File::open(archive_path)
+            .with_context(|| format!("Failed to open archive '{}'", archive_path.display()))?;
+        let mut archive = zip::ZipArchive::new(std::io::BufReader::new(file))
+            .with_context(|| format!("Failed to read '{}' as a zip archive", archive_path.display()))?;
+
+        let mut merged_issues = Vec::new();
+        let mut scanned_files = Vec::new();
+        let mut skipped_files = Vec::new();
+        let mut worst_score = 100;
+        let mut total_bytes = 0u64;
+
+        let entry_count = archive.len();
+        for i in 0..entry_count {
+            let mut entry = archive.by_index(i)?;
+            let entry_name = entry.name().to_string();
+
+            if entry.is_dir() {
+                continue;
+            }
+
+            if scanned_files.len() + skipped_files.len() >= MAX_ZIP_ENTRIES {
+                skipped_files.push(entry_name);
+                continue;
+            }
+
+            let Some(ext) = Path::new(&entry_name)
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase())
+            else {
+                continue;
+            };
+            if !SCANNABLE_EXTENSIONS.contains(&ext.as_str()) {
+                continue;
+            }
+
+            if entry.size() > MAX_ZIP_ENTRY_BYTES || total_bytes + entry.size() > MAX_ZIP_TOTAL_BYTES {
+                skipped_files.push(entry_name);
+                continue;
+            }
+
+            let mut content = String::new();
+            if entry.read_to_string(&mut content).is_err() {
+                // 非 UTF-8 内容（例如误判的二进制文件），跳过而不是整体失败
+                skipped_files.push(entry_name);
+                continue;
+            }
+            total_bytes += content.len() as u64;
+
+            let report = self.scan_file(&content, &entry_name)?;
+            worst_score = worst_score.min(report.score);
+            merged_issues.extend(report.issues);
+            scanned_files.push(entry_name);
+        }
+
+        let level = SecurityLevel::from_score(worst_score);
+        let recommendations = self.generate_recommendations(&merged_issues, worst_score);
+        let hard_trigger_issues = hard_trigger_summaries(&merged_issues);
+        let blocked = !hard_trigger_issues.is_empty();
+        let capabilities = SecurityRules::infer_capabilities("", &merged_issues);
+        let partial_scan = !skipped_files.is_empty();
+
+        Ok(SecurityReport {
+            skill_id: archive_path.display().to_string(),
+            score: worst_score,
+            level,
+            issues: merged_issues,
+            recommendations,
+            blocked,
+            hard_trigger_issues,
+            capabilities,
+            scanned_files,
+            partial_scan,
+            skipped_files,
+            advisory_db_version: None,
+        })
+    }
+
+    /// 扫描一个已安装到磁盘的 plugin/skill 目录：递归遍历，对扩展名在
+    /// [`SCANNABLE_EXTENSIONS`] 内的文本/脚本文件分别调用 [`scan_file`]，
+    /// 再补上一次 [`audit_directory_permissions`] 和一次
+    /// [`crate::security::vulnerabilities::scan_dependencies`]（按
+    /// `self.advisory_db` 对 package.json/Cargo.lock/requirements.txt 里声明
+    /// 的依赖做 CVE 匹配），最终合并成一份报告。
+    /// 与 [`scan_archive`] 不同，`score` 按合并后的全部 issue 用
+    /// `calculate_score` 统一计算，而不是取各文件中最差的一项。
+    ///
+    /// `only_files`（若提供）把实际重新扫描的范围收窄到这些相对路径，其余
+    /// 原本会被扫描的文件记入 `skipped_files` 并置 `partial_scan = true`；
+    /// 调用方负责把跳过的文件对应的历史 issue 通过
+    /// [`Self::merge_incremental_report`] 合并进最终报告。`on_file` 在每个
+    /// 实际扫描的文件完成后回调一次，供前端渲染扫描进度。
+    pub fn scan_directory_with_options(
+        &self,
+        dir: &str,
+        skill_id: &str,
+        _locale: &str,
+        options: ScanOptions,
+        only_files: Option<&[String]>,
+        mut on_file: Option<&mut dyn FnMut(&str)>,
+    ) -> Result<SecurityReport> {
+        let root = Path::new(dir);
+        let mut merged_issues = Vec::new();
+        let mut scanned_files = Vec::new();
+        let mut skipped_files = Vec::new();
+
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(current) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&current) else { continue };
+
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+
+                let Some(ext) = path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.to_lowercase())
+                else {
+                    continue;
                 };
+                if !SCANNABLE_EXTENSIONS.contains(&ext.as_str()) {
+                    continue;
+                }
+
+                let relative = path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+
+                if options.skip_readme
+                    && path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .map(|n| n.eq_ignore_ascii_case("README.md"))
+                        .unwrap_or(false)
+                {
+                    continue;
+                }
+
+                if let Some(allow) = only_files {
+                    if !allow.iter().any(|f| f == &relative) {
+                        skipped_files.push(relative);
+                        continue;
+                    }
+                }
+
+                let Ok(content) = std::fs::read_to_string(&path) else {
+                    skipped_files.push(relative);
+                    continue;
+                };
+
+                let report = self.scan_file(&content, &relative)?;
+                merged_issues.extend(report.issues);
+                scanned_files.push(relative.clone());
 
-                let severity = self.determine_severity(&category);
+                if let Some(cb) = on_file.as_deref_mut() {
+                    cb(&relative);
+                }
+            }
+        }
+
+        merged_issues.extend(audit_directory_permissions(root));
+        merged_issues.extend(scan_dependencies(root, &self.advisory_db));
+
+        let score = self.calculate_score(&merged_issues);
+        let level = SecurityLevel::from_score(score);
+        let recommendations = self.generate_recommendations(&merged_issues, score);
+        let hard_trigger_issues = hard_trigger_summaries(&merged_issues);
+        let blocked = !hard_trigger_issues.is_empty();
+        let capabilities = SecurityRules::infer_capabilities("", &merged_issues);
+        let partial_scan = only_files.is_some() || !skipped_files.is_empty();
+
+        Ok(SecurityReport {
+            skill_id: skill_id.to_string(),
+            score,
+            level,
+            issues: merged_issues,
+            recommendations,
+            blocked,
+            hard_trigger_issues,
+            capabilities,
+            scanned_files,
+            partial_scan,
+            skipped_files,
+            advisory_db_version: Some(self.advisory_db.version),
+        })
+    }
+
+    /// 把一次增量扫描的结果（`fresh`，只重新扫描了 `changed_files`）与上一次
+    /// 扫描留下的完整报告（`old`）合并：未变更文件的 issue 原样保留，
+    /// `changed_files` 里的文件只保留 `fresh` 给出的结果；目录级文件权限问题
+    /// 和依赖漏洞扫描总是取 `fresh`（两者都是每次全量重做，不区分增量，
+    /// 否则 `file_path` 为空的问题会在每次增量合并里重复累加）。
+    /// 合并后重新计算 score/level/建议/能力清单，结果与一次全量扫描的格式一致。
+    pub fn merge_incremental_report(
+        &self,
+        old: &SecurityReport,
+        fresh: &SecurityReport,
+        changed_files: &[String],
+    ) -> SecurityReport {
+        let retained_issues: Vec<SecurityIssue> = old
+            .issues
+            .iter()
+            .filter(|issue| {
+                issue.category != IssueCategory::FilePermissions
+                    && issue.category != IssueCategory::VulnerableDependency
+                    && issue
+                        .file_path
+                        .as_deref()
+                        .map(|f| !changed_files.iter().any(|c| c == f))
+                        .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
+
+        let mut issues = retained_issues;
+        issues.extend(fresh.issues.clone());
+
+        let score = self.calculate_score(&issues);
+        let level = SecurityLevel::from_score(score);
+        let recommendations = self.generate_recommendations(&issues, score);
+        let hard_trigger_issues = hard_trigger_summaries(&issues);
+        let blocked = !hard_trigger_issues.is_empty();
+
+        let mut capabilities = old.capabilities.clone();
+        capabilities.filesystem_outside_skill |= fresh.capabilities.filesystem_outside_skill;
+        capabilities.network_access |= fresh.capabilities.network_access;
+        capabilities.shell_execution |= fresh.capabilities.shell_execution;
+        capabilities.env_var_access |= fresh.capabilities.env_var_access;
+        capabilities.details.extend(fresh.capabilities.details.clone());
 
+        SecurityReport {
+            skill_id: fresh.skill_id.clone(),
+            score,
+            level,
+            issues,
+            recommendations,
+            blocked,
+            hard_trigger_issues,
+            capabilities,
+            scanned_files: fresh.scanned_files.clone(),
+            partial_scan: true,
+            skipped_files: fresh.skipped_files.clone(),
+            advisory_db_version: fresh.advisory_db_version.or(old.advisory_db_version),
+        }
+    }
+
+    /// 与 [`scan_file`] 等价，但会在 `db` 中按 `checksum` 缓存 embedding
+    /// chunk 向量：同一份内容的重复扫描直接复用缓存，不再重新调用
+    /// embedding provider。
+    pub fn scan_file_cached(
+        &self,
+        content: &str,
+        file_path: &str,
+        checksum: &str,
+        db: &Database,
+    ) -> Result<SecurityReport> {
+        let Some(detector) = &self.injection_detector else {
+            return self.scan_file(content, file_path);
+        };
+
+        let mut issues = self.line_rule_issues(content, file_path);
+
+        let cached = db.get_skill_embeddings_cache(checksum)?;
+        let semantic_issues = if let Some(cached_embeddings) = cached {
+            detector.scan_with_precomputed(content, file_path, &cached_embeddings)
+        } else {
+            let embeddings = detector.chunk_embeddings(content)?;
+            if !embeddings.is_empty() {
+                if let Err(e) = db.save_skill_embeddings_cache(checksum, &embeddings) {
+                    log::warn!("保存 embedding 缓存失败: {}", e);
+                }
+            }
+            detector.scan_with_precomputed(content, file_path, &embeddings)
+        };
+        issues.extend(semantic_issues);
+
+        Ok(self.finalize_report(content, file_path, issues))
+    }
+
+    fn line_rule_issues(&self, content: &str, file_path: &str) -> Vec<SecurityIssue> {
+        let mut issues = Vec::new();
+        for (line_num, line) in content.lines().enumerate() {
+            let findings = self.rules.check_line(line);
+            for (description, category, severity_override) in findings {
+                let severity = self.determine_severity(&category, severity_override);
                 issues.push(SecurityIssue {
                     severity,
                     category,
                     description,
                     line_number: Some(line_num + 1),
                     code_snippet: Some(line.to_string()),
+                    file_path: Some(file_path.to_string()),
                 });
             }
         }
+        issues
+    }
 
-        // 计算安全评分
+    fn finalize_report(&self, content: &str, file_path: &str, issues: Vec<SecurityIssue>) -> SecurityReport {
         let score = self.calculate_score(&issues);
         let level = SecurityLevel::from_score(score);
-
-        // 生成建议
         let recommendations = self.generate_recommendations(&issues, score);
+        let hard_trigger_issues = hard_trigger_summaries(&issues);
+        let blocked = !hard_trigger_issues.is_empty();
+        let capabilities = SecurityRules::infer_capabilities(content, &issues);
 
-        Ok(SecurityReport {
-            skill_id,
+        SecurityReport {
+            skill_id: file_path.to_string(),
             score,
             level,
             issues,
             recommendations,
-        })
+            blocked,
+            hard_trigger_issues,
+            capabilities,
+            scanned_files: vec![file_path.to_string()],
+            partial_scan: false,
+            skipped_files: Vec::new(),
+            advisory_db_version: None,
+        }
     }
 
     /// 计算安全评分（0-100分）
@@ -81,8 +512,16 @@ impl SecurityScanner {
         format!("{:x}", hasher.finalize())
     }
 
-    /// 确定问题严重程度
-    fn determine_severity(&self, category: &IssueCategory) -> IssueSeverity {
+    /// 确定问题严重程度：自定义规则可以显式指定 `override_severity`
+    /// 覆盖按类别推导的默认值；未指定时行为与内置规则完全一致。
+    fn determine_severity(
+        &self,
+        category: &IssueCategory,
+        override_severity: Option<IssueSeverity>,
+    ) -> IssueSeverity {
+        if let Some(severity) = override_severity {
+            return severity;
+        }
         match category {
             IssueCategory::ProcessExecution => IssueSeverity::Critical,
             IssueCategory::DataExfiltration => IssueSeverity::Critical,
@@ -90,6 +529,7 @@ impl SecurityScanner {
             IssueCategory::FileSystem => IssueSeverity::Warning,
             IssueCategory::DangerousFunction => IssueSeverity::Error,
             IssueCategory::ObfuscatedCode => IssueSeverity::Warning,
+            IssueCategory::FilePermissions => IssueSeverity::Warning,
             _ => IssueSeverity::Info,
         }
     }
@@ -108,6 +548,7 @@ impl SecurityScanner {
         let has_network = issues.iter().any(|i| matches!(i.category, IssueCategory::Network));
         let has_filesystem = issues.iter().any(|i| matches!(i.category, IssueCategory::FileSystem));
         let has_process = issues.iter().any(|i| matches!(i.category, IssueCategory::ProcessExecution));
+        let has_unsafe_permissions = issues.iter().any(|i| matches!(i.category, IssueCategory::FilePermissions));
 
         if has_network {
             recommendations.push("包含网络请求操作，请确认目标地址可信".to_string());
@@ -121,6 +562,10 @@ impl SecurityScanner {
             recommendations.push("包含进程执行操作，存在高风险".to_string());
         }
 
+        if has_unsafe_permissions {
+            recommendations.push("目录下存在不安全的文件权限（世界可写或 setuid/setgid），建议修正后再使用".to_string());
+        }
+
         if recommendations.is_empty() {
             recommendations.push("✅ 未发现明显安全问题".to_string());
         }
@@ -134,3 +579,110 @@ impl Default for SecurityScanner {
         Self::new()
     }
 }
+
+/// 将严重到足以阻断安装的问题渲染成可直接展示给用户的摘要文案
+fn hard_trigger_summaries(issues: &[SecurityIssue]) -> Vec<String> {
+    issues
+        .iter()
+        .filter(|i| i.severity >= HARD_TRIGGER_SEVERITY)
+        .map(|i| {
+            let file_info = i
+                .file_path
+                .as_ref()
+                .map(|f| format!("[{}] ", f))
+                .unwrap_or_default();
+            format!("{}{:?}: {}", file_info, i.severity, i.description)
+        })
+        .collect()
+}
+
+/// 递归审计 `dir` 下每个文件的 Unix 权限，标记三类问题：
+/// 世界可写（`mode & 0o002`）、组/其他用户可写的可执行文件、以及
+/// setuid/setgid 位（`mode & 0o6000`）。非 Unix 平台没有等价的 mode 位
+/// 概念，直接返回空列表。为避免异常庞大的目录拖慢扫描，最多检查
+/// [`MAX_PERMISSION_AUDIT_ENTRIES`] 个文件。
+#[cfg(unix)]
+fn audit_directory_permissions(dir: &Path) -> Vec<SecurityIssue> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut issues = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    let mut visited = 0usize;
+
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else { continue };
+
+        for entry in entries.flatten() {
+            if visited >= MAX_PERMISSION_AUDIT_ENTRIES {
+                return issues;
+            }
+            visited += 1;
+
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata() else { continue };
+
+            if metadata.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            if !metadata.is_file() {
+                continue;
+            }
+
+            let mode = metadata.permissions().mode();
+            let relative = path
+                .strip_prefix(dir)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string();
+
+            if mode & 0o6000 != 0 {
+                issues.push(SecurityIssue {
+                    severity: IssueSeverity::Critical,
+                    category: IssueCategory::FilePermissions,
+                    description: format!(
+                        "文件设置了 setuid/setgid 位 (mode {:o}): {}",
+                        mode & 0o7777,
+                        relative
+                    ),
+                    line_number: None,
+                    code_snippet: None,
+                    file_path: Some(relative.clone()),
+                });
+            }
+
+            let is_executable = mode & 0o111 != 0;
+            let group_or_other_writable = mode & 0o022 != 0;
+            if is_executable && group_or_other_writable {
+                issues.push(SecurityIssue {
+                    severity: IssueSeverity::Error,
+                    category: IssueCategory::FilePermissions,
+                    description: format!(
+                        "可执行文件对组/其他用户可写 (mode {:o}): {}",
+                        mode & 0o7777,
+                        relative
+                    ),
+                    line_number: None,
+                    code_snippet: None,
+                    file_path: Some(relative),
+                });
+            } else if mode & 0o002 != 0 {
+                issues.push(SecurityIssue {
+                    severity: IssueSeverity::Warning,
+                    category: IssueCategory::FilePermissions,
+                    description: format!("文件对所有用户可写 (mode {:o}): {}", mode & 0o7777, relative),
+                    line_number: None,
+                    code_snippet: None,
+                    file_path: Some(relative),
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(not(unix))]
+fn audit_directory_permissions(_dir: &Path) -> Vec<SecurityIssue> {
+    Vec::new()
+}
@@ -0,0 +1,188 @@
+use crate::models::{MarketplaceTrustConfig, SignatureVerification};
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// 加载一份 `trusted_signers` 配置（YAML 格式，参见
+/// [`MarketplaceTrustConfig`]）
+pub fn load_trust_config_file(path: &Path) -> Result<MarketplaceTrustConfig> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read trust config '{}'", path.display()))?;
+
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse trust config '{}'", path.display()))
+}
+
+/// 对 `repo_dir` 里的 `commit_sha` 跑一次 `git verify-commit`，判断其 GPG
+/// 签名是否来自 `trusted_signers` 中的某一个 key fingerprint。`git
+/// verify-commit` 的校验结果（`GOODSIG`/`VALIDSIG` 等 GPG status 行）写在
+/// stderr 上，而不是退出码之外的其它地方，所以这里同时看退出码与 stderr 内容。
+///
+/// `trusted_signers` 为空时直接判定失败——调用方应先用
+/// [`MarketplaceTrustConfig::signers_for`] 确认该 marketplace 确实配置了
+/// 签名校验，再调用本函数。
+pub fn verify_commit_signature(
+    repo_dir: &Path,
+    commit_sha: &str,
+    trusted_signers: &[String],
+) -> Result<SignatureVerification> {
+    if trusted_signers.is_empty() {
+        return Ok(SignatureVerification {
+            verified: false,
+            signer: None,
+            detail: "该 marketplace 未配置任何受信任签名者".to_string(),
+        });
+    }
+
+    let repo_dir_str = repo_dir.to_string_lossy().to_string();
+    let output = Command::new("git")
+        .args(["-C", &repo_dir_str, "verify-commit", "--raw", commit_sha])
+        .output()
+        .context("执行 git verify-commit 失败")?;
+
+    let status_output = String::from_utf8_lossy(&output.stderr).to_string();
+    Ok(evaluate_signature(
+        &status_output,
+        output.status.success(),
+        commit_sha,
+        trusted_signers,
+    ))
+}
+
+/// [`verify_commit_signature`] 的纯函数核心：从已经拿到的 GPG status 输出
+/// 和退出码里判定签名是否可信，拆出来是为了不需要真的调用 `git`/GnuPG 就能
+/// 做单元测试。
+fn evaluate_signature(
+    status_output: &str,
+    command_succeeded: bool,
+    commit_sha: &str,
+    trusted_signers: &[String],
+) -> SignatureVerification {
+    if !command_succeeded {
+        return SignatureVerification {
+            verified: false,
+            signer: None,
+            detail: format!("commit {} 没有有效的 GPG 签名:\n{}", commit_sha, status_output),
+        };
+    }
+
+    // 只信任 VALIDSIG 里的 key fingerprint：这一行是 GnuPG 自己校验完签名
+    // 之后吐出来的，不受签名者控制。GOODSIG 后面跟的姓名/邮箱是签名者自己在
+    // 生成密钥时填进 UID 的任意文本，谁都可以把它填成任何受信任组织的名字，
+    // 伪造一个「看起来受信任」的签名者身份——绝不能作为信任判定的依据，
+    // 这里只取它来生成人类可读的展示文案。
+    let fingerprint = parse_validsig_fingerprint(status_output);
+    let display_identity = parse_goodsig_identity(status_output);
+
+    let verified = fingerprint
+        .as_deref()
+        .map(|fp| {
+            trusted_signers
+                .iter()
+                .any(|trusted| trusted.trim().eq_ignore_ascii_case(fp))
+        })
+        .unwrap_or(false);
+
+    let signer = display_identity.or_else(|| fingerprint.clone());
+
+    let detail = match (&fingerprint, verified) {
+        (Some(fp), true) => format!("签名校验通过，签名者 key fingerprint: {}", fp),
+        (Some(fp), false) => {
+            format!("签名有效，但签名者 key fingerprint {} 不在受信任列表中", fp)
+        }
+        (None, _) => format!("无法从 git 输出中解析出签名 key fingerprint:\n{}", status_output),
+    };
+
+    SignatureVerification { verified, signer, detail }
+}
+
+/// 从 `git verify-commit --raw` 的 GPG status 输出里解析出经 GnuPG 校验过的
+/// key fingerprint：`VALIDSIG <fingerprint> <sig-creation-date> ...` 的第一个
+/// 字段。这是唯一可以用于信任判定的值。
+fn parse_validsig_fingerprint(status_output: &str) -> Option<String> {
+    for line in status_output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("[GNUPG:] VALIDSIG ") {
+            return rest.split_whitespace().next().map(|s| s.to_uppercase());
+        }
+    }
+    None
+}
+
+/// 从 `GOODSIG <keyid> <name...>` 里解析出签名者自报的姓名/邮箱，仅用于
+/// 生成展示文案，不参与信任判定（见 [`evaluate_signature`] 里的说明）。
+fn parse_goodsig_identity(status_output: &str) -> Option<String> {
+    for line in status_output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("[GNUPG:] GOODSIG ") {
+            let mut parts = rest.splitn(2, ' ');
+            let _keyid = parts.next();
+            if let Some(identity) = parts.next() {
+                return Some(identity.to_string());
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trusted_fingerprint_is_verified() {
+        let status = "[GNUPG:] GOODSIG 0123456789ABCDEF Trusted Org <releases@trusted.org>\n\
+                       [GNUPG:] VALIDSIG AAAABBBBCCCCDDDDEEEEFFFF00001111222233334444 2026-01-01 1 0 4 1 10 01 AAAABBBBCCCCDDDDEEEEFFFF00001111222233334444";
+        let trusted = vec!["AAAABBBBCCCCDDDDEEEEFFFF00001111222233334444".to_string()];
+
+        let result = evaluate_signature(status, true, "deadbeef", &trusted);
+
+        assert!(result.verified);
+    }
+
+    #[test]
+    fn spoofed_goodsig_identity_is_not_trusted_without_matching_fingerprint() {
+        // 攻击者自己生成了一个 UID 写着受信任组织名字的 keypair，GOODSIG 会
+        // 如实报告这个自报身份，但它的 VALIDSIG fingerprint 并不在允许列表里
+        let status = "[GNUPG:] GOODSIG DEADBEEFDEADBEEF Trusted Org <releases@trusted.org>\n\
+                       [GNUPG:] VALIDSIG 1111222233334444555566667777888899990000 2026-01-01 1 0 4 1 10 01 1111222233334444555566667777888899990000";
+        let trusted = vec!["AAAABBBBCCCCDDDDEEEEFFFF00001111222233334444".to_string()];
+
+        let result = evaluate_signature(status, true, "deadbeef", &trusted);
+
+        assert!(!result.verified);
+        // 展示文案可以仍然带着这个自报身份，但绝不能因为它而判定为受信任
+        assert_eq!(result.signer.as_deref(), Some("Trusted Org <releases@trusted.org>"));
+    }
+
+    #[test]
+    fn substring_match_on_display_name_is_not_enough() {
+        // 之前的实现会对 GOODSIG 展示名做 `.contains()` 子串匹配；确认同样的
+        // 输入在精确 fingerprint 比对下不再被错误地判定为通过
+        let status = "[GNUPG:] GOODSIG DEADBEEFDEADBEEF evil <evil@evil.com> impersonating Trusted-Org\n\
+                       [GNUPG:] VALIDSIG 1111222233334444555566667777888899990000 2026-01-01 1 0 4 1 10 01 1111222233334444555566667777888899990000";
+        let trusted = vec!["Trusted-Org".to_string()];
+
+        let result = evaluate_signature(status, true, "deadbeef", &trusted);
+
+        assert!(!result.verified);
+    }
+
+    #[test]
+    fn failed_command_is_not_verified() {
+        let result = evaluate_signature("gpg: no signature found", false, "deadbeef", &[
+            "AAAABBBBCCCCDDDDEEEEFFFF00001111222233334444".to_string(),
+        ]);
+
+        assert!(!result.verified);
+        assert!(result.signer.is_none());
+    }
+
+    #[test]
+    fn empty_trust_list_short_circuits_before_running_git() {
+        // 空列表时直接返回、不调用 git：repo_dir 指向不存在的目录也能拿到结果
+        let result =
+            verify_commit_signature(Path::new("/nonexistent-repo"), "deadbeef", &[]).unwrap();
+        assert!(!result.verified);
+    }
+}
@@ -0,0 +1,284 @@
+//! 依赖漏洞扫描：解析插件目录里的依赖清单（`package.json`/`Cargo.lock`/
+//! `requirements.txt`），和 [`AdvisoryDb`] 里的已知 CVE 做版本范围匹配，
+//! 产出 [`SecurityIssue`]。集成点见 [`crate::security::SecurityScanner::scan_directory_with_options`]。
+
+use crate::models::advisory::AdvisoryDb;
+use crate::models::security::{IssueCategory, SecurityIssue};
+use semver::{Version, VersionReq};
+use std::path::Path;
+
+/// 从某个依赖清单里解析出的一个声明依赖
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DeclaredPackage {
+    ecosystem: &'static str,
+    name: String,
+    version: String,
+}
+
+/// 递归收集目录下所有已知格式的依赖清单并解析成 [`DeclaredPackage`] 列表。
+/// 只认文件名，不关心目录深度——插件仓库里这三种清单通常就在根目录，
+/// 但 monorepo 风格的插件可能把它们放在子目录。
+fn collect_declared_packages(root: &Path) -> Vec<DeclaredPackage> {
+    let mut packages = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+            let Ok(content) = std::fs::read_to_string(&path) else { continue };
+
+            match name {
+                "package.json" => packages.extend(parse_package_json(&content)),
+                "Cargo.lock" => packages.extend(parse_cargo_lock(&content)),
+                "requirements.txt" => packages.extend(parse_requirements_txt(&content)),
+                _ => {}
+            }
+        }
+    }
+
+    packages
+}
+
+/// 解析 `package.json` 的 `dependencies`/`devDependencies`：版本号里常见的
+/// `^`/`~`/`>=` 等 range 前缀会被去掉，只保留具体版本号用于匹配公告范围。
+fn parse_package_json(content: &str) -> Vec<DeclaredPackage> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+
+    let mut packages = Vec::new();
+    for field in ["dependencies", "devDependencies"] {
+        let Some(deps) = value.get(field).and_then(|v| v.as_object()) else { continue };
+        for (name, version) in deps {
+            let Some(version) = version.as_str() else { continue };
+            packages.push(DeclaredPackage {
+                ecosystem: "npm",
+                name: name.clone(),
+                version: strip_version_range_prefix(version).to_string(),
+            });
+        }
+    }
+    packages
+}
+
+/// 解析 `Cargo.lock`：手写的小型 TOML 子集解析，逐行匹配 `[[package]]` 段落
+/// 里的 `name = "..."`/`version = "..."`，不引入完整 TOML 解析器。
+fn parse_cargo_lock(content: &str) -> Vec<DeclaredPackage> {
+    let mut packages = Vec::new();
+    let mut in_package_section = false;
+    let mut name: Option<String> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line == "[[package]]" {
+            in_package_section = true;
+            name = None;
+            continue;
+        }
+        if !in_package_section {
+            continue;
+        }
+        if line.starts_with('[') && line != "[[package]]" {
+            in_package_section = false;
+            name = None;
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("name = ") {
+            name = Some(value.trim_matches('"').to_string());
+        } else if let Some(value) = line.strip_prefix("version = ") {
+            if let Some(name) = name.clone() {
+                packages.push(DeclaredPackage {
+                    ecosystem: "cargo",
+                    name,
+                    version: value.trim_matches('"').to_string(),
+                });
+            }
+        }
+    }
+
+    packages
+}
+
+/// 解析 `requirements.txt`：只处理最常见的 `name==version` 精确钉版本写法，
+/// 其它形式（`name>=1.0`、`-e git+...`、无版本号）无法确定具体安装版本，跳过。
+fn parse_requirements_txt(content: &str) -> Vec<DeclaredPackage> {
+    let mut packages = Vec::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((name, version)) = line.split_once("==") else { continue };
+        packages.push(DeclaredPackage {
+            ecosystem: "pypi",
+            name: name.trim().to_string(),
+            version: version.trim().to_string(),
+        });
+    }
+    packages
+}
+
+fn strip_version_range_prefix(version: &str) -> &str {
+    version.trim_start_matches(['^', '~', '>', '<', '=', ' '])
+}
+
+/// 对目录下收集到的依赖做一次漏洞匹配，返回命中公告的 [`SecurityIssue`] 列表。
+/// `VersionReq`/`Version` 任一侧解析失败的依赖直接跳过（版本号本身不规范，
+/// 不当作漏洞处理，避免误报）。
+pub fn scan_dependencies(root: &Path, db: &AdvisoryDb) -> Vec<SecurityIssue> {
+    let declared = collect_declared_packages(root);
+    let mut issues = Vec::new();
+
+    for package in &declared {
+        let Ok(installed) = Version::parse(package.version.trim_start_matches('v')) else {
+            continue;
+        };
+
+        for advisory in db.find_matching(package.ecosystem, &package.name) {
+            let Ok(range) = VersionReq::parse(&advisory.affected_range) else {
+                continue;
+            };
+            if !range.matches(&installed) {
+                continue;
+            }
+
+            issues.push(SecurityIssue {
+                severity: advisory.severity.to_issue_severity(),
+                category: IssueCategory::VulnerableDependency,
+                description: format!(
+                    "{} {}: {} ({})",
+                    package.name,
+                    package.version,
+                    advisory.cve_id,
+                    advisory.severity.as_str()
+                ),
+                line_number: None,
+                code_snippet: None,
+                file_path: None,
+            });
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::advisory::{Advisory, VulnSeverity};
+    use std::fs;
+
+    fn advisory_db(advisories: Vec<Advisory>) -> AdvisoryDb {
+        AdvisoryDb { version: 1, advisories }
+    }
+
+    fn advisory(ecosystem: &str, package: &str, affected_range: &str, cve_id: &str) -> Advisory {
+        Advisory {
+            ecosystem: ecosystem.to_string(),
+            package: package.to_string(),
+            affected_range: affected_range.to_string(),
+            cve_id: cve_id.to_string(),
+            severity: VulnSeverity::High,
+        }
+    }
+
+    #[test]
+    fn flags_dependency_within_affected_range() {
+        let dir = tempdir();
+        fs::write(
+            dir.join("package.json"),
+            r#"{"dependencies": {"lodash": "^4.17.4"}}"#,
+        )
+        .unwrap();
+        let db = advisory_db(vec![advisory("npm", "lodash", "<4.17.5", "CVE-2019-10744")]);
+
+        let issues = scan_dependencies(&dir, &db);
+
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].description.contains("CVE-2019-10744"));
+    }
+
+    #[test]
+    fn does_not_flag_dependency_outside_affected_range() {
+        let dir = tempdir();
+        fs::write(
+            dir.join("package.json"),
+            r#"{"dependencies": {"lodash": "^4.17.21"}}"#,
+        )
+        .unwrap();
+        let db = advisory_db(vec![advisory("npm", "lodash", "<4.17.5", "CVE-2019-10744")]);
+
+        let issues = scan_dependencies(&dir, &db);
+
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn unparseable_installed_version_is_skipped_not_flagged() {
+        let dir = tempdir();
+        fs::write(
+            dir.join("requirements.txt"),
+            "django==not-a-version\n",
+        )
+        .unwrap();
+        let db = advisory_db(vec![advisory("pypi", "django", "*", "CVE-0000-0000")]);
+
+        let issues = scan_dependencies(&dir, &db);
+
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn parses_cargo_lock_packages() {
+        let content = r#"
+[[package]]
+name = "time"
+version = "0.1.42"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "serde"
+version = "1.0.0"
+"#;
+        let packages = parse_cargo_lock(content);
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "time");
+        assert_eq!(packages[0].version, "0.1.42");
+        assert_eq!(packages[0].ecosystem, "cargo");
+    }
+
+    #[test]
+    fn requirements_txt_ignores_ranges_without_exact_version() {
+        let content = "flask>=2.0\ndjango==3.2.1\n# comment\n-e git+https://example.com/foo.git\n";
+        let packages = parse_requirements_txt(content);
+
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "django");
+        assert_eq!(packages[0].version, "3.2.1");
+    }
+
+    fn tempdir() -> std::path::PathBuf {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!(
+            "agent-skills-guard-vuln-test-{}-{}",
+            std::process::id(),
+            tempdir_counter()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn tempdir_counter() -> u64 {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,1207 @@
+use crate::models::Plugin;
+use crate::services::claude_cli::{ClaudeCli, ClaudeCliResult, ClaudeCommand, ClaudeCommandOutput};
+use crate::services::operation_log::OperationLogger;
+use crate::services::plugin_backend::{
+    BackendActionOutcome, BackendAvailablePlugin, BackendInstalledPlugin, PluginBackend,
+};
+use crate::services::plugin_manager::{default_marketplace_install_location, ClaudeMarketplace};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use which::which;
+
+/// [`PluginBackend`] 实现：把插件安装/卸载/更新/同步委托给本机的 Claude Code
+/// CLI（`claude plugin ...`）。这是目前唯一注册的 backend，承载了原先散落在
+/// `PluginManager` 各个方法里的 argv 拼装与输出解析逻辑。
+pub struct ClaudeBackend;
+
+impl ClaudeBackend {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn cli(cli_command: &str) -> ClaudeCli {
+        ClaudeCli::new(cli_command.to_string())
+    }
+}
+
+impl PluginBackend for ClaudeBackend {
+    fn id(&self) -> &'static str {
+        "claude"
+    }
+
+    fn prepare(&self, cli_command: &str) -> Result<()> {
+        if which(cli_command).is_err() {
+            let mut message = format!("未找到 Claude Code CLI: {}", cli_command);
+            if which("codex").is_ok() {
+                message.push_str("\n检测到 Codex，但该流程仅支持 Claude Code Plugin。");
+            }
+            if which("opencode").is_ok() {
+                message.push_str("\n检测到 OpenCode，但该流程仅支持 Claude Code Plugin。");
+            }
+            anyhow::bail!(message);
+        }
+        Ok(())
+    }
+
+    fn install(
+        &self,
+        cli_command: &str,
+        plugin: &Plugin,
+        marketplace_repo: &str,
+        operation_logger: &OperationLogger,
+        on_output: Option<&mut dyn FnMut(&str)>,
+    ) -> Result<(BackendActionOutcome, BackendActionOutcome)> {
+        let claude_cli = Self::cli(cli_command);
+
+        let mut commands = Vec::new();
+        let add_args = plugin
+            .marketplace_add_command
+            .as_deref()
+            .and_then(parse_slash_command_args)
+            .unwrap_or_else(|| {
+                vec![
+                    "plugin".to_string(),
+                    "marketplace".to_string(),
+                    "add".to_string(),
+                    marketplace_repo.to_string(),
+                    "--json".to_string(),
+                ]
+            });
+        commands.push(ClaudeCommand {
+            args: add_args,
+            timeout: Duration::from_secs(60),
+        });
+
+        let install_args = plugin
+            .plugin_install_command
+            .as_deref()
+            .and_then(parse_slash_command_args)
+            .unwrap_or_else(|| {
+                vec![
+                    "plugin".to_string(),
+                    "install".to_string(),
+                    plugin.plugin_spec(),
+                    "--json".to_string(),
+                ]
+            });
+        commands.push(ClaudeCommand {
+            args: install_args,
+            timeout: Duration::from_secs(180),
+        });
+
+        let cli_result = run_logged(
+            operation_logger,
+            "confirm_plugin_installation",
+            &claude_cli,
+            &commands,
+            on_output,
+        )?;
+        let mut outputs = cli_result.outputs.into_iter();
+
+        let marketplace_result_output = outputs.next();
+        let marketplace_outcome = marketplace_result_output
+            .as_ref()
+            .map(parse_marketplace_add_output)
+            .unwrap_or_else(|| CommandResult::Failed {
+                reason: "未捕获到 marketplace add 命令的输出".to_string(),
+            });
+        let marketplace_output = marketplace_result_output.map(|o| o.output).unwrap_or_default();
+        let (marketplace_success, marketplace_already, marketplace_status) = match &marketplace_outcome {
+            CommandResult::Succeeded => (true, false, "added"),
+            CommandResult::AlreadyPresent => (true, true, "already_added"),
+            CommandResult::NotFound => (false, false, "failed"),
+            CommandResult::Failed { .. } => (false, false, "failed"),
+        };
+        let marketplace_result = BackendActionOutcome {
+            success: marketplace_success,
+            already: marketplace_already,
+            status: marketplace_status.to_string(),
+            raw_log: cli_result.raw_log.clone(),
+            output: marketplace_output,
+        };
+
+        let plugin_result_output = outputs.next();
+        let plugin_outcome = plugin_result_output
+            .as_ref()
+            .map(parse_plugin_install_output)
+            .unwrap_or_else(|| CommandResult::Failed {
+                reason: "未捕获到 plugin install 命令的输出".to_string(),
+            });
+        let plugin_output = plugin_result_output.map(|o| o.output).unwrap_or_default();
+        let (plugin_success, plugin_already, plugin_status) = match &plugin_outcome {
+            CommandResult::Succeeded => (true, false, "installed"),
+            CommandResult::AlreadyPresent => (true, true, "already_installed"),
+            CommandResult::NotFound => (false, false, "failed"),
+            CommandResult::Failed { .. } => (false, false, "failed"),
+        };
+        let plugin_result = BackendActionOutcome {
+            success: plugin_success,
+            already: plugin_already,
+            status: plugin_status.to_string(),
+            raw_log: cli_result.raw_log,
+            output: plugin_output,
+        };
+
+        Ok((marketplace_result, plugin_result))
+    }
+
+    fn remove(
+        &self,
+        cli_command: &str,
+        plugin: &Plugin,
+        operation_logger: &OperationLogger,
+    ) -> Result<BackendActionOutcome> {
+        let claude_cli = Self::cli(cli_command);
+        let commands = vec![ClaudeCommand {
+            args: vec![
+                "plugin".to_string(),
+                "uninstall".to_string(),
+                plugin.plugin_spec(),
+                "--json".to_string(),
+            ],
+            timeout: Duration::from_secs(60),
+        }];
+
+        let cli_result = run_logged(operation_logger, "uninstall_plugin", &claude_cli, &commands, None)?;
+        let outcome = cli_result
+            .outputs
+            .first()
+            .map(parse_plugin_uninstall_output)
+            .unwrap_or_else(|| CommandResult::Failed {
+                reason: "未捕获到 plugin uninstall 命令的输出".to_string(),
+            });
+        let output = cli_result.outputs.first().map(|o| o.output.clone()).unwrap_or_default();
+        let (already, status) = match &outcome {
+            CommandResult::Succeeded => (false, "uninstalled"),
+            CommandResult::AlreadyPresent => (false, "uninstalled"),
+            CommandResult::NotFound => (true, "uninstalled"),
+            CommandResult::Failed { .. } => (false, "uninstall_failed"),
+        };
+
+        Ok(BackendActionOutcome {
+            success: outcome.is_success(),
+            already,
+            status: status.to_string(),
+            raw_log: cli_result.raw_log,
+            output,
+        })
+    }
+
+    fn update(
+        &self,
+        cli_command: &str,
+        plugin: &Plugin,
+        operation_logger: &OperationLogger,
+    ) -> Result<BackendActionOutcome> {
+        let scope = plugin.claude_scope.clone().unwrap_or_else(|| "user".to_string());
+        let plugin_spec = plugin.claude_id.clone().unwrap_or_else(|| plugin.plugin_spec());
+
+        let claude_cli = Self::cli(cli_command);
+        let commands = vec![ClaudeCommand {
+            args: vec![
+                "plugin".to_string(),
+                "update".to_string(),
+                "--scope".to_string(),
+                scope,
+                plugin_spec,
+            ],
+            timeout: Duration::from_secs(180),
+        }];
+
+        let cli_result = run_logged(operation_logger, "update_plugin", &claude_cli, &commands, None)?;
+        let status = cli_result
+            .outputs
+            .first()
+            .map(parse_plugin_update_output)
+            .unwrap_or_else(|| "failed".to_string());
+        let output = cli_result.outputs.first().map(|o| o.output.clone()).unwrap_or_default();
+        let success = status != "failed";
+        let already = status == "already_latest";
+
+        Ok(BackendActionOutcome {
+            success,
+            already,
+            status,
+            raw_log: cli_result.raw_log,
+            output,
+        })
+    }
+
+    fn marketplace_remove(
+        &self,
+        cli_command: &str,
+        marketplace_name: &str,
+        operation_logger: &OperationLogger,
+    ) -> Result<BackendActionOutcome> {
+        let claude_cli = Self::cli(cli_command);
+        let commands = vec![ClaudeCommand {
+            args: vec![
+                "plugin".to_string(),
+                "marketplace".to_string(),
+                "remove".to_string(),
+                marketplace_name.to_string(),
+                "--json".to_string(),
+            ],
+            timeout: Duration::from_secs(60),
+        }];
+
+        let cli_result = run_logged(operation_logger, "remove_marketplace", &claude_cli, &commands, None)?;
+        let outcome = cli_result
+            .outputs
+            .first()
+            .map(parse_marketplace_remove_output)
+            .unwrap_or_else(|| CommandResult::Failed {
+                reason: "未捕获到 marketplace remove 命令的输出".to_string(),
+            });
+        let output = cli_result.outputs.first().map(|o| o.output.clone()).unwrap_or_default();
+        let (already, status) = match &outcome {
+            CommandResult::Succeeded => (false, "removed"),
+            CommandResult::AlreadyPresent => (false, "removed"),
+            CommandResult::NotFound => (true, "removed"),
+            CommandResult::Failed { .. } => (false, "failed"),
+        };
+
+        Ok(BackendActionOutcome {
+            success: outcome.is_success(),
+            already,
+            status: status.to_string(),
+            raw_log: cli_result.raw_log,
+            output,
+        })
+    }
+
+    fn list(&self, cli_command: &str) -> Result<Vec<BackendInstalledPlugin>> {
+        let claude_cli = Self::cli(cli_command);
+        let commands = vec![
+            ClaudeCommand {
+                args: vec![
+                    "plugin".to_string(),
+                    "marketplace".to_string(),
+                    "list".to_string(),
+                    "--json".to_string(),
+                ],
+                timeout: Duration::from_secs(15),
+            },
+            ClaudeCommand {
+                args: vec!["plugin".to_string(), "list".to_string(), "--json".to_string()],
+                timeout: Duration::from_secs(15),
+            },
+        ];
+
+        let cli_result = claude_cli.run(&commands)?;
+        let marketplace_output = cli_result.outputs.get(0).map(|o| o.output.as_str()).unwrap_or_default();
+        let plugins_output = cli_result.outputs.get(1).map(|o| o.output.as_str()).unwrap_or_default();
+
+        let marketplaces: Vec<ClaudeMarketplaceListEntry> = match parse_json_output(marketplace_output) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("解析 `claude plugin marketplace list --json` 失败，尝试解析文本输出: {}", e);
+                parse_marketplace_list_text(marketplace_output)
+                    .into_iter()
+                    .map(|m| ClaudeMarketplaceListEntry {
+                        name: m.name,
+                        source: m.source,
+                        repo: m.repo,
+                        install_location: m.install_location,
+                    })
+                    .collect()
+            }
+        };
+        let installed_plugins: Vec<ClaudeInstalledPluginEntry> = parse_json_output(plugins_output)
+            .context("解析 `claude plugin list --json` 输出失败")?;
+
+        let mut marketplace_repo_url_by_name: HashMap<String, String> = HashMap::new();
+        for entry in marketplaces {
+            if let Some(repo_url) = marketplace_repo_url(&entry) {
+                marketplace_repo_url_by_name.insert(entry.name, repo_url);
+            }
+        }
+
+        let mut result = Vec::new();
+        for entry in installed_plugins {
+            let Some((name, marketplace_name)) = parse_claude_plugin_id(&entry.id) else {
+                log::warn!("无法解析 Claude plugin id: {}", entry.id);
+                continue;
+            };
+
+            result.push(BackendInstalledPlugin {
+                backend_plugin_id: entry.id,
+                marketplace_repository_url: marketplace_repo_url_by_name.get(&marketplace_name).cloned(),
+                name,
+                marketplace_name,
+                version: entry.version,
+                scope: entry.scope,
+                enabled: entry.enabled,
+                install_path: entry.install_path,
+                installed_at: parse_datetime(&entry.installed_at),
+                last_updated: parse_datetime(&entry.last_updated),
+            });
+        }
+
+        Ok(result)
+    }
+
+    fn check_updates(&self, cli_command: &str) -> Result<Vec<BackendAvailablePlugin>> {
+        let claude_cli = Self::cli(cli_command);
+        let commands = vec![ClaudeCommand {
+            args: vec![
+                "plugin".to_string(),
+                "list".to_string(),
+                "--json".to_string(),
+                "--available".to_string(),
+            ],
+            timeout: Duration::from_secs(30),
+        }];
+
+        let cli_result = claude_cli.run(&commands)?;
+        let output = cli_result.outputs.first().map(|o| o.output.as_str()).unwrap_or_default();
+        let payload = parse_claude_plugin_list_with_available(output)
+            .context("解析 `claude plugin list --json --available` 输出失败")?;
+
+        Ok(payload
+            .available
+            .into_iter()
+            .map(|entry| BackendAvailablePlugin {
+                backend_plugin_id: entry.plugin_id,
+                name: entry.name,
+                marketplace_name: entry.marketplace_name,
+                version: entry.version,
+            })
+            .collect())
+    }
+}
+
+/// 执行一组 Claude CLI 命令并把完整命令行、流式输出、最终退出状态写入按时间戳
+/// 命名的操作日志文件；失败时把日志路径拼进错误信息，方便 UI 引导用户直接打开
+/// 对应日志而不是只看到一句扁平的错误文案。
+pub(crate) fn run_logged(
+    operation_logger: &OperationLogger,
+    operation: &str,
+    claude_cli: &ClaudeCli,
+    commands: &[ClaudeCommand],
+    mut on_chunk: Option<&mut dyn FnMut(&str)>,
+) -> Result<ClaudeCliResult> {
+    let log = operation_logger.start(operation)?;
+    for command in commands {
+        log.log_command(&command.args.join(" "));
+    }
+
+    let mut tee = |chunk: &str| {
+        log.log_output(chunk);
+        if let Some(cb) = on_chunk.as_deref_mut() {
+            cb(chunk);
+        }
+    };
+
+    match claude_cli.run_with_output(commands, Some(&mut tee)) {
+        Ok(cli_result) => {
+            for output in &cli_result.outputs {
+                log.log_exit_status(&format!("{}: {}", output.command, output.exit_summary));
+            }
+            Ok(cli_result)
+        }
+        Err(e) => {
+            log.log_exit_status(&format!("error: {}", e));
+            anyhow::bail!(log.fail_message(&e.to_string()));
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ClaudeMarketplaceListEntry {
+    pub name: String,
+    #[allow(dead_code)]
+    pub source: Option<String>,
+    pub repo: Option<String>,
+    #[serde(rename = "installLocation", alias = "install_location")]
+    pub install_location: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct ClaudeInstalledPluginEntry {
+    pub id: String,
+    pub version: Option<String>,
+    scope: Option<String>,
+    enabled: Option<bool>,
+    #[serde(rename = "installPath", alias = "install_path")]
+    install_path: Option<String>,
+    #[serde(rename = "installedAt", alias = "installed_at")]
+    installed_at: Option<String>,
+    #[serde(rename = "lastUpdated", alias = "last_updated")]
+    last_updated: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct ClaudeAvailablePluginEntry {
+    #[serde(rename = "pluginId", alias = "plugin_id")]
+    pub plugin_id: String,
+    pub name: Option<String>,
+    #[serde(rename = "marketplaceName", alias = "marketplace_name", alias = "marketplace")]
+    pub marketplace_name: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ClaudePluginListWithAvailable {
+    #[serde(default, alias = "installedPlugins")]
+    pub installed: Vec<ClaudeInstalledPluginEntry>,
+    #[serde(default, alias = "availablePlugins")]
+    pub available: Vec<ClaudeAvailablePluginEntry>,
+}
+
+pub(crate) fn parse_claude_plugin_id(id: &str) -> Option<(String, String)> {
+    let (name, marketplace) = id.rsplit_once('@')?;
+    if name.is_empty() || marketplace.is_empty() {
+        return None;
+    }
+    Some((name.to_string(), marketplace.to_string()))
+}
+
+pub(crate) fn parse_slash_command_args(command: &str) -> Option<Vec<String>> {
+    let trimmed = command.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let trimmed = trimmed.strip_prefix('/').unwrap_or(trimmed);
+    let parts: Vec<String> = trimmed.split_whitespace().map(|s| s.to_string()).collect();
+    if parts.first().map(|s| s.as_str()) != Some("plugin") {
+        return None;
+    }
+    Some(parts)
+}
+
+pub(crate) fn extract_marketplace_repo_from_command(command: &str) -> Option<String> {
+    let parts = parse_slash_command_args(command)?;
+    if parts.len() >= 4
+        && parts[0] == "plugin"
+        && parts[1] == "marketplace"
+        && parts[2] == "add"
+    {
+        return Some(parts[3].clone());
+    }
+    None
+}
+
+pub(crate) fn marketplace_repo_url(entry: &ClaudeMarketplaceListEntry) -> Option<String> {
+    let repo = entry.repo.as_deref()?.trim();
+    if repo.is_empty() {
+        return None;
+    }
+
+    // Claude CLI 的 github source 通常返回 owner/repo
+    if repo.starts_with("http://") || repo.starts_with("https://") {
+        return Some(repo.to_string());
+    }
+
+    Some(format!("https://github.com/{}", repo))
+}
+
+pub(crate) fn parse_datetime(value: &Option<String>) -> Option<DateTime<Utc>> {
+    value.as_ref().and_then(|s| s.parse().ok())
+}
+
+pub(crate) fn parse_claude_plugin_list_with_available(output: &str) -> Result<ClaudePluginListWithAvailable> {
+    let cleaned = strip_terminal_escapes(output);
+
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(&cleaned) {
+        if json_has_plugin_list_fields(&value) {
+            return serde_json::from_value(value).context("JSON 解析失败");
+        }
+    }
+
+    if let Some(value) = find_json_value_with_predicate(&cleaned, json_has_plugin_list_fields) {
+        return serde_json::from_value(value).context("JSON 解析失败");
+    }
+
+    parse_json_output(&cleaned).context("JSON 解析失败")
+}
+
+pub(crate) fn parse_json_output<T: for<'de> Deserialize<'de>>(output: &str) -> Result<T> {
+    let cleaned = strip_terminal_escapes(output);
+    // 1) 优先直接解析（输出本身就是纯 JSON 的情况）
+    if let Ok(value) = serde_json::from_str::<T>(&cleaned) {
+        return Ok(value);
+    }
+
+    // 2) 兼容：输出前后混有提示符/日志/ANSI 等，尝试提取一个完整 JSON 值并解析
+    if let Ok(value) = parse_first_json_value::<T>(&cleaned) {
+        return Ok(value);
+    }
+
+    // 3) 兜底：旧逻辑（按首尾括号截取），有助于处理一些更“干净但带前缀”的输出
+    let payload = extract_json_payload(&cleaned).unwrap_or(cleaned.as_str());
+    serde_json::from_str(payload).context("JSON 解析失败")
+}
+
+fn extract_json_payload(output: &str) -> Option<&str> {
+    let start = output.find(|c| c == '{' || c == '[')?;
+    let end = output.rfind(|c| c == '}' || c == ']')?;
+    if end < start {
+        return None;
+    }
+    Some(&output[start..=end])
+}
+
+fn parse_first_json_value<T: for<'de> Deserialize<'de>>(output: &str) -> Result<T> {
+    // 通过 serde_json 的流式反序列化能力，从任意位置尝试解析出“第一个匹配的 JSON 值”
+    // 这样可以兼容 PowerShell/Terminal 的提示符、以及 CLI 可能输出的非 JSON 文本或日志。
+    let bytes = output.as_bytes();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let offset = match bytes[pos..].iter().position(|b| *b == b'{' || *b == b'[') {
+            Some(value) => value,
+            None => break,
+        };
+        let start = pos + offset;
+        let slice = &output[start..];
+        let mut stream = serde_json::Deserializer::from_str(slice).into_iter::<serde_json::Value>();
+        let value = match stream.next() {
+            Some(Ok(v)) => v,
+            _ => {
+                pos = start + 1;
+                continue;
+            }
+        };
+
+        let end = stream.byte_offset();
+        if end == 0 || end > slice.len() {
+            pos = start + 1;
+            continue;
+        }
+
+        // 只取 JSON 值本体，忽略后续的任何噪声输出
+        let payload = &slice[..end];
+        match serde_json::from_str::<T>(payload)
+            .or_else(|_| serde_json::from_value::<T>(value))
+        {
+            Ok(parsed) => return Ok(parsed),
+            Err(_) => {
+                pos = start + end;
+                continue;
+            }
+        }
+    }
+
+    anyhow::bail!("JSON 解析失败");
+}
+
+fn json_has_plugin_list_fields(value: &serde_json::Value) -> bool {
+    let Some(obj) = value.as_object() else {
+        return false;
+    };
+
+    obj.contains_key("installed")
+        || obj.contains_key("available")
+        || obj.contains_key("installedPlugins")
+        || obj.contains_key("availablePlugins")
+}
+
+fn find_json_value_with_predicate<F>(output: &str, predicate: F) -> Option<serde_json::Value>
+where
+    F: Fn(&serde_json::Value) -> bool,
+{
+    let bytes = output.as_bytes();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let offset = match bytes[pos..].iter().position(|b| *b == b'{' || *b == b'[') {
+            Some(value) => value,
+            None => break,
+        };
+        let start = pos + offset;
+        let slice = &output[start..];
+        let mut stream = serde_json::Deserializer::from_str(slice).into_iter::<serde_json::Value>();
+        let value = match stream.next() {
+            Some(Ok(v)) => v,
+            _ => {
+                pos = start + 1;
+                continue;
+            }
+        };
+
+        let end = stream.byte_offset();
+        if end == 0 || end > slice.len() {
+            pos = start + 1;
+            continue;
+        }
+
+        if predicate(&value) {
+            return Some(value);
+        }
+
+        pos = start + end;
+    }
+
+    None
+}
+
+pub(crate) fn strip_terminal_escapes(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        if ch == '\u{001b}' {
+            // CSI: ESC [
+            if i + 1 < chars.len() && chars[i + 1] == '[' {
+                i += 2;
+                while i < chars.len() {
+                    let c = chars[i];
+                    // CSI sequences typically end with a byte in @..~
+                    if ('@'..='~').contains(&c) {
+                        i += 1;
+                        break;
+                    }
+                    i += 1;
+                }
+                continue;
+            }
+
+            // OSC: ESC ]
+            if i + 1 < chars.len() && chars[i + 1] == ']' {
+                i += 2;
+                while i < chars.len() {
+                    let c = chars[i];
+                    // BEL ends OSC
+                    if c == '\u{0007}' {
+                        i += 1;
+                        break;
+                    }
+                    // ST ends OSC: ESC \
+                    if c == '\u{001b}' && i + 1 < chars.len() && chars[i + 1] == '\\' {
+                        i += 2;
+                        break;
+                    }
+                    i += 1;
+                }
+                continue;
+            }
+
+            // Other ESC sequences: best-effort skip next char
+            i += 1;
+            if i < chars.len() {
+                i += 1;
+            }
+            continue;
+        }
+
+        out.push(ch);
+        i += 1;
+    }
+
+    out
+}
+
+pub(crate) fn parse_marketplace_list_text(output: &str) -> Vec<ClaudeMarketplace> {
+    let cleaned = strip_terminal_escapes(output);
+    let mut results: Vec<ClaudeMarketplace> = Vec::new();
+    let mut current_index: Option<usize> = None;
+
+    for raw_line in cleaned.lines() {
+        let line = raw_line.trim_end();
+        let trimmed = line.trim_start();
+
+        if let Some(rest) = trimmed.strip_prefix('>') {
+            let name = rest.trim().to_string();
+            if name.is_empty() {
+                continue;
+            }
+            let install_location = default_marketplace_install_location(&name);
+            results.push(ClaudeMarketplace {
+                name,
+                source: None,
+                repo: None,
+                repository_url: None,
+                install_location,
+            });
+            current_index = Some(results.len() - 1);
+            continue;
+        }
+
+        let Some(idx) = current_index else { continue };
+        if !trimmed.to_lowercase().starts_with("source:") {
+            continue;
+        }
+
+        // Example:
+        // Source: GitHub (anthropics/claude-plugins-official)
+        // Source: URL (https://...)
+        let after = trimmed.splitn(2, ':').nth(1).unwrap_or("").trim();
+        if after.is_empty() {
+            continue;
+        }
+
+        let (source_text, paren) = match after.split_once('(') {
+            Some((a, b)) => (a.trim(), Some(b.trim_end_matches(')').trim())),
+            None => (after.trim(), None),
+        };
+
+        if !source_text.is_empty() {
+            results[idx].source = Some(source_text.to_string());
+        }
+
+        if let Some(value) = paren {
+            if !value.is_empty() {
+                // GitHub: owner/repo; URL/Local: value as-is
+                results[idx].repo = Some(value.to_string());
+                results[idx].repository_url = if value.starts_with("http://") || value.starts_with("https://") {
+                    Some(value.to_string())
+                } else if value.contains('/') {
+                    Some(format!("https://github.com/{}", value))
+                } else {
+                    None
+                };
+            }
+        }
+    }
+
+    results
+}
+
+pub(crate) fn parse_plugin_update_output(output: &ClaudeCommandOutput) -> String {
+    if output.timed_out {
+        return "failed".to_string();
+    }
+
+    let heuristic = parse_plugin_update_output_heuristic(&output.output);
+    match output.exit_code {
+        // 退出码为 0 但文本没猜出已知状态：相信退出码，按更新成功处理
+        Some(0) if heuristic == "failed" => "updated".to_string(),
+        // 退出码非零：无论文本怎么说都是失败
+        Some(code) if code != 0 => "failed".to_string(),
+        // 退出状态未知，退回纯文本启发式
+        _ => heuristic,
+    }
+}
+
+fn parse_plugin_update_output_heuristic(output: &str) -> String {
+    let text = output.to_lowercase();
+    if text.contains("already") && text.contains("latest") {
+        return "already_latest".to_string();
+    }
+    if text.contains("success") && text.contains("updated") {
+        return "updated".to_string();
+    }
+    if text.contains("updated") {
+        return "updated".to_string();
+    }
+    "failed".to_string()
+}
+
+/// 单次 CLI 操作（marketplace add/remove/update、plugin install/uninstall）的
+/// 结构化结果。相比旧的 `CommandOutcome { success, already }` 两个布尔值，这里
+/// 多留了"目标本就不存在"一档，且失败原因不再只留在日志里——调用方可以穷尽
+/// 匹配这四种结果，而不是靠布尔组合去猜。
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CommandResult {
+    /// 操作本身成功执行（新增 marketplace / 新装 / 新卸载 / 新更新）
+    Succeeded,
+    /// 目标在操作前就已经处于期望状态（marketplace 已存在、plugin 已装、已是最新）
+    AlreadyPresent,
+    /// 目标不存在（卸载一个本来就没装的 plugin、移除一个不存在的 marketplace）
+    NotFound,
+    /// 明确失败，保留原因用于写入 `install_log`
+    Failed { reason: String },
+}
+
+impl CommandResult {
+    /// 是否达成了操作的目的——卸载/移除场景下，目标本就不存在也算
+    pub(crate) fn is_success(&self) -> bool {
+        !matches!(self, CommandResult::Failed { .. })
+    }
+}
+
+/// 用 CLI 进程的退出码/超时状态校正一次纯文本启发式判断的结果：
+/// - 超时被强杀：无论文本怎么写，都是明确失败，原因直接说明是超时强杀
+/// - 退出码非零：文本猜出的"已存在/未找到"更细，予以保留；猜不出更细状态时
+///   退出码本身就足以判定失败，不必再纠结文案
+/// - 退出码为 0：命令本身跑成功了，启发式猜出的"失败"大概率是匹配到了噪音
+///   行（比如把进度输出里的 "error" 当成了错误），按成功处理
+/// - 退出状态未知（被信号杀死、或者这次调用压根拿不到退出状态）：没有更可靠
+///   的依据，原样相信文本启发式
+fn refine_with_exit_status(heuristic: CommandResult, output: &ClaudeCommandOutput) -> CommandResult {
+    if output.timed_out {
+        return CommandResult::Failed {
+            reason: format!("命令执行超时（{}），已终止进程及其子进程", output.exit_summary),
+        };
+    }
+
+    match output.exit_code {
+        Some(0) => match heuristic {
+            CommandResult::Failed { .. } => CommandResult::Succeeded,
+            other => other,
+        },
+        Some(_) => match heuristic {
+            CommandResult::AlreadyPresent | CommandResult::NotFound => heuristic,
+            _ => CommandResult::Failed {
+                reason: output.output.trim().to_string(),
+            },
+        },
+        None => heuristic,
+    }
+}
+
+/// 期望从 `--json` 输出里读出的显式状态字段；字段名按 Claude Code 实际可能
+/// 返回的几种变体做了宽松匹配（camelCase/snake_case 都接受），解析不出就说明
+/// 这次输出没有可用的结构化状态，调用方应退回关键字启发式判断。
+#[derive(Debug, Deserialize)]
+struct ClaudeCommandStatusPayload {
+    #[serde(alias = "result", alias = "state")]
+    status: Option<String>,
+    #[serde(alias = "alreadyInstalled", alias = "already_installed", alias = "alreadyAdded", alias = "already_added", alias = "alreadyExists", alias = "already_exists")]
+    already: Option<bool>,
+    success: Option<bool>,
+    #[serde(alias = "message", alias = "reason", alias = "detail")]
+    error: Option<String>,
+}
+
+/// 在 `output` 里找第一个带 `status`/`result`/`success` 字段的 JSON 值并解析成
+/// [`CommandResult`]；找不到 JSON，或 JSON 里的状态取值无法识别，都返回
+/// `None`，由调用方退回关键字启发式判断。也供
+/// [`crate::services::plugin_manager::PluginManager::run_plugin_lifecycle_hook`]
+/// 复用来解析生命周期钩子脚本的输出。
+pub(crate) fn parse_json_command_result(output: &str) -> Option<CommandResult> {
+    let cleaned = strip_terminal_escapes(output);
+    let value = find_json_value_with_predicate(&cleaned, |v| {
+        v.as_object()
+            .map(|o| o.contains_key("status") || o.contains_key("result") || o.contains_key("success"))
+            .unwrap_or(false)
+    })?;
+    let payload: ClaudeCommandStatusPayload = serde_json::from_value(value).ok()?;
+
+    if let Some(status) = payload.status.as_deref().map(|s| s.to_lowercase()) {
+        return match status.as_str() {
+            "success" | "succeeded" | "ok" | "installed" | "added" | "uninstalled" | "removed" | "updated" => {
+                Some(CommandResult::Succeeded)
+            }
+            s if s.contains("already") => Some(CommandResult::AlreadyPresent),
+            s if s.contains("not_found") || s.contains("not found") || s.contains("not_installed") => {
+                Some(CommandResult::NotFound)
+            }
+            "failed" | "error" => Some(CommandResult::Failed {
+                reason: payload.error.unwrap_or_else(|| output.trim().to_string()),
+            }),
+            _ => None,
+        };
+    }
+
+    let success = payload.success?;
+    if !success {
+        return Some(CommandResult::Failed {
+            reason: payload.error.unwrap_or_else(|| output.trim().to_string()),
+        });
+    }
+    Some(if payload.already.unwrap_or(false) {
+        CommandResult::AlreadyPresent
+    } else {
+        CommandResult::Succeeded
+    })
+}
+
+pub(crate) fn parse_marketplace_add_output(output: &ClaudeCommandOutput) -> CommandResult {
+    if let Some(result) = parse_json_command_result(&output.output) {
+        return result;
+    }
+    refine_with_exit_status(parse_marketplace_add_output_heuristic(&output.output), output)
+}
+
+fn parse_marketplace_add_output_heuristic(output: &str) -> CommandResult {
+    let text = output.to_lowercase();
+
+    // 检查是否已存在（优先判断，因为 Claude Code 输出可能是 "Failed to add: already installed"）
+    let already = text.contains("already") && (text.contains("marketplace") || text.contains("exists") || text.contains("added") || text.contains("installed"));
+
+    // 如果已存在，直接视为成功
+    if already {
+        return CommandResult::AlreadyPresent;
+    }
+
+    // 检查是否有明确的失败信息
+    let has_error = text.contains("error")
+        || text.contains("failed")
+        || text.contains("failure")
+        || text.contains("unable to")
+        || text.contains("could not");
+
+    // 检查成功情况（排除错误情况）
+    let success = !has_error && (
+        text.contains("marketplace added")
+        || text.contains("added marketplace")
+        || text.contains("successfully added")
+        || (text.contains("marketplace") && text.contains("added") && !text.contains("not added"))
+    );
+
+    if success {
+        CommandResult::Succeeded
+    } else {
+        CommandResult::Failed { reason: output.trim().to_string() }
+    }
+}
+
+pub(crate) fn parse_plugin_install_output(output: &ClaudeCommandOutput) -> CommandResult {
+    if let Some(result) = parse_json_command_result(&output.output) {
+        return result;
+    }
+    refine_with_exit_status(parse_plugin_install_output_heuristic(&output.output), output)
+}
+
+fn parse_plugin_install_output_heuristic(output: &str) -> CommandResult {
+    let text = output.to_lowercase();
+
+    // 检查是否有明确的失败信息
+    let has_error = text.contains("error")
+        || text.contains("failed")
+        || text.contains("failure")
+        || text.contains("unable to")
+        || text.contains("could not");
+
+    // 检查是否未安装（否定）
+    let not_installed = text.contains("not installed") || text.contains("not found");
+
+    // 检查是否已存在
+    let already = text.contains("already installed") || text.contains("already exists");
+
+    // 检查成功情况（排除错误和否定情况）
+    let success = !has_error && !not_installed && (
+        already
+        || text.contains("successfully installed")
+        || text.contains("installation complete")
+        || text.contains("install success")
+        || text.contains("plugin installed")
+        // 只有当 "installed" 不是在否定上下文中出现时才算成功
+        || (text.contains("installed") && !text.contains("not installed") && !text.contains("isn't installed"))
+    );
+
+    if !success {
+        CommandResult::Failed { reason: output.trim().to_string() }
+    } else if already {
+        CommandResult::AlreadyPresent
+    } else {
+        CommandResult::Succeeded
+    }
+}
+
+pub(crate) fn parse_plugin_uninstall_output(output: &ClaudeCommandOutput) -> CommandResult {
+    if let Some(result) = parse_json_command_result(&output.output) {
+        return result;
+    }
+    refine_with_exit_status(parse_plugin_uninstall_output_heuristic(&output.output), output)
+}
+
+fn parse_plugin_uninstall_output_heuristic(output: &str) -> CommandResult {
+    let text = output.to_lowercase();
+
+    // 检查是否本来就未安装（可视为"成功"卸载）
+    // 优先检查这个，因为 "not found" 比一般错误更具体
+    let not_installed = text.contains("not installed")
+        || text.contains("not found")
+        || text.contains("doesn't exist")
+        || (text.contains("not found") && text.contains("installed plugins"));
+
+    if not_installed {
+        return CommandResult::NotFound;
+    }
+
+    // 检查是否有明确的失败信息
+    let has_error = text.contains("error")
+        || text.contains("failed")
+        || text.contains("failure")
+        || text.contains("unable to")
+        || text.contains("could not");
+
+    // 检查成功情况
+    let success = !has_error && (
+        text.contains("successfully uninstalled")
+        || text.contains("uninstall success")
+        || text.contains("plugin uninstalled")
+        || text.contains("removed")
+        || (text.contains("uninstalled") && !text.contains("not uninstalled"))
+    );
+
+    if success {
+        CommandResult::Succeeded
+    } else {
+        CommandResult::Failed { reason: output.trim().to_string() }
+    }
+}
+
+pub(crate) fn parse_marketplace_remove_output(output: &ClaudeCommandOutput) -> CommandResult {
+    if let Some(result) = parse_json_command_result(&output.output) {
+        return result;
+    }
+    refine_with_exit_status(parse_marketplace_remove_output_heuristic(&output.output), output)
+}
+
+fn parse_marketplace_remove_output_heuristic(output: &str) -> CommandResult {
+    let text = output.to_lowercase();
+
+    // 检查是否本来就不存在（可视为"成功"移除）
+    let not_found = text.contains("not found")
+        || text.contains("doesn't exist")
+        || (text.contains("marketplace") && text.contains("not found"));
+
+    if not_found {
+        return CommandResult::NotFound;
+    }
+
+    // 检查是否有明确的失败信息
+    let has_error = text.contains("error")
+        || text.contains("failed")
+        || text.contains("failure")
+        || text.contains("unable to")
+        || text.contains("could not");
+
+    // 检查成功情况
+    let success = !has_error && (
+        text.contains("successfully removed")
+        || text.contains("marketplace removed")
+        || text.contains("removed marketplace")
+        || text.contains("uninstalled")
+        || (text.contains("removed") && !text.contains("not removed"))
+    );
+
+    if success {
+        CommandResult::Succeeded
+    } else {
+        CommandResult::Failed { reason: output.trim().to_string() }
+    }
+}
+
+pub(crate) fn parse_marketplace_update_output(output: &ClaudeCommandOutput) -> CommandResult {
+    if let Some(result) = parse_json_command_result(&output.output) {
+        return result;
+    }
+    refine_with_exit_status(parse_marketplace_update_output_heuristic(&output.output), output)
+}
+
+fn parse_marketplace_update_output_heuristic(output: &str) -> CommandResult {
+    let text = output.to_lowercase();
+
+    if text.contains("already up to date") {
+        return CommandResult::AlreadyPresent;
+    }
+
+    let success = text.contains("successfully updated marketplace")
+        || (text.contains("updated") && text.contains("marketplace") && !text.contains("failed"));
+
+    if success {
+        CommandResult::Succeeded
+    } else {
+        CommandResult::Failed { reason: output.trim().to_string() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_claude_plugin_list_with_available_from_powershell_output() {
+        let output = r#"PS C:\Users\Bruce> claude plugin list --json --available
+{
+  "installed": [
+    {
+      "id": "superpowers@superpowers-marketplace",
+      "version": "4.0.3",
+      "scope": "user",
+      "enabled": false,
+      "installPath": "C:\\Users\\Bruce\\.claude\\plugins\\cache\\superpowers-marketplace\\superpowers\\4.0.3",
+      "installedAt": "2025-12-26T01:58:19.521Z",
+      "lastUpdated": "2026-01-14T01:51:11.830Z"
+    }
+  ],
+  "available": [
+    {
+      "pluginId": "superpowers@claude-plugins-official",
+      "name": "superpowers",
+      "marketplaceName": "claude-plugins-official",
+      "version": "4.0.4",
+      "source": {
+        "source": "url",
+        "url": "https://github.com/obra/superpowers.git"
+      },
+      "installCount": 123
+    }
+  ]
+}
+PS C:\Users\Bruce> "#;
+
+        let payload: ClaudePluginListWithAvailable = parse_json_output(output).unwrap();
+        assert_eq!(payload.installed.len(), 1);
+        assert_eq!(payload.installed[0].id, "superpowers@superpowers-marketplace");
+        assert_eq!(payload.available.len(), 1);
+        assert_eq!(payload.available[0].plugin_id, "superpowers@claude-plugins-official");
+        assert_eq!(
+            payload.available[0].marketplace_name.as_deref(),
+            Some("claude-plugins-official")
+        );
+        assert_eq!(payload.available[0].version.as_deref(), Some("4.0.4"));
+    }
+
+    #[test]
+    fn parse_claude_plugin_list_with_available_accepts_snake_case_fields() {
+        let output = r#"
+noise before json...
+{
+  "installed": [
+    {
+      "id": "foo@bar",
+      "version": "1.0.0",
+      "install_path": "/Users/a/.claude/plugins/cache/bar/foo/1.0.0",
+      "installed_at": "2026-01-01T00:00:00Z",
+      "last_updated": "2026-01-02T00:00:00Z"
+    }
+  ],
+  "available": [
+    {
+      "plugin_id": "foo@bar",
+      "marketplace_name": "bar",
+      "version": "1.0.1"
+    }
+  ]
+}
+noise after json..."#;
+
+        let payload: ClaudePluginListWithAvailable = parse_json_output(output).unwrap();
+        assert_eq!(payload.installed.len(), 1);
+        assert_eq!(payload.installed[0].install_path.as_deref(), Some("/Users/a/.claude/plugins/cache/bar/foo/1.0.0"));
+        assert_eq!(payload.available.len(), 1);
+        assert_eq!(payload.available[0].plugin_id, "foo@bar");
+        assert_eq!(payload.available[0].marketplace_name.as_deref(), Some("bar"));
+        assert_eq!(payload.available[0].version.as_deref(), Some("1.0.1"));
+    }
+
+    #[test]
+    fn parse_claude_plugin_list_with_available_skips_unrelated_json() {
+        let output = r#"
+{"event":"progress","message":"fetching"}
+{
+  "installed": [
+    {
+      "id": "sample@market",
+      "version": "1.0.0"
+    }
+  ],
+  "available": [
+    {
+      "pluginId": "sample@market",
+      "marketplaceName": "market",
+      "version": "1.1.0"
+    }
+  ]
+}
+"#;
+
+        let payload = parse_claude_plugin_list_with_available(output).unwrap();
+        assert_eq!(payload.installed.len(), 1);
+        assert_eq!(payload.installed[0].id, "sample@market");
+        assert_eq!(payload.available.len(), 1);
+        assert_eq!(payload.available[0].plugin_id, "sample@market");
+        assert_eq!(payload.available[0].version.as_deref(), Some("1.1.0"));
+    }
+}
@@ -1,9 +1,11 @@
 use anyhow::{Context, Result};
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+use regex::Regex;
 use std::io::{Read, Write};
 use std::sync::mpsc::{self, RecvTimeoutError};
 use std::thread;
 use std::time::{Duration, Instant};
+use sysinfo::{Pid, Signal, System};
 
 pub struct ClaudeCommand {
     pub args: Vec<String>,
@@ -13,6 +15,18 @@ pub struct ClaudeCommand {
 pub struct ClaudeCommandOutput {
     pub command: String,
     pub output: String,
+    /// 归一化后的退出状态描述（例如 `"exit code: 0"`），屏蔽了
+    /// `std::process::ExitStatus` 在 Unix 上渲染为 "exit status: N"、
+    /// 在 Windows 上渲染为 "exit code: N" 的平台差异
+    pub exit_summary: String,
+    /// 正常退出时的数字退出码；被信号终止、超时强杀、或退出状态未知时为 `None`。
+    /// 调用方应优先信任这个值而不是对 `output` 做子串匹配——解析不出结构化
+    /// JSON 时，退出码非零足以判定失败，不需要再去猜测文案
+    pub exit_code: Option<i32>,
+    /// 是否因为超过 `ClaudeCommand::timeout` 被强制终止（见
+    /// [`terminate_process_tree`]）；为 `true` 时 `exit_code`/`exit_summary`
+    /// 反映的是被强杀之后的状态，不代表命令本身的执行结果
+    pub timed_out: bool,
 }
 
 pub struct ClaudeCliResult {
@@ -20,31 +34,189 @@ pub struct ClaudeCliResult {
     pub raw_log: String,
 }
 
+/// 用于匹配交互式提示文本的模式：可以是大小写不敏感的子串，也可以是正则
+pub enum PromptPattern {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl PromptPattern {
+    pub fn substring(needle: impl Into<String>) -> Self {
+        PromptPattern::Substring(needle.into().to_lowercase())
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Ok(PromptPattern::Regex(
+            Regex::new(pattern).context("无效的 prompt 匹配正则")?,
+        ))
+    }
+
+    fn matches(&self, buffer: &str) -> bool {
+        match self {
+            PromptPattern::Substring(needle) => buffer.to_lowercase().contains(needle.as_str()),
+            PromptPattern::Regex(re) => re.is_match(buffer),
+        }
+    }
+}
+
+/// 一条交互式提示的自动应答规则：命中 `pattern` 时向 PTY 写入 `response`，
+/// 最多尝试 `max_attempts` 次，相邻两次发送之间至少间隔 `cooldown`。
+pub struct PromptRule {
+    pub label: &'static str,
+    pub pattern: PromptPattern,
+    pub response: Vec<u8>,
+    pub max_attempts: u8,
+    pub cooldown: Duration,
+}
+
+impl PromptRule {
+    pub fn new(
+        label: &'static str,
+        pattern: PromptPattern,
+        response: Vec<u8>,
+        max_attempts: u8,
+        cooldown: Duration,
+    ) -> Self {
+        Self {
+            label,
+            pattern,
+            response,
+            max_attempts,
+            cooldown,
+        }
+    }
+
+    /// 内置规则：workspace trust 提示，直接回车确认
+    pub fn workspace_trust_default() -> Self {
+        Self::new(
+            "workspace_trust",
+            PromptPattern::Regex(
+                Regex::new(r"(?i)quick safety check|(?:trust this folder.*enter to confirm)|(?:accessing workspace.*trust)")
+                    .expect("内置 workspace trust 正则无效"),
+            ),
+            line_ending().to_vec(),
+            3,
+            Duration::from_millis(400),
+        )
+    }
+}
+
+/// 内置的默认规则集合（目前仅包含 workspace trust 确认）
+pub fn default_prompt_rules() -> Vec<PromptRule> {
+    vec![PromptRule::workspace_trust_default()]
+}
+
+/// 按顺序尝试一组 [`PromptRule`]，对每条规则独立追踪尝试次数与冷却时间
+struct PromptResponder {
+    rules: Vec<PromptRule>,
+    attempts: Vec<u8>,
+    last_sent: Vec<Option<Instant>>,
+}
+
+impl PromptResponder {
+    fn new(rules: Vec<PromptRule>) -> Self {
+        let len = rules.len();
+        Self {
+            rules,
+            attempts: vec![0; len],
+            last_sent: vec![None; len],
+        }
+    }
+
+    /// 检查 buffer 是否命中任一规则，命中且满足冷却条件时写入其应答。
+    /// 一次只应答一条规则，避免同一帧内重复写入多条响应。
+    fn try_respond(&mut self, buffer: &str, writer: &mut dyn Write) -> Result<()> {
+        for (idx, rule) in self.rules.iter().enumerate() {
+            if self.attempts[idx] >= rule.max_attempts {
+                continue;
+            }
+            if !rule.pattern.matches(buffer) {
+                continue;
+            }
+
+            let ready = match self.last_sent[idx] {
+                Some(last) => last.elapsed() >= rule.cooldown,
+                None => true,
+            };
+            if !ready {
+                continue;
+            }
+
+            log::info!("交互式提示命中规则 `{}`，自动应答。", rule.label);
+            writer
+                .write_all(&rule.response)
+                .with_context(|| format!("规则 `{}` 写入应答失败", rule.label))?;
+            writer.flush().ok();
+            self.attempts[idx] += 1;
+            self.last_sent[idx] = Some(Instant::now());
+            return Ok(());
+        }
+
+        Ok(())
+    }
+}
+
 pub struct ClaudeCli {
     command: String,
+    prompt_rules: Vec<PromptRule>,
 }
 
 impl ClaudeCli {
     pub fn new(command: String) -> Self {
-        Self { command }
+        Self::with_prompt_rules(command, default_prompt_rules())
+    }
+
+    /// 使用自定义的交互式提示规则集合构造（例如补充模型选择、覆盖确认、API key 输入等提示）
+    pub fn with_prompt_rules(command: String, prompt_rules: Vec<PromptRule>) -> Self {
+        Self {
+            command,
+            prompt_rules,
+        }
     }
 
     pub fn run(&self, commands: &[ClaudeCommand]) -> Result<ClaudeCliResult> {
+        self.run_with_output(commands, None)
+    }
+
+    /// 与 [`run`] 等价，但会把捕获到的 PTY 输出块实时回调给 `on_chunk`
+    /// （调用方可以借此把输出转发为 Tauri 事件，实现流式展示进度）。
+    pub fn run_with_output(
+        &self,
+        commands: &[ClaudeCommand],
+        mut on_chunk: Option<&mut dyn FnMut(&str)>,
+    ) -> Result<ClaudeCliResult> {
         let mut raw_log = String::new();
         let mut outputs = Vec::new();
         for command in commands {
-            let output = self.run_command(command)?;
-            raw_log.push_str(&output);
+            let result = self.run_command(command, on_chunk.as_deref_mut())?;
+            raw_log.push_str(&result.output);
+            if result.timed_out {
+                raw_log.push_str(&format!(
+                    "\n[{}] 执行超时（{:?}），已终止进程及其子进程\n",
+                    command.args.join(" "),
+                    command.timeout
+                ));
+            }
+            raw_log.push_str(&format!("\n[{}] {}\n", command.args.join(" "), result.exit_summary));
             outputs.push(ClaudeCommandOutput {
                 command: command.args.join(" "),
-                output,
+                output: result.output,
+                exit_summary: result.exit_summary,
+                exit_code: result.exit_code,
+                timed_out: result.timed_out,
             });
         }
 
         Ok(ClaudeCliResult { outputs, raw_log })
     }
 
-    fn run_command(&self, command: &ClaudeCommand) -> Result<String> {
+    /// 运行单条命令，返回捕获到的 PTY 输出、归一化后的退出状态描述、数字退出码
+    /// （可用时）以及是否因超时被强制终止
+    fn run_command(
+        &self,
+        command: &ClaudeCommand,
+        on_chunk: Option<&mut dyn FnMut(&str)>,
+    ) -> Result<RunCommandResult> {
         let pty_system = native_pty_system();
         let pair = pty_system
             .openpty(PtySize {
@@ -85,23 +257,60 @@ impl ClaudeCli {
             }
         });
 
-        let output = read_until_exit_with_prompts(
+        let responder = PromptResponder::new(
+            self.prompt_rules
+                .iter()
+                .map(|r| PromptRule {
+                    label: r.label,
+                    pattern: match &r.pattern {
+                        PromptPattern::Substring(s) => PromptPattern::Substring(s.clone()),
+                        PromptPattern::Regex(re) => PromptPattern::Regex(re.clone()),
+                    },
+                    response: r.response.clone(),
+                    max_attempts: r.max_attempts,
+                    cooldown: r.cooldown,
+                })
+                .collect(),
+        );
+
+        let (output, timed_out) = read_until_exit_with_prompts(
             &rx,
             &mut writer,
             child.as_mut(),
             command.timeout,
+            responder,
+            on_chunk,
         )?;
 
         drop(writer);
-        let _ = child.kill();
-
-        let wait_start = Instant::now();
-        while wait_start.elapsed() < Duration::from_secs(2) {
-            match child.try_wait() {
-                Ok(Some(_)) => break,
-                Ok(None) => thread::sleep(Duration::from_millis(50)),
-                Err(_) => break,
+
+        let mut exit_status = child.try_wait().ok().flatten();
+        let mut killed_tree = false;
+        if exit_status.is_none() {
+            match child.process_id() {
+                Some(pid) => {
+                    terminate_process_tree(pid);
+                    killed_tree = true;
+                }
+                None => {
+                    let _ = child.kill();
+                }
             }
+
+            let wait_start = Instant::now();
+            while wait_start.elapsed() < Duration::from_secs(2) {
+                match child.try_wait() {
+                    Ok(Some(status)) => {
+                        exit_status = Some(status);
+                        break;
+                    }
+                    Ok(None) => thread::sleep(Duration::from_millis(50)),
+                    Err(_) => break,
+                }
+            }
+        }
+        if timed_out && exit_status.is_none() && !killed_tree {
+            log::warn!("命令超时后既未拿到退出状态也未能定位进程树，可能残留子进程");
         }
 
         let join_start = Instant::now();
@@ -115,42 +324,73 @@ impl ClaudeCli {
             log::warn!("PTY reader thread did not finish in time; detaching.");
         }
 
-        Ok(output)
+        Ok(RunCommandResult {
+            output,
+            exit_summary: format_exit_status(exit_status.as_ref()),
+            exit_code: exit_code_of(exit_status.as_ref()),
+            timed_out,
+        })
+    }
+}
+
+/// [`ClaudeCli::run_command`] 的内部返回值，携带足够的信息供上层区分"超时被杀"
+/// 与"正常退出但退出码非零"
+struct RunCommandResult {
+    output: String,
+    exit_summary: String,
+    exit_code: Option<i32>,
+    timed_out: bool,
+}
+
+/// 把 [`portable_pty::ExitStatus`] 渲染成统一格式，屏蔽平台差异
+fn format_exit_status(status: Option<&portable_pty::ExitStatus>) -> String {
+    match status {
+        Some(status) => match status.signal() {
+            Some(signal) => format!("terminated by signal: {}", signal),
+            None => format!("exit code: {}", status.exit_code()),
+        },
+        None => "exit code: unknown".to_string(),
+    }
+}
+
+/// 提取数字退出码；被信号终止或压根没拿到退出状态（例如超时强杀后等待
+/// 超时）时返回 `None`，调用方应退回子串启发式判断
+fn exit_code_of(status: Option<&portable_pty::ExitStatus>) -> Option<i32> {
+    let status = status?;
+    if status.signal().is_some() {
+        return None;
     }
+    Some(status.exit_code() as i32)
 }
 
+/// 读取 PTY 输出直到子进程退出或 `timeout` 到期，期间把每个输出块喂给
+/// `responder` 尝试自动应答交互式提示。返回捕获到的完整输出，以及是否因为
+/// 超时才结束（而非子进程自行退出）。
 fn read_until_exit_with_prompts(
     rx: &mpsc::Receiver<String>,
     writer: &mut dyn Write,
     child: &mut dyn portable_pty::Child,
     timeout: Duration,
-) -> Result<String> {
+    mut responder: PromptResponder,
+    mut on_chunk: Option<&mut dyn FnMut(&str)>,
+) -> Result<(String, bool)> {
     let start = Instant::now();
     let mut buffer = String::new();
-    let mut trust_attempts: u8 = 0;
-    let mut last_trust_sent: Option<Instant> = None;
+    let mut timed_out = false;
 
     loop {
         if start.elapsed() >= timeout {
+            timed_out = true;
             break;
         }
 
         match rx.recv_timeout(Duration::from_millis(200)) {
             Ok(chunk) => {
-                buffer.push_str(&chunk);
-
-                if is_workspace_trust_prompt(&buffer) && trust_attempts < 3 {
-                    let should_send = match last_trust_sent {
-                        Some(last) => last.elapsed() >= Duration::from_millis(400),
-                        None => true,
-                    };
-                    if should_send {
-                        log::info!("Workspace trust prompt detected; auto-confirming.");
-                        send_enter(writer).context("发送信任确认失败")?;
-                        trust_attempts += 1;
-                        last_trust_sent = Some(Instant::now());
-                    }
+                if let Some(cb) = on_chunk.as_deref_mut() {
+                    cb(&chunk);
                 }
+                buffer.push_str(&chunk);
+                responder.try_respond(&buffer, writer)?;
             }
             Err(RecvTimeoutError::Timeout) => {
                 if matches!(child.try_wait(), Ok(Some(_))) {
@@ -168,25 +408,51 @@ fn read_until_exit_with_prompts(
     let drain_start = Instant::now();
     while drain_start.elapsed() < Duration::from_millis(300) {
         match rx.recv_timeout(Duration::from_millis(50)) {
-            Ok(chunk) => buffer.push_str(&chunk),
+            Ok(chunk) => {
+                if let Some(cb) = on_chunk.as_deref_mut() {
+                    cb(&chunk);
+                }
+                buffer.push_str(&chunk);
+            }
             Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
         }
     }
 
-    Ok(buffer)
+    Ok((buffer, timed_out))
 }
 
-fn is_workspace_trust_prompt(output: &str) -> bool {
-    let text = output.to_lowercase();
-    text.contains("quick safety check")
-        || (text.contains("trust this folder") && text.contains("enter to confirm"))
-        || (text.contains("accessing workspace") && text.contains("trust"))
-}
+/// 终止 `root_pid` 及其所有子孙进程：先对整棵树发 `SIGTERM`，留出喘息时间
+/// 让进程自行退出，仍存活的再补一记 `SIGKILL`。用于命令超时后的强制回收，
+/// 避免只杀掉直接子进程、让它 fork 出的孙进程变成孤儿继续跑。
+fn terminate_process_tree(root_pid: u32) {
+    let mut system = System::new();
+    system.refresh_processes();
 
-fn send_enter(writer: &mut dyn Write) -> Result<()> {
-    writer.write_all(line_ending())?;
-    writer.flush().ok();
-    Ok(())
+    let root_pid = Pid::from_u32(root_pid);
+    let mut tree = vec![root_pid];
+    let mut frontier = vec![root_pid];
+    while let Some(parent) = frontier.pop() {
+        for (pid, process) in system.processes() {
+            if process.parent() == Some(parent) && !tree.contains(pid) {
+                tree.push(*pid);
+                frontier.push(*pid);
+            }
+        }
+    }
+
+    for pid in &tree {
+        if let Some(process) = system.process(*pid) {
+            process.kill_with(Signal::Term);
+        }
+    }
+
+    thread::sleep(Duration::from_millis(300));
+    system.refresh_processes();
+    for pid in &tree {
+        if let Some(process) = system.process(*pid) {
+            process.kill_with(Signal::Kill);
+        }
+    }
 }
 
 fn line_ending() -> &'static [u8] {
@@ -1,13 +1,338 @@
-use crate::models::{Plugin, Repository, Skill};
+use crate::models::{
+    DatabaseStats, HostConfig, IssueSeverity, Plugin, Repository, ScoreBucket, ScoreHistoryEntry,
+    SecurityFinding, SecurityLevelCount, Skill, SkillInstallation, UpdateStatus,
+};
+use crate::models::advisory::AdvisoryDb;
+use crate::models::security::SecurityReport;
 use anyhow::{Result, Context};
-use rusqlite::{Connection, params, OptionalExtension};
-use std::path::PathBuf;
-use std::sync::Mutex;
+use chrono::{DateTime, Utc};
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{Connection, Row, params, OptionalExtension, TransactionBehavior};
+use std::path::{Path, PathBuf};
+
+/// 每次从池中取连接时等待空闲连接的超时时间，超过后返回错误而不是无限阻塞
+const POOL_CONNECTION_TIMEOUT_SECS: u64 = 5;
+
+/// 单个连接上的 SQLite busy 超时（毫秒），配合 WAL 模式让并发写入等待而不是立即报错
+const SQLITE_BUSY_TIMEOUT_MS: u32 = 5_000;
 
 pub struct Database {
-    conn: Mutex<Connection>,
+    pool: Pool<SqliteConnectionManager>,
+}
+
+/// 一条按 `PRAGMA user_version` 排序的迁移。`version` 必须严格递增且永不复用；
+/// `up` 是该版本要执行的 SQL（可以是多条语句，用 `;` 分隔，通过 `execute_batch`
+/// 整体执行），不应假设之前的迁移是否已经执行过——迁移框架保证每个 version
+/// 只会被应用一次。
+struct Migration {
+    version: u32,
+    name: &'static str,
+    up: &'static str,
 }
 
+/// 所有已知迁移，严格按版本号升序排列。新增迁移时在末尾追加一条，version 取
+/// 当前最大值 + 1；已发布的条目不可修改或重排，否则会导致已经应用过它的数据库
+/// 跳过这条迁移。
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "add_repository_owner",
+        up: r#"
+            ALTER TABLE skills ADD COLUMN repository_owner TEXT;
+
+            UPDATE skills
+            SET repository_owner = CASE
+                WHEN repository_url = 'local' THEN 'local'
+                WHEN repository_url LIKE '%github.com/%' THEN
+                    substr(
+                        repository_url,
+                        instr(repository_url, 'github.com/') + 11,
+                        CASE
+                            WHEN instr(substr(repository_url, instr(repository_url, 'github.com/') + 11), '/') > 0
+                            THEN instr(substr(repository_url, instr(repository_url, 'github.com/') + 11), '/') - 1
+                            ELSE length(substr(repository_url, instr(repository_url, 'github.com/') + 11))
+                        END
+                    )
+                ELSE 'unknown'
+            END
+            WHERE repository_owner IS NULL;
+        "#,
+    },
+    Migration {
+        version: 2,
+        name: "add_cache_fields",
+        up: r#"
+            ALTER TABLE repositories ADD COLUMN cache_path TEXT;
+            ALTER TABLE repositories ADD COLUMN cached_at TEXT;
+            ALTER TABLE repositories ADD COLUMN cached_commit_sha TEXT;
+        "#,
+    },
+    Migration {
+        version: 3,
+        name: "add_security_enhancement_fields",
+        up: r#"
+            ALTER TABLE skills ADD COLUMN security_level TEXT;
+            ALTER TABLE skills ADD COLUMN scanned_at TEXT;
+        "#,
+    },
+    Migration {
+        version: 4,
+        name: "add_local_paths",
+        up: r#"
+            ALTER TABLE skills ADD COLUMN local_paths TEXT;
+
+            UPDATE skills
+            SET local_paths = json_array(local_path)
+            WHERE local_path IS NOT NULL AND local_paths IS NULL;
+        "#,
+    },
+    Migration {
+        version: 5,
+        name: "add_installed_commit_sha",
+        up: "ALTER TABLE skills ADD COLUMN installed_commit_sha TEXT;",
+    },
+    Migration {
+        version: 6,
+        name: "add_plugin_claude_fields",
+        up: r#"
+            ALTER TABLE plugins ADD COLUMN claude_id TEXT;
+            ALTER TABLE plugins ADD COLUMN installed_version TEXT;
+            ALTER TABLE plugins ADD COLUMN discovery_source TEXT;
+            ALTER TABLE plugins ADD COLUMN claude_scope TEXT;
+            ALTER TABLE plugins ADD COLUMN claude_enabled INTEGER;
+            ALTER TABLE plugins ADD COLUMN claude_install_path TEXT;
+            ALTER TABLE plugins ADD COLUMN claude_last_updated TEXT;
+
+            UPDATE plugins
+            SET claude_id = name || '@' || marketplace_name
+            WHERE claude_id IS NULL;
+
+            UPDATE plugins
+            SET discovery_source = 'repository_scan'
+            WHERE discovery_source IS NULL;
+        "#,
+    },
+    Migration {
+        version: 7,
+        name: "add_plugin_install_commands",
+        up: r#"
+            ALTER TABLE plugins ADD COLUMN marketplace_add_command TEXT;
+            ALTER TABLE plugins ADD COLUMN plugin_install_command TEXT;
+        "#,
+    },
+    Migration {
+        version: 8,
+        name: "add_skill_embeddings_cache",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS skill_embeddings_cache (
+                checksum TEXT PRIMARY KEY,
+                chunk_embeddings BLOB NOT NULL,
+                created_at TEXT NOT NULL
+            );
+        "#,
+    },
+    Migration {
+        version: 9,
+        name: "add_skill_capability_manifest",
+        up: "ALTER TABLE skills ADD COLUMN capability_manifest TEXT;",
+    },
+    Migration {
+        version: 10,
+        name: "add_skill_content_checksum",
+        up: "ALTER TABLE skills ADD COLUMN content_checksum TEXT;",
+    },
+    Migration {
+        version: 11,
+        name: "add_skill_report_json",
+        up: "ALTER TABLE skills ADD COLUMN report_json TEXT;",
+    },
+    Migration {
+        version: 12,
+        name: "add_update_status",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS update_status (
+                item_type TEXT NOT NULL,
+                item_id TEXT NOT NULL,
+                item_name TEXT NOT NULL,
+                available_version TEXT NOT NULL,
+                checked_at TEXT NOT NULL,
+                last_notified_version TEXT,
+                last_notified_at TEXT,
+                PRIMARY KEY (item_type, item_id)
+            );
+        "#,
+    },
+    Migration {
+        version: 13,
+        name: "add_plugin_backend",
+        up: "ALTER TABLE plugins ADD COLUMN backend TEXT;",
+    },
+    Migration {
+        version: 14,
+        name: "add_plugin_scan_incremental_fields",
+        up: r#"
+            ALTER TABLE plugins ADD COLUMN scanned_commit_sha TEXT;
+            ALTER TABLE plugins ADD COLUMN report_json TEXT;
+        "#,
+    },
+    Migration {
+        version: 15,
+        name: "add_fts5_search",
+        up: r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS skills_fts USING fts5(
+                name, description, author,
+                content='skills',
+                content_rowid='rowid'
+            );
+
+            CREATE TRIGGER IF NOT EXISTS skills_fts_ai AFTER INSERT ON skills BEGIN
+                INSERT INTO skills_fts(rowid, name, description, author)
+                VALUES (new.rowid, new.name, new.description, new.author);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS skills_fts_ad AFTER DELETE ON skills BEGIN
+                INSERT INTO skills_fts(skills_fts, rowid, name, description, author)
+                VALUES ('delete', old.rowid, old.name, old.description, old.author);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS skills_fts_au AFTER UPDATE ON skills BEGIN
+                INSERT INTO skills_fts(skills_fts, rowid, name, description, author)
+                VALUES ('delete', old.rowid, old.name, old.description, old.author);
+                INSERT INTO skills_fts(rowid, name, description, author)
+                VALUES (new.rowid, new.name, new.description, new.author);
+            END;
+
+            INSERT INTO skills_fts(rowid, name, description, author)
+            SELECT rowid, name, description, author FROM skills;
+
+            CREATE VIRTUAL TABLE IF NOT EXISTS plugins_fts USING fts5(
+                name, description, author,
+                content='plugins',
+                content_rowid='rowid'
+            );
+
+            CREATE TRIGGER IF NOT EXISTS plugins_fts_ai AFTER INSERT ON plugins BEGIN
+                INSERT INTO plugins_fts(rowid, name, description, author)
+                VALUES (new.rowid, new.name, new.description, new.author);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS plugins_fts_ad AFTER DELETE ON plugins BEGIN
+                INSERT INTO plugins_fts(plugins_fts, rowid, name, description, author)
+                VALUES ('delete', old.rowid, old.name, old.description, old.author);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS plugins_fts_au AFTER UPDATE ON plugins BEGIN
+                INSERT INTO plugins_fts(plugins_fts, rowid, name, description, author)
+                VALUES ('delete', old.rowid, old.name, old.description, old.author);
+                INSERT INTO plugins_fts(rowid, name, description, author)
+                VALUES (new.rowid, new.name, new.description, new.author);
+            END;
+
+            INSERT INTO plugins_fts(rowid, name, description, author)
+            SELECT rowid, name, description, author FROM plugins;
+        "#,
+    },
+    Migration {
+        version: 16,
+        name: "add_skill_needs_redownload",
+        up: "ALTER TABLE skills ADD COLUMN needs_redownload INTEGER NOT NULL DEFAULT 0;",
+    },
+    Migration {
+        version: 17,
+        name: "add_security_findings",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS security_findings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                subject_type TEXT NOT NULL,
+                subject_id TEXT NOT NULL,
+                rule_id TEXT NOT NULL,
+                severity TEXT NOT NULL,
+                title TEXT NOT NULL,
+                detail TEXT,
+                scanned_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_security_findings_subject
+                ON security_findings(subject_type, subject_id);
+
+            CREATE INDEX IF NOT EXISTS idx_security_findings_severity
+                ON security_findings(severity);
+
+            CREATE TABLE IF NOT EXISTS security_score_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                subject_type TEXT NOT NULL,
+                subject_id TEXT NOT NULL,
+                score INTEGER NOT NULL,
+                scanned_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_security_score_history_subject
+                ON security_score_history(subject_type, subject_id, scanned_at);
+        "#,
+    },
+    Migration {
+        version: 18,
+        name: "add_repository_git_ref",
+        up: "ALTER TABLE repositories ADD COLUMN git_ref TEXT;",
+    },
+    Migration {
+        version: 19,
+        name: "add_repository_host_config",
+        up: r#"
+            ALTER TABLE repositories ADD COLUMN api_base_url TEXT;
+            ALTER TABLE repositories ADD COLUMN raw_base_url TEXT;
+        "#,
+    },
+    Migration {
+        version: 20,
+        name: "add_plugin_dependencies",
+        up: "ALTER TABLE plugins ADD COLUMN dependencies TEXT;",
+    },
+    Migration {
+        version: 21,
+        name: "add_plugin_guard_scan",
+        up: r#"
+            ALTER TABLE plugins ADD COLUMN manifest_digest TEXT;
+            ALTER TABLE plugins ADD COLUMN guard_risk_score INTEGER;
+            ALTER TABLE plugins ADD COLUMN guard_findings TEXT;
+        "#,
+    },
+    Migration {
+        version: 22,
+        name: "add_plugin_signature_verification",
+        up: r#"
+            ALTER TABLE plugins ADD COLUMN signature_verified INTEGER;
+            ALTER TABLE plugins ADD COLUMN signature_signer TEXT;
+        "#,
+    },
+    Migration {
+        version: 23,
+        name: "add_plugin_source_pinning",
+        up: r#"
+            ALTER TABLE plugins ADD COLUMN branch TEXT;
+            ALTER TABLE plugins ADD COLUMN revision TEXT;
+        "#,
+    },
+    Migration {
+        version: 24,
+        name: "add_advisory_db_cache",
+        up: r#"
+            CREATE TABLE IF NOT EXISTS advisory_db_cache (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                version INTEGER NOT NULL,
+                data TEXT NOT NULL,
+                cached_at TEXT NOT NULL
+            );
+        "#,
+    },
+    Migration {
+        version: 25,
+        name: "add_plugin_capabilities",
+        up: "ALTER TABLE plugins ADD COLUMN capabilities TEXT;",
+    },
+];
+
 impl Database {
     /// 创建或打开数据库
     pub fn new(db_path: PathBuf) -> Result<Self> {
@@ -16,33 +341,69 @@ impl Database {
             std::fs::create_dir_all(parent)?;
         }
 
-        let conn = Connection::open(db_path)
-            .context("Failed to open database")?;
-
-        let db = Self {
-            conn: Mutex::new(conn),
-        };
+        let pool = Self::build_pool(&db_path)?;
+        let db = Self { pool };
 
         db.initialize_schema()?;
         Ok(db)
     }
 
-    /// 重置数据库中的所有业务数据（保留表结构与迁移）
-    pub fn reset_all_data(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+    /// 构建连接池。文件数据库开启 WAL，允许多个读连接与一个写连接并发；
+    /// `:memory:` 是测试用的快速通道——SQLite 的内存数据库不跨连接共享，
+    /// 所以把池大小钉死为 1，让每次 checkout 都拿到同一个连接，其余代码
+    /// 完全不需要感知这个区别。
+    fn build_pool(db_path: &Path) -> Result<Pool<SqliteConnectionManager>> {
+        let is_memory = db_path.as_os_str() == ":memory:";
+
+        let manager = if is_memory {
+            SqliteConnectionManager::memory()
+        } else {
+            SqliteConnectionManager::file(db_path)
+        }
+        .with_init(|conn| {
+            conn.execute_batch(&format!(
+                "PRAGMA journal_mode = WAL;
+                 PRAGMA busy_timeout = {};",
+                SQLITE_BUSY_TIMEOUT_MS
+            ))
+        });
+
+        let mut builder = Pool::builder()
+            .connection_timeout(std::time::Duration::from_secs(POOL_CONNECTION_TIMEOUT_SECS));
+        if is_memory {
+            builder = builder.max_size(1);
+        }
+
+        builder
+            .build(manager)
+            .context("Failed to build sqlite connection pool")
+    }
 
-        conn.execute_batch(
-            r#"
-            PRAGMA foreign_keys=OFF;
-            BEGIN IMMEDIATE;
-            DELETE FROM installations;
-            DELETE FROM plugins;
-            DELETE FROM skills;
-            DELETE FROM repositories;
-            COMMIT;
-            PRAGMA foreign_keys=ON;
-            "#,
+    /// 重置数据库中的所有业务数据（保留表结构与迁移）。
+    /// `VACUUM` 本身就需要整个数据库文件的独占访问，所以这里不做额外的加锁：
+    /// 拿到一个连接、清空业务表并执行 `VACUUM`，SQLite 会在需要时阻塞其他连接。
+    pub fn reset_all_data(&self) -> Result<()> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+
+        // 表名从 sqlite_master 里动态查出来，而不是在这里手写一份列表——
+        // 手写的列表在每次新增持久化表（embedding 缓存、安全扫描历史、
+        // advisory 缓存……）时都需要有人记得同步过来，漏掉就会导致
+        // “重置所有数据”之后悄悄留下一堆旧数据。`sqlite_` 开头的表是
+        // SQLite 自己的内部表（如 `sqlite_sequence`），不应该被用户数据
+        // 重置逻辑动到。
+        let mut stmt = conn.prepare(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
         )?;
+        let table_names: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        drop(stmt);
+
+        conn.execute_batch("PRAGMA foreign_keys=OFF; BEGIN IMMEDIATE;")?;
+        for table in &table_names {
+            conn.execute(&format!("DELETE FROM \"{}\"", table), [])?;
+        }
+        conn.execute_batch("COMMIT; PRAGMA foreign_keys=ON;")?;
 
         // 若启用了 WAL，尽量将 WAL 截断，避免残留旧页面
         let _ = conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);");
@@ -55,7 +416,7 @@ impl Database {
 
     /// 初始化数据库架构
     fn initialize_schema(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
 
         conn.execute(
             "CREATE TABLE IF NOT EXISTS repositories (
@@ -119,7 +480,14 @@ impl Database {
                 scanned_at TEXT,
                 staging_path TEXT,
                 install_log TEXT,
-                install_status TEXT
+                install_status TEXT,
+                backend TEXT,
+                scanned_commit_sha TEXT,
+                report_json TEXT,
+                dependencies TEXT,
+                manifest_digest TEXT,
+                guard_risk_score INTEGER,
+                guard_findings TEXT
             )",
             [],
         )?;
@@ -136,35 +504,156 @@ impl Database {
             [],
         )?;
 
-        // 释放锁以便调用迁移方法
-        drop(conn);
+        // 执行版本化迁移（按 PRAGMA user_version 记录的进度，每条迁移只运行一次）
+        Self::run_migrations(&conn)?;
+
+        Ok(())
+    }
 
-        // 执行数据库迁移
-        self.migrate_add_repository_owner()?;
-        self.migrate_add_cache_fields()?;
-        self.migrate_add_security_enhancement_fields()?;
-        self.migrate_add_local_paths()?;
-        self.migrate_add_installed_commit_sha()?;
-        self.migrate_add_plugin_claude_fields()?;
-        self.migrate_add_plugin_install_commands()?;
+    /// 执行所有尚未应用的迁移，并把进度记录在 SQLite 内置的 `PRAGMA user_version` 里，
+    /// 取代逐条 `ALTER TABLE ... ADD COLUMN` 时靠吞掉"列已存在"错误来实现幂等的做法——
+    /// 这样每条迁移（包括一次性的数据回填 UPDATE）只会在升级到对应版本时运行一次，
+    /// 而不是在每次启动时都重新执行一遍。
+    ///
+    /// 若数据库的 `user_version` 高于当前程序已知的最新迁移版本（例如用旧版本程序
+    /// 打开了被新版本升级过的数据库），直接拒绝启动，避免后续操作进一步破坏 schema。
+    fn run_migrations(conn: &Connection) -> Result<()> {
+        let current_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        let latest_version = MIGRATIONS.iter().map(|m| m.version).max().unwrap_or(0);
+        if current_version > latest_version as i64 {
+            anyhow::bail!(
+                "数据库 schema 版本 ({}) 高于当前程序已知的最新版本 ({})，请升级应用后再打开该数据库",
+                current_version,
+                latest_version
+            );
+        }
+
+        for migration in MIGRATIONS.iter().filter(|m| m.version as i64 > current_version) {
+            conn.execute_batch("BEGIN IMMEDIATE")
+                .with_context(|| format!("开启迁移事务失败：{} (version {})", migration.name, migration.version))?;
+
+            let result = conn
+                .execute_batch(migration.up)
+                .and_then(|_| conn.execute_batch(&format!("PRAGMA user_version = {}", migration.version)));
+
+            match result {
+                Ok(()) => {
+                    conn.execute_batch("COMMIT")?;
+                }
+                Err(e) => {
+                    let _ = conn.execute_batch("ROLLBACK");
+                    return Err(e).with_context(|| {
+                        format!("数据库迁移执行失败：{} (version {})", migration.name, migration.version)
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 读取某个 skill 内容（按 checksum）缓存的 chunk embedding 向量
+    pub fn get_skill_embeddings_cache(&self, checksum: &str) -> Result<Option<Vec<Vec<f32>>>> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+
+        let blob: Option<Vec<u8>> = conn
+            .query_row(
+                "SELECT chunk_embeddings FROM skill_embeddings_cache WHERE checksum = ?1",
+                params![checksum],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match blob {
+            Some(bytes) => {
+                let embeddings = serde_json::from_slice(&bytes)
+                    .context("Failed to deserialize cached embeddings")?;
+                Ok(Some(embeddings))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 缓存某个 skill 内容（按 checksum）的 chunk embedding 向量，供下次
+    /// 重扫时跳过 embedding 计算
+    pub fn save_skill_embeddings_cache(&self, checksum: &str, embeddings: &[Vec<f32>]) -> Result<()> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+
+        let blob = serde_json::to_vec(embeddings)
+            .context("Failed to serialize embeddings for caching")?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO skill_embeddings_cache (checksum, chunk_embeddings, created_at)
+             VALUES (?1, ?2, ?3)",
+            params![checksum, blob, chrono::Utc::now().to_rfc3339()],
+        )?;
+
+        Ok(())
+    }
+
+    /// 读取缓存的依赖漏洞公告数据库（见 [`AdvisoryDb`]），由
+    /// [`crate::commands::advisory_db::refresh_advisory_db`] 写入；
+    /// 未刷新过时返回 `None`，调用方应退回到 `AdvisoryDb::embedded()`。
+    pub fn get_advisory_db_cache(&self) -> Result<Option<AdvisoryDb>> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+
+        let data: Option<String> = conn
+            .query_row(
+                "SELECT data FROM advisory_db_cache WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match data {
+            Some(json) => {
+                let db = serde_json::from_str(&json)
+                    .context("Failed to deserialize cached advisory db")?;
+                Ok(Some(db))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 用一次成功刷新的结果整体替换缓存的公告数据库（单行，`id` 恒为 1）
+    pub fn save_advisory_db_cache(&self, db: &AdvisoryDb) -> Result<()> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+
+        let json = serde_json::to_string(db)
+            .context("Failed to serialize advisory db for caching")?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO advisory_db_cache (id, version, data, cached_at)
+             VALUES (1, ?1, ?2, ?3)",
+            params![db.version as i64, json, Utc::now().to_rfc3339()],
+        )?;
 
         Ok(())
     }
 
     /// 保存 plugin
     pub fn save_plugin(&self, plugin: &Plugin) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
 
         let security_issues_json = plugin.security_issues.as_ref()
             .map(|issues| serde_json::to_string(issues).unwrap());
+        let dependencies_json = plugin.dependencies.as_ref()
+            .map(|deps| serde_json::to_string(deps).unwrap());
+        let guard_findings_json = plugin.guard_findings.as_ref()
+            .map(|findings| serde_json::to_string(findings).unwrap());
+        let capabilities_json = plugin.capabilities.as_ref()
+            .map(|capabilities| serde_json::to_string(capabilities).unwrap());
 
         conn.execute(
             "INSERT OR REPLACE INTO plugins
             (id, claude_id, name, description, version, installed_version, author, repository_url, repository_owner,
              marketplace_name, source, discovery_source, marketplace_add_command, plugin_install_command, installed,
              installed_at, claude_scope, claude_enabled, claude_install_path, claude_last_updated, security_score,
-             security_issues, security_level, scanned_at, staging_path, install_log, install_status)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27)",
+             security_issues, security_level, scanned_at, staging_path, install_log, install_status, backend,
+             scanned_commit_sha, report_json, dependencies, manifest_digest, guard_risk_score, guard_findings,
+             signature_verified, signature_signer, branch, revision, capabilities)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32, ?33, ?34, ?35, ?36, ?37, ?38, ?39)",
             params![
                 plugin.id,
                 plugin.claude_id,
@@ -193,43 +682,28 @@ impl Database {
                 plugin.staging_path,
                 plugin.install_log,
                 plugin.install_status,
+                plugin.backend,
+                plugin.scanned_commit_sha,
+                plugin.report_json,
+                dependencies_json,
+                plugin.manifest_digest,
+                plugin.guard_risk_score,
+                guard_findings_json,
+                plugin.signature_verified.map(|v| if v { 1 } else { 0 }),
+                plugin.signature_signer,
+                plugin.branch,
+                plugin.revision,
+                capabilities_json,
             ],
         )?;
 
-        Ok(())
-    }
-
-    /// 数据库迁移：添加 repository_owner 列
-    fn migrate_add_repository_owner(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-
-        // 尝试添加列（如果列已存在会失败，这是正常的）
-        let _ = conn.execute(
-            "ALTER TABLE skills ADD COLUMN repository_owner TEXT",
-            [],
-        );
-
-        // 为现有记录填充 repository_owner
-        conn.execute(
-            r#"
-            UPDATE skills
-            SET repository_owner = CASE
-                WHEN repository_url = 'local' THEN 'local'
-                WHEN repository_url LIKE '%github.com/%' THEN
-                    substr(
-                        repository_url,
-                        instr(repository_url, 'github.com/') + 11,
-                        CASE
-                            WHEN instr(substr(repository_url, instr(repository_url, 'github.com/') + 11), '/') > 0
-                            THEN instr(substr(repository_url, instr(repository_url, 'github.com/') + 11), '/') - 1
-                            ELSE length(substr(repository_url, instr(repository_url, 'github.com/') + 11))
-                        END
-                    )
-                ELSE 'unknown'
-            END
-            WHERE repository_owner IS NULL
-            "#,
-            [],
+        Self::record_security_scan(
+            &conn,
+            "plugin",
+            &plugin.id,
+            plugin.security_score,
+            plugin.report_json.as_deref(),
+            plugin.scanned_at.as_ref(),
         )?;
 
         Ok(())
@@ -237,12 +711,12 @@ impl Database {
 
     /// 添加仓库
     pub fn add_repository(&self, repo: &Repository) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
 
         conn.execute(
             "INSERT OR REPLACE INTO repositories
-            (id, url, name, description, enabled, scan_subdirs, added_at, last_scanned, cache_path, cached_at, cached_commit_sha)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            (id, url, name, description, enabled, scan_subdirs, added_at, last_scanned, cache_path, cached_at, cached_commit_sha, git_ref, api_base_url, raw_base_url)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
             params![
                 repo.id,
                 repo.url,
@@ -255,6 +729,9 @@ impl Database {
                 repo.cache_path,
                 repo.cached_at.as_ref().map(|d| d.to_rfc3339()),
                 repo.cached_commit_sha,
+                repo.git_ref,
+                repo.host.as_ref().map(|h| h.api_base_url.as_str()),
+                repo.host.as_ref().map(|h| h.raw_base_url.as_str()),
             ],
         )?;
 
@@ -263,9 +740,9 @@ impl Database {
 
     /// 获取所有仓库
     pub fn get_repositories(&self) -> Result<Vec<Repository>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
         let mut stmt = conn.prepare(
-            "SELECT id, url, name, description, enabled, scan_subdirs, added_at, last_scanned, cache_path, cached_at, cached_commit_sha
+            "SELECT id, url, name, description, enabled, scan_subdirs, added_at, last_scanned, cache_path, cached_at, cached_commit_sha, git_ref, api_base_url, raw_base_url
              FROM repositories
              ORDER BY added_at DESC"
         )?;
@@ -285,6 +762,11 @@ impl Database {
                 cached_at: row.get::<_, Option<String>>(9)?
                     .and_then(|s| s.parse().ok()),
                 cached_commit_sha: row.get(10)?,
+                git_ref: row.get(11)?,
+                host: match (row.get::<_, Option<String>>(12)?, row.get::<_, Option<String>>(13)?) {
+                    (Some(api_base_url), Some(raw_base_url)) => Some(HostConfig { api_base_url, raw_base_url }),
+                    _ => None,
+                },
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -294,7 +776,7 @@ impl Database {
 
     /// 保存 skill
     pub fn save_skill(&self, skill: &Skill) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
 
         let security_issues_json = skill.security_issues.as_ref()
             .map(|issues| serde_json::to_string(issues).unwrap());
@@ -302,11 +784,14 @@ impl Database {
         let local_paths_json = skill.local_paths.as_ref()
             .map(|paths| serde_json::to_string(paths).unwrap());
 
+        let capability_manifest_json = skill.capability_manifest.as_ref()
+            .map(|m| serde_json::to_string(m).unwrap());
+
         conn.execute(
             "INSERT OR REPLACE INTO skills
             (id, name, description, repository_url, repository_owner, file_path, version, author,
-             installed, installed_at, local_path, local_paths, checksum, security_score, security_issues, security_level, scanned_at, installed_commit_sha)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+             installed, installed_at, local_path, local_paths, checksum, security_score, security_issues, security_level, scanned_at, installed_commit_sha, capability_manifest, content_checksum, report_json, needs_redownload)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)",
             params![
                 skill.id,
                 skill.name,
@@ -326,122 +811,514 @@ impl Database {
                 skill.security_level,
                 skill.scanned_at.as_ref().map(|d| d.to_rfc3339()),
                 skill.installed_commit_sha,
+                capability_manifest_json,
+                skill.content_checksum,
+                skill.report_json,
+                skill.needs_redownload as i32,
             ],
         )?;
 
+        Self::record_security_scan(
+            &conn,
+            "skill",
+            &skill.id,
+            skill.security_score,
+            skill.report_json.as_deref(),
+            skill.scanned_at.as_ref(),
+        )?;
+
+        Ok(())
+    }
+
+    /// 批量保存 skills（单个 `BEGIN IMMEDIATE` 事务内完成，复用同一条 prepared statement，
+    /// 用于并行扫描结束后一次性落库，避免每个 skill 各自加锁、各自提交一次事务）
+    pub fn save_skills_batch(&self, skills: &[Skill]) -> Result<()> {
+        let mut conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        Self::insert_skills(&tx, skills)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 批量保存 plugins（单个 `BEGIN IMMEDIATE` 事务内完成，复用同一条 prepared statement）
+    pub fn save_plugins_batch(&self, plugins: &[Plugin]) -> Result<()> {
+        let mut conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        Self::insert_plugins(&tx, plugins)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 重新扫描某个仓库：在一个事务里删除该仓库之前未安装的 skills/plugins 并插入
+    /// 本次扫描的最新结果，原子地完成「回收旧数据 + 落库新数据」，避免进程在
+    /// 扫描中途被打断时留下新旧数据混杂的半完成状态
+    pub fn replace_repository_items(
+        &self,
+        repository_url: &str,
+        skills: &[Skill],
+        plugins: &[Plugin],
+    ) -> Result<()> {
+        let mut conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        tx.execute(
+            "DELETE FROM skills WHERE repository_url = ?1 AND installed = 0",
+            params![repository_url],
+        )?;
+        tx.execute(
+            "DELETE FROM plugins WHERE repository_url = ?1 AND installed = 0",
+            params![repository_url],
+        )?;
+
+        Self::insert_skills(&tx, skills)?;
+        Self::insert_plugins(&tx, plugins)?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// `save_skills_batch`/`replace_repository_items` 共用的插入逻辑：
+    /// 在传入的事务里准备一次 `INSERT OR REPLACE` 语句，为每个 skill 重新绑定参数执行
+    fn insert_skills(tx: &rusqlite::Transaction, skills: &[Skill]) -> Result<()> {
+        let mut stmt = tx.prepare(
+            "INSERT OR REPLACE INTO skills
+            (id, name, description, repository_url, repository_owner, file_path, version, author,
+             installed, installed_at, local_path, local_paths, checksum, security_score, security_issues, security_level, scanned_at, installed_commit_sha, capability_manifest, content_checksum, report_json, needs_redownload)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)"
+        )?;
+
+        for skill in skills {
+            let security_issues_json = skill.security_issues.as_ref()
+                .map(|issues| serde_json::to_string(issues).unwrap());
+            let local_paths_json = skill.local_paths.as_ref()
+                .map(|paths| serde_json::to_string(paths).unwrap());
+            let capability_manifest_json = skill.capability_manifest.as_ref()
+                .map(|m| serde_json::to_string(m).unwrap());
+
+            stmt.execute(params![
+                skill.id,
+                skill.name,
+                skill.description,
+                skill.repository_url,
+                skill.repository_owner,
+                skill.file_path,
+                skill.version,
+                skill.author,
+                skill.installed as i32,
+                skill.installed_at.as_ref().map(|d| d.to_rfc3339()),
+                skill.local_path,
+                local_paths_json,
+                skill.checksum,
+                skill.security_score,
+                security_issues_json,
+                skill.security_level,
+                skill.scanned_at.as_ref().map(|d| d.to_rfc3339()),
+                skill.installed_commit_sha,
+                capability_manifest_json,
+                skill.content_checksum,
+                skill.report_json,
+                skill.needs_redownload as i32,
+            ])?;
+
+            Self::record_security_scan(
+                tx,
+                "skill",
+                &skill.id,
+                skill.security_score,
+                skill.report_json.as_deref(),
+                skill.scanned_at.as_ref(),
+            )?;
+        }
+
         Ok(())
     }
 
+    /// `save_plugins_batch`/`replace_repository_items` 共用的插入逻辑：
+    /// 在传入的事务里准备一次 `INSERT OR REPLACE` 语句，为每个 plugin 重新绑定参数执行
+    fn insert_plugins(tx: &rusqlite::Transaction, plugins: &[Plugin]) -> Result<()> {
+        let mut stmt = tx.prepare(
+            "INSERT OR REPLACE INTO plugins
+            (id, claude_id, name, description, version, installed_version, author, repository_url, repository_owner,
+             marketplace_name, source, discovery_source, marketplace_add_command, plugin_install_command, installed,
+             installed_at, claude_scope, claude_enabled, claude_install_path, claude_last_updated, security_score,
+             security_issues, security_level, scanned_at, staging_path, install_log, install_status, backend,
+             scanned_commit_sha, report_json, dependencies, manifest_digest, guard_risk_score, guard_findings,
+             signature_verified, signature_signer, branch, revision, capabilities)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32, ?33, ?34, ?35, ?36, ?37, ?38, ?39)"
+        )?;
+
+        for plugin in plugins {
+            let security_issues_json = plugin.security_issues.as_ref()
+                .map(|issues| serde_json::to_string(issues).unwrap());
+            let dependencies_json = plugin.dependencies.as_ref()
+                .map(|deps| serde_json::to_string(deps).unwrap());
+            let guard_findings_json = plugin.guard_findings.as_ref()
+                .map(|findings| serde_json::to_string(findings).unwrap());
+            let capabilities_json = plugin.capabilities.as_ref()
+                .map(|capabilities| serde_json::to_string(capabilities).unwrap());
+
+            stmt.execute(params![
+                plugin.id,
+                plugin.claude_id,
+                plugin.name,
+                plugin.description,
+                plugin.version,
+                plugin.installed_version,
+                plugin.author,
+                plugin.repository_url,
+                plugin.repository_owner,
+                plugin.marketplace_name,
+                plugin.source,
+                plugin.discovery_source,
+                plugin.marketplace_add_command,
+                plugin.plugin_install_command,
+                plugin.installed as i32,
+                plugin.installed_at.as_ref().map(|d| d.to_rfc3339()),
+                plugin.claude_scope,
+                plugin.claude_enabled.map(|v| if v { 1 } else { 0 }),
+                plugin.claude_install_path,
+                plugin.claude_last_updated.as_ref().map(|d| d.to_rfc3339()),
+                plugin.security_score,
+                security_issues_json,
+                plugin.security_level,
+                plugin.scanned_at.as_ref().map(|d| d.to_rfc3339()),
+                plugin.staging_path,
+                plugin.install_log,
+                plugin.install_status,
+                plugin.backend,
+                plugin.scanned_commit_sha,
+                plugin.report_json,
+                dependencies_json,
+                plugin.manifest_digest,
+                plugin.guard_risk_score,
+                guard_findings_json,
+                plugin.signature_verified.map(|v| if v { 1 } else { 0 }),
+                plugin.signature_signer,
+                plugin.branch,
+                plugin.revision,
+                capabilities_json,
+            ])?;
+
+            Self::record_security_scan(
+                tx,
+                "plugin",
+                &plugin.id,
+                plugin.security_score,
+                plugin.report_json.as_deref(),
+                plugin.scanned_at.as_ref(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// 记录一次扫描结果到 `security_findings`/`security_score_history`：
+    /// 从 `report_json`（完整 `SecurityReport`）里展开每条 issue 追加一行 finding，
+    /// 并在有 `score` 时记一次分数快照，均为追加写入，不覆盖之前的扫描记录。
+    /// `scanned_at` 或可解析的 `report_json` 缺失时直接跳过；两者都在时每次调用
+    /// 都会追加，即便这次保存只更新了安装状态、并未重新扫描。
+    fn record_security_scan(
+        conn: &Connection,
+        subject_type: &str,
+        subject_id: &str,
+        score: Option<i32>,
+        report_json: Option<&str>,
+        scanned_at: Option<&DateTime<Utc>>,
+    ) -> Result<()> {
+        let Some(scanned_at) = scanned_at else { return Ok(()); };
+        let Some(issues) = report_json
+            .and_then(|json| serde_json::from_str::<SecurityReport>(json).ok())
+            .map(|report| report.issues)
+        else {
+            return Ok(());
+        };
+        let scanned_at = scanned_at.to_rfc3339();
+
+        for issue in &issues {
+            conn.execute(
+                "INSERT INTO security_findings (subject_type, subject_id, rule_id, severity, title, detail, scanned_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    subject_type,
+                    subject_id,
+                    issue.category.rule_id(),
+                    issue.severity.as_str(),
+                    issue.category.short_description(),
+                    issue.description,
+                    scanned_at,
+                ],
+            )?;
+        }
+
+        if let Some(score) = score {
+            conn.execute(
+                "INSERT INTO security_score_history (subject_type, subject_id, score, scanned_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![subject_type, subject_id, score, scanned_at],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// 按严重程度查询所有 `security_findings`，最新扫描排在前面
+    pub fn findings_by_severity(&self, severity: IssueSeverity) -> Result<Vec<SecurityFinding>> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        let mut stmt = conn.prepare(
+            "SELECT id, subject_type, subject_id, rule_id, severity, title, detail, scanned_at
+             FROM security_findings
+             WHERE severity = ?1
+             ORDER BY scanned_at DESC"
+        )?;
+
+        let findings = stmt.query_map(params![severity.as_str()], Self::finding_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(findings)
+    }
+
+    /// 获取某个 subject（skill 或 plugin）的全部历史 findings，最新扫描排在前面
+    pub fn findings_for(&self, subject_id: &str) -> Result<Vec<SecurityFinding>> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        let mut stmt = conn.prepare(
+            "SELECT id, subject_type, subject_id, rule_id, severity, title, detail, scanned_at
+             FROM security_findings
+             WHERE subject_id = ?1
+             ORDER BY scanned_at DESC"
+        )?;
+
+        let findings = stmt.query_map(params![subject_id], Self::finding_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(findings)
+    }
+
+    fn finding_from_row(row: &Row) -> rusqlite::Result<SecurityFinding> {
+        let severity: String = row.get(4)?;
+        let severity = IssueSeverity::parse(&severity).unwrap_or(IssueSeverity::Info);
+        let scanned_at: String = row.get(7)?;
+
+        Ok(SecurityFinding {
+            id: row.get(0)?,
+            subject_type: row.get(1)?,
+            subject_id: row.get(2)?,
+            rule_id: row.get(3)?,
+            severity,
+            title: row.get(5)?,
+            detail: row.get(6)?,
+            scanned_at: scanned_at.parse().unwrap_or_else(|_| Utc::now()),
+        })
+    }
+
+    /// 某个 subject（skill 或 plugin）的分数随时间变化的趋势，按扫描时间升序排列
+    pub fn score_history(&self, subject_id: &str) -> Result<Vec<ScoreHistoryEntry>> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        let mut stmt = conn.prepare(
+            "SELECT score, scanned_at
+             FROM security_score_history
+             WHERE subject_id = ?1
+             ORDER BY scanned_at ASC"
+        )?;
+
+        let history = stmt.query_map(params![subject_id], |row| {
+            let scanned_at: String = row.get(1)?;
+            Ok(ScoreHistoryEntry {
+                score: row.get(0)?,
+                scanned_at: scanned_at.parse().unwrap_or_else(|_| Utc::now()),
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(history)
+    }
+
     /// 获取所有 skills
     pub fn get_skills(&self) -> Result<Vec<Skill>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
         let mut stmt = conn.prepare(
             "SELECT id, name, description, repository_url, repository_owner, file_path, version, author,
-                    installed, installed_at, local_path, local_paths, checksum, security_score, security_issues, security_level, scanned_at, installed_commit_sha
+                    installed, installed_at, local_path, local_paths, checksum, security_score, security_issues, security_level, scanned_at, installed_commit_sha, capability_manifest, content_checksum, report_json, needs_redownload
              FROM skills"
         )?;
 
-        let skills = stmt.query_map([], |row| {
-            let security_issues: Option<String> = row.get(14)?;
-            let security_issues = security_issues
-                .and_then(|s| serde_json::from_str(&s).ok());
+        let skills = stmt.query_map([], Self::skill_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
 
-            let local_paths: Option<String> = row.get(11)?;
-            let local_paths = local_paths
-                .and_then(|s| serde_json::from_str(&s).ok());
+        Ok(skills)
+    }
 
-            Ok(Skill {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                repository_url: row.get(3)?,
-                repository_owner: row.get(4)?,
-                file_path: row.get(5)?,
-                version: row.get(6)?,
-                author: row.get(7)?,
-                installed: row.get::<_, i32>(8)? != 0,
-                installed_at: row.get::<_, Option<String>>(9)?
-                    .and_then(|s| s.parse().ok()),
-                local_path: row.get(10)?,
-                local_paths,
-                checksum: row.get(12)?,
-                security_score: row.get(13)?,
-                security_issues,
-                security_level: row.get(15)?,
-                scanned_at: row.get::<_, Option<String>>(16)?
-                    .and_then(|s| s.parse().ok()),
-                installed_commit_sha: row.get(17)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+    /// 按相关度（BM25，`ORDER BY rank`）对 name/description/author 做全文检索，
+    /// 支持 FTS5 的前缀查询（`term*`）与多词查询语法
+    pub fn search_skills(&self, query: &str) -> Result<Vec<Skill>> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        let mut stmt = conn.prepare(
+            "SELECT s.id, s.name, s.description, s.repository_url, s.repository_owner, s.file_path, s.version, s.author,
+                    s.installed, s.installed_at, s.local_path, s.local_paths, s.checksum, s.security_score, s.security_issues, s.security_level, s.scanned_at, s.installed_commit_sha, s.capability_manifest, s.content_checksum, s.report_json, s.needs_redownload
+             FROM skills_fts
+             JOIN skills s ON s.rowid = skills_fts.rowid
+             WHERE skills_fts MATCH ?1
+             ORDER BY rank"
+        )?;
+
+        let skills = stmt.query_map(params![query], Self::skill_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(skills)
     }
 
+    fn skill_from_row(row: &Row) -> rusqlite::Result<Skill> {
+        let security_issues: Option<String> = row.get(14)?;
+        let security_issues = security_issues
+            .and_then(|s| serde_json::from_str(&s).ok());
+
+        let local_paths: Option<String> = row.get(11)?;
+        let local_paths = local_paths
+            .and_then(|s| serde_json::from_str(&s).ok());
+
+        let capability_manifest: Option<String> = row.get(18)?;
+        let capability_manifest = capability_manifest
+            .and_then(|s| serde_json::from_str(&s).ok());
+
+        Ok(Skill {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            repository_url: row.get(3)?,
+            repository_owner: row.get(4)?,
+            file_path: row.get(5)?,
+            version: row.get(6)?,
+            author: row.get(7)?,
+            installed: row.get::<_, i32>(8)? != 0,
+            installed_at: row.get::<_, Option<String>>(9)?
+                .and_then(|s| s.parse().ok()),
+            local_path: row.get(10)?,
+            local_paths,
+            checksum: row.get(12)?,
+            security_score: row.get(13)?,
+            security_issues,
+            security_level: row.get(15)?,
+            scanned_at: row.get::<_, Option<String>>(16)?
+                .and_then(|s| s.parse().ok()),
+            installed_commit_sha: row.get(17)?,
+            capability_manifest,
+            content_checksum: row.get(19)?,
+            report_json: row.get(20)?,
+            needs_redownload: row.get::<_, i32>(21)? != 0,
+        })
+    }
+
     /// 获取所有 plugins
     pub fn get_plugins(&self) -> Result<Vec<Plugin>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
         let mut stmt = conn.prepare(
             "SELECT id, claude_id, name, description, version, installed_version, author, repository_url, repository_owner,
                     marketplace_name, source, discovery_source, marketplace_add_command, plugin_install_command,
                     installed, installed_at, claude_scope, claude_enabled, claude_install_path, claude_last_updated,
-                    security_score, security_issues, security_level, scanned_at, staging_path, install_log, install_status
+                    security_score, security_issues, security_level, scanned_at, staging_path, install_log, install_status,
+                    backend, scanned_commit_sha, report_json, dependencies, manifest_digest, guard_risk_score, guard_findings,
+                    signature_verified, signature_signer, branch, revision, capabilities
              FROM plugins"
         )?;
 
-        let plugins = stmt.query_map([], |row| {
-            let security_issues: Option<String> = row.get(21)?;
-            let security_issues = security_issues
-                .and_then(|s| serde_json::from_str(&s).ok());
+        let plugins = stmt.query_map([], Self::plugin_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
 
-            Ok(Plugin {
-                id: row.get(0)?,
-                claude_id: row.get(1)?,
-                name: row.get(2)?,
-                description: row.get(3)?,
-                version: row.get(4)?,
-                installed_version: row.get(5)?,
-                author: row.get(6)?,
-                repository_url: row.get(7)?,
-                repository_owner: row.get(8)?,
-                marketplace_name: row.get(9)?,
-                source: row.get(10)?,
-                discovery_source: row.get(11)?,
-                marketplace_add_command: row.get(12)?,
-                plugin_install_command: row.get(13)?,
-                installed: row.get::<_, i32>(14)? != 0,
-                installed_at: row.get::<_, Option<String>>(15)?
-                    .and_then(|s| s.parse().ok()),
-                claude_scope: row.get(16)?,
-                claude_enabled: row.get::<_, Option<i32>>(17)?.map(|v| v != 0),
-                claude_install_path: row.get(18)?,
-                claude_last_updated: row.get::<_, Option<String>>(19)?
-                    .and_then(|s| s.parse().ok()),
-                security_score: row.get(20)?,
-                security_issues,
-                security_level: row.get(22)?,
-                scanned_at: row.get::<_, Option<String>>(23)?
-                    .and_then(|s| s.parse().ok()),
-                staging_path: row.get(24)?,
-                install_log: row.get(25)?,
-                install_status: row.get(26)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+        Ok(plugins)
+    }
+
+    /// 按相关度（BM25，`ORDER BY rank`）对 name/description/author 做全文检索，
+    /// 支持 FTS5 的前缀查询（`term*`）与多词查询语法
+    pub fn search_plugins(&self, query: &str) -> Result<Vec<Plugin>> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        let mut stmt = conn.prepare(
+            "SELECT p.id, p.claude_id, p.name, p.description, p.version, p.installed_version, p.author, p.repository_url, p.repository_owner,
+                    p.marketplace_name, p.source, p.discovery_source, p.marketplace_add_command, p.plugin_install_command,
+                    p.installed, p.installed_at, p.claude_scope, p.claude_enabled, p.claude_install_path, p.claude_last_updated,
+                    p.security_score, p.security_issues, p.security_level, p.scanned_at, p.staging_path, p.install_log, p.install_status,
+                    p.backend, p.scanned_commit_sha, p.report_json, p.dependencies, p.manifest_digest, p.guard_risk_score, p.guard_findings,
+                    p.signature_verified, p.signature_signer, p.branch, p.revision, p.capabilities
+             FROM plugins_fts
+             JOIN plugins p ON p.rowid = plugins_fts.rowid
+             WHERE plugins_fts MATCH ?1
+             ORDER BY rank"
+        )?;
+
+        let plugins = stmt.query_map(params![query], Self::plugin_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(plugins)
     }
 
+    fn plugin_from_row(row: &Row) -> rusqlite::Result<Plugin> {
+        let security_issues: Option<String> = row.get(21)?;
+        let security_issues = security_issues
+            .and_then(|s| serde_json::from_str(&s).ok());
+        let dependencies: Option<String> = row.get(30)?;
+        let dependencies = dependencies
+            .and_then(|s| serde_json::from_str(&s).ok());
+        let guard_findings: Option<String> = row.get(33)?;
+        let guard_findings = guard_findings
+            .and_then(|s| serde_json::from_str(&s).ok());
+        let capabilities: Option<String> = row.get(38)?;
+        let capabilities = capabilities
+            .and_then(|s| serde_json::from_str(&s).ok());
+
+        Ok(Plugin {
+            id: row.get(0)?,
+            claude_id: row.get(1)?,
+            name: row.get(2)?,
+            description: row.get(3)?,
+            version: row.get(4)?,
+            installed_version: row.get(5)?,
+            author: row.get(6)?,
+            repository_url: row.get(7)?,
+            repository_owner: row.get(8)?,
+            marketplace_name: row.get(9)?,
+            source: row.get(10)?,
+            discovery_source: row.get(11)?,
+            marketplace_add_command: row.get(12)?,
+            plugin_install_command: row.get(13)?,
+            installed: row.get::<_, i32>(14)? != 0,
+            installed_at: row.get::<_, Option<String>>(15)?
+                .and_then(|s| s.parse().ok()),
+            claude_scope: row.get(16)?,
+            claude_enabled: row.get::<_, Option<i32>>(17)?.map(|v| v != 0),
+            claude_install_path: row.get(18)?,
+            claude_last_updated: row.get::<_, Option<String>>(19)?
+                .and_then(|s| s.parse().ok()),
+            security_score: row.get(20)?,
+            security_issues,
+            security_level: row.get(22)?,
+            scanned_at: row.get::<_, Option<String>>(23)?
+                .and_then(|s| s.parse().ok()),
+            staging_path: row.get(24)?,
+            install_log: row.get(25)?,
+            install_status: row.get(26)?,
+            backend: row.get(27)?,
+            scanned_commit_sha: row.get(28)?,
+            report_json: row.get(29)?,
+            dependencies,
+            manifest_digest: row.get(31)?,
+            guard_risk_score: row.get(32)?,
+            guard_findings,
+            signature_verified: row.get::<_, Option<i32>>(34)?.map(|v| v != 0),
+            signature_signer: row.get(35)?,
+            branch: row.get(36)?,
+            revision: row.get(37)?,
+            capabilities,
+        })
+    }
+
     /// 删除仓库
     pub fn delete_repository(&self, repo_id: &str) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
         conn.execute("DELETE FROM repositories WHERE id = ?1", params![repo_id])?;
         Ok(())
     }
 
     /// 删除指定仓库的所有未安装技能
     pub fn delete_uninstalled_skills_by_repository_url(&self, repository_url: &str) -> Result<usize> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
         let deleted_count = conn.execute(
             "DELETE FROM skills WHERE repository_url = ?1 AND installed = 0",
             params![repository_url]
@@ -451,7 +1328,7 @@ impl Database {
 
     /// 删除指定仓库的所有未安装插件
     pub fn delete_uninstalled_plugins_by_repository_url(&self, repository_url: &str) -> Result<usize> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
         let deleted_count = conn.execute(
             "DELETE FROM plugins WHERE repository_url = ?1 AND installed = 0",
             params![repository_url]
@@ -461,83 +1338,78 @@ impl Database {
 
     /// 删除 skill
     pub fn delete_skill(&self, skill_id: &str) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
         conn.execute("DELETE FROM skills WHERE id = ?1", params![skill_id])?;
         conn.execute("DELETE FROM installations WHERE skill_id = ?1", params![skill_id])?;
         Ok(())
     }
 
-    /// 删除 plugin 记录
-    pub fn delete_plugin(&self, plugin_id: &str) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM plugins WHERE id = ?1", params![plugin_id])?;
-        Ok(())
-    }
-
-    /// 数据库迁移：添加缓存相关字段
-    fn migrate_add_cache_fields(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-
-        // 添加 cache_path 列
-        let _ = conn.execute(
-            "ALTER TABLE repositories ADD COLUMN cache_path TEXT",
-            [],
-        );
+    /// 查出 `installations` 表中 `skill_id` 已不存在于 `skills` 表的孤儿记录，
+    /// 供 [`crate::services::SkillManager::verify_installations`] 汇总进报告
+    pub fn get_orphaned_installations(&self) -> Result<Vec<SkillInstallation>> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        let mut stmt = conn.prepare(
+            "SELECT i.skill_id, i.installed_at, i.version, i.local_path, i.checksum
+             FROM installations i
+             LEFT JOIN skills s ON s.id = i.skill_id
+             WHERE s.id IS NULL"
+        )?;
 
-        // 添加 cached_at 列
-        let _ = conn.execute(
-            "ALTER TABLE repositories ADD COLUMN cached_at TEXT",
-            [],
-        );
+        let installations = stmt.query_map([], |row| {
+            let installed_at: String = row.get(1)?;
+            Ok(SkillInstallation {
+                skill_id: row.get(0)?,
+                installed_at: installed_at.parse().unwrap_or_else(|_| chrono::Utc::now()),
+                version: row.get(2)?,
+                local_path: row.get(3)?,
+                checksum: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
 
-        // 添加 cached_commit_sha 列
-        let _ = conn.execute(
-            "ALTER TABLE repositories ADD COLUMN cached_commit_sha TEXT",
-            [],
-        );
+        Ok(installations)
+    }
 
+    /// 清除单条孤儿 `installations` 记录
+    pub fn delete_orphaned_installation(&self, skill_id: &str) -> Result<()> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        conn.execute("DELETE FROM installations WHERE skill_id = ?1", params![skill_id])?;
         Ok(())
     }
 
-    /// 数据库迁移：添加安全扫描增强字段
-    fn migrate_add_security_enhancement_fields(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-
-        // 添加 security_level 列
-        let _ = conn.execute(
-            "ALTER TABLE skills ADD COLUMN security_level TEXT",
-            [],
-        );
-
-        // 添加 scanned_at 列
-        let _ = conn.execute(
-            "ALTER TABLE skills ADD COLUMN scanned_at TEXT",
-            [],
-        );
-
+    /// 记录一次完整的目录安装：`version` 是实际安装所用的 ref/commit SHA，
+    /// `checksum` 是安装目录下所有文件的聚合校验和。同一 `skill_id` 重复安装
+    /// 时直接覆盖旧记录
+    pub fn save_installation(&self, installation: &SkillInstallation) -> Result<()> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        conn.execute(
+            "INSERT OR REPLACE INTO installations (skill_id, installed_at, version, local_path, checksum)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                installation.skill_id,
+                installation.installed_at.to_rfc3339(),
+                installation.version,
+                installation.local_path,
+                installation.checksum,
+            ],
+        )?;
         Ok(())
     }
 
-    /// 数据库迁移：添加 local_paths 列,支持多个安装路径
-    fn migrate_add_local_paths(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-
-        // 添加 local_paths 列（JSON 数组格式）
-        let _ = conn.execute(
-            "ALTER TABLE skills ADD COLUMN local_paths TEXT",
-            [],
-        );
-
-        // 将现有的 local_path 迁移到 local_paths 数组中
+    /// 把 skill 标记为需要重新下载（完整性校验发现文件缺失或校验和不一致）
+    pub fn flag_skill_needs_redownload(&self, skill_id: &str) -> Result<()> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
         conn.execute(
-            r#"
-            UPDATE skills
-            SET local_paths = json_array(local_path)
-            WHERE local_path IS NOT NULL AND local_paths IS NULL
-            "#,
-            [],
+            "UPDATE skills SET needs_redownload = 1 WHERE id = ?1",
+            params![skill_id],
         )?;
+        Ok(())
+    }
 
+    /// 删除 plugin 记录
+    pub fn delete_plugin(&self, plugin_id: &str) -> Result<()> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        conn.execute("DELETE FROM plugins WHERE id = ?1", params![plugin_id])?;
         Ok(())
     }
 
@@ -549,7 +1421,7 @@ impl Database {
         cached_at: chrono::DateTime<chrono::Utc>,
         cached_commit_sha: Option<&str>,
     ) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
 
         conn.execute(
             "UPDATE repositories
@@ -569,7 +1441,7 @@ impl Database {
 
     /// 清除仓库缓存信息（但不删除文件）
     pub fn clear_repository_cache_metadata(&self, repo_id: &str) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
 
         conn.execute(
             "UPDATE repositories
@@ -581,66 +1453,13 @@ impl Database {
         Ok(())
     }
 
-    /// 数据库迁移：添加 installed_commit_sha 列
-    fn migrate_add_installed_commit_sha(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-
-        // 添加 installed_commit_sha 列
-        let _ = conn.execute(
-            "ALTER TABLE skills ADD COLUMN installed_commit_sha TEXT",
-            [],
-        );
-
-        Ok(())
-    }
-
-    /// 数据库迁移：为 plugins 增加 Claude CLI 同步字段
-    fn migrate_add_plugin_claude_fields(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-
-        let _ = conn.execute("ALTER TABLE plugins ADD COLUMN claude_id TEXT", []);
-        let _ = conn.execute("ALTER TABLE plugins ADD COLUMN installed_version TEXT", []);
-        let _ = conn.execute("ALTER TABLE plugins ADD COLUMN discovery_source TEXT", []);
-        let _ = conn.execute("ALTER TABLE plugins ADD COLUMN claude_scope TEXT", []);
-        let _ = conn.execute("ALTER TABLE plugins ADD COLUMN claude_enabled INTEGER", []);
-        let _ = conn.execute("ALTER TABLE plugins ADD COLUMN claude_install_path TEXT", []);
-        let _ = conn.execute("ALTER TABLE plugins ADD COLUMN claude_last_updated TEXT", []);
-
-        // 填充缺失字段，保证旧数据可被新逻辑识别
-        let _ = conn.execute(
-            "UPDATE plugins
-             SET claude_id = name || '@' || marketplace_name
-             WHERE claude_id IS NULL",
-            [],
-        );
-
-        let _ = conn.execute(
-            "UPDATE plugins
-             SET discovery_source = 'repository_scan'
-             WHERE discovery_source IS NULL",
-            [],
-        );
-
-        Ok(())
-    }
-
-    /// 数据库迁移：为 plugins 增加 marketplace/plugin 安装指令字段
-    fn migrate_add_plugin_install_commands(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-
-        let _ = conn.execute("ALTER TABLE plugins ADD COLUMN marketplace_add_command TEXT", []);
-        let _ = conn.execute("ALTER TABLE plugins ADD COLUMN plugin_install_command TEXT", []);
-
-        Ok(())
-    }
-
     /// 获取单个仓库信息
     pub fn get_repository(&self, repo_id: &str) -> Result<Option<Repository>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
 
         let mut stmt = conn.prepare(
             "SELECT id, url, name, description, enabled, scan_subdirs,
-                    added_at, last_scanned, cache_path, cached_at, cached_commit_sha
+                    added_at, last_scanned, cache_path, cached_at, cached_commit_sha, git_ref, api_base_url, raw_base_url
              FROM repositories
              WHERE id = ?1"
         )?;
@@ -660,6 +1479,11 @@ impl Database {
                 cached_at: row.get::<_, Option<String>>(9)?
                     .and_then(|s| s.parse().ok()),
                 cached_commit_sha: row.get(10)?,
+                git_ref: row.get(11)?,
+                host: match (row.get::<_, Option<String>>(12)?, row.get::<_, Option<String>>(13)?) {
+                    (Some(api_base_url), Some(raw_base_url)) => Some(HostConfig { api_base_url, raw_base_url }),
+                    _ => None,
+                },
             })
         }).optional()?;
 
@@ -668,7 +1492,7 @@ impl Database {
 
     /// 获取所有未扫描的仓库ID列表
     pub fn get_unscanned_repositories(&self) -> Result<Vec<String>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
         let mut stmt = conn.prepare(
             "SELECT id FROM repositories WHERE last_scanned IS NULL AND enabled = 1"
         )?;
@@ -680,4 +1504,181 @@ impl Database {
 
         Ok(repo_ids)
     }
+
+    /// 获取所有已缓存的更新检查状态，供 UI 直接读取而无需触发 CLI 调用
+    pub fn get_update_status(&self) -> Result<Vec<UpdateStatus>> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+        let mut stmt = conn.prepare(
+            "SELECT item_type, item_id, item_name, available_version, checked_at,
+                    last_notified_version, last_notified_at
+             FROM update_status"
+        )?;
+
+        let statuses = stmt.query_map([], |row| {
+            Ok(UpdateStatus {
+                item_type: row.get(0)?,
+                item_id: row.get(1)?,
+                item_name: row.get(2)?,
+                available_version: row.get(3)?,
+                checked_at: row.get::<_, String>(4)?.parse().unwrap_or_else(|_| chrono::Utc::now()),
+                last_notified_version: row.get(5)?,
+                last_notified_at: row.get::<_, Option<String>>(6)?
+                    .and_then(|s| s.parse().ok()),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(statuses)
+    }
+
+    /// 记录一次后台更新检查的结果（不改变上次提醒记录，由调用方决定是否需要
+    /// 通过 [`Self::mark_update_notified`] 单独标记为"已提醒"）
+    pub fn upsert_update_check(
+        &self,
+        item_type: &str,
+        item_id: &str,
+        item_name: &str,
+        available_version: &str,
+        checked_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<()> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+
+        conn.execute(
+            "INSERT INTO update_status (item_type, item_id, item_name, available_version, checked_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(item_type, item_id) DO UPDATE SET
+                item_name = excluded.item_name,
+                available_version = excluded.available_version,
+                checked_at = excluded.checked_at",
+            params![item_type, item_id, item_name, available_version, checked_at.to_rfc3339()],
+        )?;
+
+        Ok(())
+    }
+
+    /// 标记某个条目已经就给定版本向用户发出过提醒，用于后续抑制重复提醒
+    pub fn mark_update_notified(
+        &self,
+        item_type: &str,
+        item_id: &str,
+        version: &str,
+        notified_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<()> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+
+        conn.execute(
+            "UPDATE update_status
+             SET last_notified_version = ?1, last_notified_at = ?2
+             WHERE item_type = ?3 AND item_id = ?4",
+            params![version, notified_at.to_rfc3339(), item_type, item_id],
+        )?;
+
+        Ok(())
+    }
+
+    /// 仪表盘用的聚合统计，一次连接取出所有计数，避免把整张表加载到 Rust 里再数
+    pub fn stats(&self) -> Result<DatabaseStats> {
+        let conn = self.pool.get().context("Failed to get a pooled sqlite connection")?;
+
+        let (total_repositories, enabled_repositories, unscanned_repositories): (i64, i64, i64) = conn.query_row(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(enabled), 0),
+                    COALESCE(SUM(CASE WHEN last_scanned IS NULL THEN 1 ELSE 0 END), 0)
+             FROM repositories",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+
+        let (total_skills, installed_skills): (i64, i64) = conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(installed), 0) FROM skills",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let (total_plugins, installed_plugins): (i64, i64) = conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(installed), 0) FROM plugins",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let skill_security_levels = Self::security_level_counts(&conn, "skills")?;
+        let plugin_security_levels = Self::security_level_counts(&conn, "plugins")?;
+        let skill_score_buckets = Self::score_buckets(&conn, "skills")?;
+
+        let oldest_scanned_at: Option<String> = conn.query_row(
+            "SELECT MIN(scanned_at) FROM (
+                SELECT scanned_at FROM skills WHERE scanned_at IS NOT NULL
+                UNION ALL
+                SELECT scanned_at FROM plugins WHERE scanned_at IS NOT NULL
+             )",
+            [],
+            |row| row.get(0),
+        )?;
+        let oldest_scanned_at = oldest_scanned_at.and_then(|s| s.parse().ok());
+
+        Ok(DatabaseStats {
+            total_repositories,
+            enabled_repositories,
+            unscanned_repositories,
+            total_skills,
+            installed_skills,
+            total_plugins,
+            installed_plugins,
+            skill_security_levels,
+            plugin_security_levels,
+            skill_score_buckets,
+            oldest_scanned_at,
+        })
+    }
+
+    /// 按 `security_level` 分组计数，NULL（未扫描）归入 `"unscanned"`。
+    /// `table` 只接受本文件内部传入的字面量表名（`"skills"` / `"plugins"`），不接受外部输入。
+    fn security_level_counts(conn: &Connection, table: &str) -> Result<Vec<SecurityLevelCount>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT COALESCE(security_level, 'unscanned') AS level, COUNT(*) AS count
+             FROM {table}
+             GROUP BY level
+             ORDER BY count DESC"
+        ))?;
+
+        let counts = stmt.query_map([], |row| {
+            Ok(SecurityLevelCount {
+                level: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(counts)
+    }
+
+    /// 按 `security_score` 以 20 分为一档分桶，NULL（未扫描）归入 `"unscanned"`。
+    /// `table` 只接受本文件内部传入的字面量表名。
+    fn score_buckets(conn: &Connection, table: &str) -> Result<Vec<ScoreBucket>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT
+                CASE
+                    WHEN security_score IS NULL THEN 'unscanned'
+                    WHEN security_score >= 80 THEN '80-100'
+                    WHEN security_score >= 60 THEN '60-79'
+                    WHEN security_score >= 40 THEN '40-59'
+                    WHEN security_score >= 20 THEN '20-39'
+                    ELSE '0-19'
+                END AS range,
+                COUNT(*) AS count
+             FROM {table}
+             GROUP BY range
+             ORDER BY range"
+        ))?;
+
+        let buckets = stmt.query_map([], |row| {
+            Ok(ScoreBucket {
+                range: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(buckets)
+    }
 }
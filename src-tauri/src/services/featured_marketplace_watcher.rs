@@ -0,0 +1,110 @@
+use crate::models::FeaturedMarketplacesConfig;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 事件合并窗口：`refresh_featured_marketplaces` 走的是临时文件写入 + rename
+/// 落盘，短时间内会在缓存目录里触发好几个文件系统事件；在这段时间内收到的
+/// 后续事件只会延长等待，窗口到期后才真正重新解析一次
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// watcher 出错（通常是被监听的目录被删除）之后，等多久重新建立监听
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// 精选 marketplace 缓存发生变化并成功重新解析后，推送给前端的事件名；
+/// payload 是重新解析出的 [`FeaturedMarketplacesConfig`]
+pub const FEATURED_MARKETPLACES_UPDATED_EVENT: &str = "featured-marketplaces-updated";
+
+/// 启动一个后台线程，监听 `cache_path` 所在目录，文件发生变化时重新解析 YAML
+/// 并通过 [`FEATURED_MARKETPLACES_UPDATED_EVENT`] 推送给前端，这样后台刷新或
+/// 用户手动编辑缓存文件之后不需要切换页面就能看到最新数据。
+///
+/// 只监听目录、不直接监听文件句柄：`refresh_featured_marketplaces` 是临时
+/// 文件 + rename 落盘的，直接监听文件在 rename 后会失效。监听目录再加上
+/// 出错即重建的外层循环，也让 watcher 在缓存目录被整个删除/重建（例如用户
+/// 清空了 app_data_dir）之后能自愈，而不需要应用重启。
+///
+/// 解析失败（文件被截断到一半、内容损坏）的变更会被静默跳过并只记录 debug
+/// 日志，只有成功解析之后才会 emit，避免前端看到一份空的或半份的配置。
+pub fn spawn_featured_marketplaces_watcher(app: AppHandle, cache_path: PathBuf) {
+    let watch_dir = cache_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| cache_path.clone());
+
+    std::thread::spawn(move || loop {
+        if let Err(e) = watch_until_broken(&app, &watch_dir, &cache_path) {
+            log::debug!(
+                "精选 marketplace 缓存监听中断，{:?} 后重试: {}",
+                RETRY_DELAY,
+                e
+            );
+        }
+        std::thread::sleep(RETRY_DELAY);
+    });
+}
+
+/// 建立一次 watch 并持续消费事件，直到 watcher 本身出错才返回；调用方负责在
+/// 延迟之后重新调用本函数建立新的 watch
+fn watch_until_broken(app: &AppHandle, watch_dir: &Path, cache_path: &Path) -> notify::Result<()> {
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+
+    let mut watcher = RecommendedWatcher::new(
+        move |res| {
+            let _ = tx.send(res);
+        },
+        notify::Config::default(),
+    )?;
+    watcher.watch(watch_dir, RecursiveMode::NonRecursive)?;
+
+    loop {
+        let first = rx
+            .recv()
+            .map_err(|_| notify::Error::generic("featured marketplaces watcher channel closed"))?;
+        let first = first?;
+        let mut touched = event_touches_path(&first, cache_path);
+
+        // 吸收防抖窗口内陆续到达的其它事件，合并成一次重新解析；`watch_dir`
+        // 监听的是整个目录（同目录下还有 agent-skills.db 的 WAL churn、
+        // operation_logs/ 等不相关的写入），所以只有当这批事件里真的有一个
+        // touch 到 `cache_path` 本身时才值得重新解析
+        loop {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(res) => {
+                    let event = res?;
+                    touched = touched || event_touches_path(&event, cache_path);
+                }
+                Err(_) => break,
+            }
+        }
+
+        if !touched {
+            continue;
+        }
+
+        match reload(cache_path) {
+            Ok(config) => {
+                if let Err(e) = app.emit(FEATURED_MARKETPLACES_UPDATED_EVENT, &config) {
+                    log::warn!("推送精选 marketplace 更新事件失败: {}", e);
+                }
+            }
+            Err(e) => {
+                log::debug!("精选 marketplace 缓存变更但重新解析失败，已跳过: {}", e);
+            }
+        }
+    }
+}
+
+/// 事件涉及的路径里是否有一个就是 `cache_path` 本身
+fn event_touches_path(event: &Event, cache_path: &Path) -> bool {
+    event.paths.iter().any(|p| p == cache_path)
+}
+
+fn reload(cache_path: &Path) -> Result<FeaturedMarketplacesConfig, String> {
+    let yaml = std::fs::read_to_string(cache_path)
+        .map_err(|e| format!("Failed to read featured marketplaces cache: {}", e))?;
+    serde_yaml::from_str(&yaml)
+        .map_err(|e| format!("Failed to parse featured marketplaces cache: {}", e))
+}
@@ -1,9 +1,9 @@
-use crate::models::{GitHubContent, Repository, Skill};
-use anyhow::{Result, Context};
-use reqwest::Client;
+use crate::models::{GitHubContent, HostConfig, Repository, Skill};
+use anyhow::{Context, Result};
+use octocrab::Octocrab;
 use serde::Deserialize;
-use std::future::Future;
-use std::pin::Pin;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
 
 /// SKILL.md 文件的 frontmatter
 #[derive(Debug, Deserialize)]
@@ -12,201 +12,502 @@ struct SkillFrontmatter {
     description: Option<String>,
 }
 
+/// 单次 git tree（递归）条目
+#[derive(Debug, Deserialize)]
+struct GitTreeEntry {
+    path: String,
+    #[serde(rename = "type")]
+    entry_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitTreeResponse {
+    tree: Vec<GitTreeEntry>,
+    truncated: bool,
+}
+
+/// `GET /rate_limit` 的响应（调用本身不计入限额），用于在真正打到 403 之前
+/// 就知道 `X-RateLimit-Remaining`/`X-RateLimit-Reset` 还剩多少
+#[derive(Debug, Deserialize)]
+struct RateLimitResponse {
+    resources: RateLimitResources,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitResources {
+    core: RateLimitWindow,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitWindow {
+    /// 对应响应头 `X-RateLimit-Remaining`
+    remaining: u32,
+    /// 对应响应头 `X-RateLimit-Reset`（unix 时间戳，窗口重置时间）
+    reset: i64,
+}
+
+/// 递归扫描时允许下探的最大目录深度，避免恶意/异常大仓库无限展开
+const MAX_SCAN_DEPTH: usize = 5;
+/// 遇到 403/429（限流）时的最大重试次数
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+/// 单次重试最多等待的时长，即便 `reset` 建议的窗口更长，也不至于挂起整个扫描
+const MAX_BACKOFF: Duration = Duration::from_secs(120);
+
 pub struct GitHubService {
-    client: Client,
-    api_base: String,
+    client: Octocrab,
+    token: Option<String>,
+    host: HostConfig,
+}
+
+/// [`GitHubService::scoped_to`] 的返回值：大多数仓库沿用公共 github.com，
+/// 直接借用现有实例；只有指向了另一个主机的仓库才需要临时构造一个新客户端
+pub(crate) enum ScopedGitHubService<'a> {
+    Borrowed(&'a GitHubService),
+    Owned(GitHubService),
+}
+
+impl std::ops::Deref for ScopedGitHubService<'_> {
+    type Target = GitHubService;
+
+    fn deref(&self) -> &GitHubService {
+        match self {
+            ScopedGitHubService::Borrowed(service) => service,
+            ScopedGitHubService::Owned(service) => service,
+        }
+    }
 }
 
 impl GitHubService {
     pub fn new() -> Self {
-        Self {
-            client: Client::builder()
-                .user_agent("agent-skills-guard/0.1.0")
-                .build()
-                .unwrap(),
-            api_base: "https://api.github.com".to_string(),
-        }
+        Self::with_token(None)
     }
 
-    /// 扫描仓库中的 skills
-    pub async fn scan_repository(&self, repo: &Repository) -> Result<Vec<Skill>> {
-        let (owner, repo_name) = Repository::from_github_url(&repo.url)?;
-        let mut skills = Vec::new();
-
-        // 获取仓库根目录内容
-        let contents = self.fetch_directory_contents(&owner, &repo_name, "").await?;
-
-        for item in contents {
-            if item.content_type == "dir" {
-                // 检查文件夹是否为 skill（包含 SKILL.md）
-                if self.is_skill_directory(&owner, &repo_name, &item.path).await? {
-                    let skill = Skill::new(
-                        item.name.clone(),
-                        repo.url.clone(),
-                        item.path.clone(),
-                    );
-                    skills.push(skill);
-                } else if repo.scan_subdirs {
-                    // 递归扫描子目录
-                    match self.scan_directory(&owner, &repo_name, &item.path, &repo.url).await {
-                        Ok(mut sub_skills) => skills.append(&mut sub_skills),
-                        Err(e) => log::warn!("Failed to scan subdirectory {}: {}", item.path, e),
-                    }
+    /// 使用可选的 personal access token 构造服务：提供 token 后请求以
+    /// `Authorization: Bearer` 发出，私有仓库可访问，速率限制也从
+    /// 60/小时提升到 5000/小时。指向公共 github.com。
+    pub fn with_token(token: Option<String>) -> Self {
+        Self::with_host(token, HostConfig::default())
+    }
+
+    /// 使用可选的 token 和主机端点配置构造服务，指向 GitHub Enterprise Server、
+    /// Gitee 等 GitHub REST API 兼容的自建实例；`host` 为默认值时等价于
+    /// [`Self::with_token`]
+    pub fn with_host(token: Option<String>, host: HostConfig) -> Self {
+        let client = Self::build_client(token.as_deref(), &host);
+        Self { client, token, host }
+    }
+
+    fn build_client(token: Option<&str>, host: &HostConfig) -> Octocrab {
+        let mut builder = Octocrab::builder();
+        if let Some(token) = token {
+            builder = builder.personal_token(token.to_string());
+        }
+        if host.api_base_url != HostConfig::default().api_base_url {
+            builder = match builder.base_uri(&host.api_base_url) {
+                Ok(builder) => builder,
+                Err(e) => {
+                    log::warn!("Invalid API base URL {}: {}, falling back to github.com", host.api_base_url, e);
+                    Octocrab::builder()
                 }
-            }
+            };
         }
 
-        Ok(skills)
+        builder
+            .build()
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to build Octocrab client, falling back to default: {}", e);
+                Octocrab::default()
+            })
     }
 
-    /// 递归扫描目录
-    fn scan_directory<'a>(
-        &'a self,
-        owner: &'a str,
-        repo: &'a str,
-        path: &'a str,
-        repo_url: &'a str,
-    ) -> Pin<Box<dyn Future<Output = Result<Vec<Skill>>> + Send + 'a>> {
-        Box::pin(async move {
-            let mut skills = Vec::new();
-            let contents = self.fetch_directory_contents(owner, repo, path).await?;
-
-            for item in contents {
-                if item.content_type == "dir" {
-                    // 检查文件夹是否为 skill（包含 SKILL.md）
-                    if self.is_skill_directory(owner, repo, &item.path).await? {
-                        let skill = Skill::new(
-                            item.name.clone(),
-                            repo_url.to_string(),
-                            item.path.clone(),
-                        );
-                        skills.push(skill);
-                    } else if path.split('/').count() < 5 {
-                        // 递归扫描（限制深度避免无限递归）
-                        match self.scan_directory(owner, repo, &item.path, repo_url).await {
-                            Ok(mut sub_skills) => skills.append(&mut sub_skills),
-                            Err(e) => log::warn!("Failed to scan subdirectory {}: {}", item.path, e),
-                        }
-                    }
-                }
+    /// 给定仓库实际所属的主机，返回一个能正确访问该主机的服务实例：主机与
+    /// 当前实例一致时原样复用，否则按需构造一个指向该主机的临时客户端
+    pub(crate) fn scoped_to(&self, repo: &Repository) -> ScopedGitHubService<'_> {
+        match &repo.host {
+            Some(host) if *host != self.host => {
+                ScopedGitHubService::Owned(Self::with_host(self.token.clone(), host.clone()))
             }
+            _ => ScopedGitHubService::Borrowed(self),
+        }
+    }
 
-            Ok(skills)
-        })
+    /// 该实例配置的原始文件服务根地址下某个文件的完整下载 URL
+    pub(crate) fn raw_file_url(&self, owner: &str, repo: &str, git_ref: &str, path: &str) -> String {
+        format!(
+            "{}/{}/{}/{}/{}",
+            self.host.raw_base_url, owner, repo, git_ref, path
+        )
+    }
+
+    /// 解析仓库的默认分支（不再硬编码 "main"）
+    pub async fn resolve_default_branch(&self, owner: &str, repo: &str) -> Result<String> {
+        let repository = self
+            .with_backoff(|| self.client.repos(owner, repo).get())
+            .await
+            .context("Failed to fetch repository metadata")?;
+
+        Ok(repository.default_branch.unwrap_or_else(|| "main".to_string()))
     }
 
-    /// 获取目录内容
-    async fn fetch_directory_contents(
+    /// 解析出实际要使用的 git ref：用户固定了分支/tag/commit SHA 就直接用它，
+    /// 否则解析仓库的实际默认分支（不再猜测 "main"）
+    pub async fn resolve_ref(&self, owner: &str, repo: &str, git_ref: Option<&str>) -> Result<String> {
+        match git_ref {
+            Some(git_ref) => Ok(git_ref.to_string()),
+            None => self.resolve_default_branch(owner, repo).await,
+        }
+    }
+
+    /// 扫描仓库中的 skills：解析出要使用的 git ref（固定 ref 或默认分支），
+    /// 单次调用 Git Trees API 递归取回整棵 git tree，在内存中找出所有
+    /// `SKILL.md` 对应的 skill 目录。GitHub 对单次 tree 响应有条目数/体积
+    /// 限制，响应被标记 `truncated` 时退回逐目录 BFS 兜底，保证大仓库也不会
+    /// 漏扫，只是慢很多。`repo.host` 固定了其它主机（GitHub Enterprise Server、
+    /// Gitee 等）时，透明地改用指向该主机的客户端
+    pub async fn scan_repository(&self, repo: &Repository) -> Result<Vec<Skill>> {
+        let service = self.scoped_to(repo);
+        let (owner, repo_name) = Repository::from_github_url(&repo.url)?;
+        let branch = service.resolve_ref(&owner, &repo_name, repo.git_ref.as_deref()).await?;
+
+        let tree = service.fetch_recursive_tree(&owner, &repo_name, &branch).await?;
+
+        let mut skill_dirs = if tree.truncated {
+            log::warn!(
+                "Git tree for {}/{} was truncated by GitHub; falling back to per-directory scan",
+                owner,
+                repo_name
+            );
+            service.scan_directory_walk(&owner, &repo_name, &branch).await?
+        } else {
+            skill_dirs_from_tree(&tree)
+        };
+
+        skill_dirs.sort_unstable();
+        skill_dirs.dedup();
+
+        let skills = skill_dirs
+            .into_iter()
+            .map(|dir| Skill::new(skill_name_from_path(&dir), repo.url.clone(), dir))
+            .collect();
+
+        Ok(skills)
+    }
+
+    /// 逐目录 BFS 扫描：`fetch_recursive_tree` 的响应被截断时的兜底路径，
+    /// 每个目录一次 Contents API 调用，比单次 tree 调用慢得多，但不受
+    /// GitHub 对单次 tree 响应条目数/体积的限制
+    async fn scan_directory_walk(
         &self,
         owner: &str,
         repo: &str,
-        path: &str,
-    ) -> Result<Vec<GitHubContent>> {
-        let url = if path.is_empty() {
-            format!("{}/repos/{}/{}/contents", self.api_base, owner, repo)
-        } else {
-            format!("{}/repos/{}/{}/contents/{}", self.api_base, owner, repo, path)
-        };
+        git_ref: &str,
+    ) -> Result<Vec<String>> {
+        let mut skill_dirs = Vec::new();
+        let mut queue: std::collections::VecDeque<(String, usize)> = std::collections::VecDeque::new();
+        queue.push_back((String::new(), 0));
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .context("Failed to fetch GitHub directory")?;
+        while let Some((dir, depth)) = queue.pop_front() {
+            let entries = self
+                .list_skill_files(owner, repo, &dir, Some(git_ref))
+                .await?;
+
+            let has_skill_md = entries
+                .iter()
+                .any(|entry| entry.content_type == "file" && entry.name.eq_ignore_ascii_case("SKILL.md"));
+            if has_skill_md {
+                skill_dirs.push(dir.clone());
+            }
 
-        if !response.status().is_success() {
-            anyhow::bail!("GitHub API returned error: {}", response.status());
+            if depth >= MAX_SCAN_DEPTH {
+                continue;
+            }
+            for entry in entries {
+                if entry.content_type == "dir" {
+                    queue.push_back((entry.path, depth + 1));
+                }
+            }
         }
 
-        let contents: Vec<GitHubContent> = response
-            .json()
-            .await
-            .context("Failed to parse GitHub response")?;
+        Ok(skill_dirs)
+    }
 
-        Ok(contents)
+    /// 调用一次 Git Trees API（`recursive=1`），取回整棵仓库树
+    async fn fetch_recursive_tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Result<GitTreeResponse> {
+        let route = format!(
+            "/repos/{owner}/{repo}/git/trees/{git_ref}?recursive=1",
+            owner = owner,
+            repo = repo,
+            git_ref = git_ref,
+        );
+
+        self.with_backoff(|| self.client.get(&route, None::<&()>))
+            .await
+            .context("Failed to fetch git tree")
     }
 
     /// 下载文件内容
     pub async fn download_file(&self, download_url: &str) -> Result<Vec<u8>> {
-        let response = self.client
-            .get(download_url)
-            .send()
+        let bytes = self
+            .with_backoff(|| async {
+                let response = reqwest::Client::new()
+                    .get(download_url)
+                    .header(reqwest::header::USER_AGENT, "agent-skills-guard")
+                    .send()
+                    .await?
+                    .error_for_status()?;
+                Ok::<_, anyhow::Error>(response.bytes().await?)
+            })
             .await
             .context("Failed to download file")?;
 
-        if !response.status().is_success() {
-            anyhow::bail!("Failed to download file: {}", response.status());
-        }
-
-        let bytes = response
-            .bytes()
-            .await
-            .context("Failed to read file bytes")?;
-
         Ok(bytes.to_vec())
     }
 
-    /// 判断文件夹是否为 skill（包含 SKILL.md）
-    async fn is_skill_directory(&self, owner: &str, repo: &str, path: &str) -> Result<bool> {
-        // 获取文件夹内容
-        match self.fetch_directory_contents(owner, repo, path).await {
-            Ok(contents) => {
-                // 检查是否包含 SKILL.md 文件
-                Ok(contents.iter().any(|item| {
-                    item.content_type == "file" && item.name.to_uppercase() == "SKILL.MD"
-                }))
-            }
-            Err(e) => {
-                log::warn!("Failed to check directory {}: {}", path, e);
-                Ok(false)
+    /// 下载文件内容，并在调用方提供了期望的 SHA-256 摘要时校验下载内容是否
+    /// 匹配，防止下载过程中内容被篡改（中间人、被污染的缓存等）。摘要不匹配
+    /// 时返回一个可与正常下载失败区分开的错误，调用方应据此拒绝安装
+    pub async fn download_file_verified(
+        &self,
+        download_url: &str,
+        expected_sha256: Option<&str>,
+    ) -> Result<Vec<u8>> {
+        let content = self.download_file(download_url).await?;
+
+        if let Some(expected) = expected_sha256 {
+            let actual = sha256_hex(&content);
+            if !actual.eq_ignore_ascii_case(expected) {
+                anyhow::bail!(
+                    "Checksum mismatch for {}: expected {}, got {}",
+                    download_url,
+                    expected,
+                    actual
+                );
             }
         }
+
+        Ok(content)
     }
 
-    /// 下载并解析 SKILL.md 的 frontmatter
-    pub async fn fetch_skill_metadata(&self, owner: &str, repo: &str, skill_path: &str) -> Result<(String, Option<String>)> {
-        // 构建 SKILL.md 的下载 URL
-        let download_url = format!(
-            "https://raw.githubusercontent.com/{}/{}/main/{}/SKILL.md",
-            owner, repo, skill_path
-        );
+    /// 下载并解析 SKILL.md 的 frontmatter。`git_ref` 为 `None` 时解析仓库的实际默认分支
+    pub async fn fetch_skill_metadata(
+        &self,
+        owner: &str,
+        repo: &str,
+        skill_path: &str,
+        git_ref: Option<&str>,
+    ) -> Result<(String, Option<String>)> {
+        let git_ref = self.resolve_ref(owner, repo, git_ref).await?;
+        let download_url = self.raw_file_url(owner, repo, &git_ref, &format!("{}/SKILL.md", skill_path));
 
         log::info!("Fetching SKILL.md from: {}", download_url);
 
-        // 下载文件内容
         let content = self.download_file(&download_url).await?;
-        let content_str = String::from_utf8(content)
-            .context("Failed to decode SKILL.md as UTF-8")?;
+        let content_str =
+            String::from_utf8(content).context("Failed to decode SKILL.md as UTF-8")?;
 
-        // 解析 frontmatter
         self.parse_skill_frontmatter(&content_str)
     }
 
+    /// 列出一个 skill 目录下的所有文件（SKILL.md 及其附带的脚本/资源）。
+    /// `git_ref` 作为 Contents API 的 `?ref=` 查询参数传入，为 `None` 时按仓库默认分支列出
+    pub async fn list_skill_files(
+        &self,
+        owner: &str,
+        repo: &str,
+        skill_path: &str,
+        git_ref: Option<&str>,
+    ) -> Result<Vec<GitHubContent>> {
+        let content = self
+            .with_backoff(|| {
+                let mut request = self.client
+                    .repos(owner, repo)
+                    .get_content()
+                    .path(skill_path);
+                if let Some(git_ref) = git_ref {
+                    request = request.r#ref(git_ref);
+                }
+                request.send()
+            })
+            .await
+            .context("Failed to list skill directory contents")?;
+
+        Ok(content
+            .items
+            .into_iter()
+            .map(|item| GitHubContent {
+                name: item.name,
+                path: item.path,
+                content_type: item.r#type,
+                download_url: item.download_url,
+            })
+            .collect())
+    }
+
     /// 解析 SKILL.md 的 frontmatter
     fn parse_skill_frontmatter(&self, content: &str) -> Result<(String, Option<String>)> {
-        // 查找 frontmatter 的边界（--- ... ---）
         let lines: Vec<&str> = content.lines().collect();
 
         if lines.is_empty() || lines[0] != "---" {
             anyhow::bail!("Invalid SKILL.md format: missing frontmatter");
         }
 
-        // 找到第二个 "---"
-        let end_index = lines.iter()
+        let end_index = lines
+            .iter()
             .skip(1)
             .position(|&line| line == "---")
             .context("Invalid SKILL.md format: frontmatter not closed")?;
 
-        // 提取 frontmatter 内容（跳过第一个 "---"）
         let frontmatter_lines = &lines[1..=end_index];
         let frontmatter_str = frontmatter_lines.join("\n");
 
-        // 解析 YAML
         let frontmatter: SkillFrontmatter = serde_yaml::from_str(&frontmatter_str)
             .context("Failed to parse SKILL.md frontmatter as YAML")?;
 
         Ok((frontmatter.name, frontmatter.description))
     }
+
+    /// 对命中 403/429（速率限制）的请求按 GitHub 建议的窗口退避重试。
+    /// 每次尝试前先检查是否还有配额（`X-RateLimit-Remaining` 为 0 就提前等到
+    /// `X-RateLimit-Reset`），命中限流错误后同样查询 reset 时间来决定睡多久，
+    /// 只有在 `/rate_limit` 查询本身失败时才退化为指数退避；响应里的
+    /// `Retry-After` 头不会被读取。
+    async fn with_backoff<F, Fut, T>(&self, mut make_request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, octocrab::Error>>,
+    {
+        let mut attempt = 0;
+        loop {
+            self.wait_for_quota().await;
+
+            match make_request().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < MAX_RATE_LIMIT_RETRIES && is_rate_limited(&e) => {
+                    attempt += 1;
+                    let delay = self.rate_limit_reset_delay().await.unwrap_or_else(|| {
+                        Duration::from_secs(2u64.pow(attempt)).min(MAX_BACKOFF)
+                    }) + jitter();
+                    log::warn!(
+                        "GitHub API rate limited (attempt {}/{}), retrying in {:?}",
+                        attempt,
+                        MAX_RATE_LIMIT_RETRIES,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e).context("GitHub API request failed"),
+            }
+        }
+    }
+
+    /// 在发起请求前主动查一次 `/rate_limit`（不计入限额）：如果核心配额已经
+    /// 耗尽，直接睡到 `X-RateLimit-Reset` 而不是先发一个注定 403 的请求
+    async fn wait_for_quota(&self) {
+        let Some(delay) = self.rate_limit_reset_delay_if_exhausted().await else {
+            return;
+        };
+
+        log::warn!("GitHub API quota exhausted, waiting {:?} for reset", delay);
+        tokio::time::sleep(delay + jitter()).await;
+    }
+
+    async fn rate_limit_reset_delay_if_exhausted(&self) -> Option<Duration> {
+        let response: RateLimitResponse = self
+            .client
+            .get("/rate_limit", None::<&()>)
+            .await
+            .ok()?;
+
+        if response.resources.core.remaining > 0 {
+            return None;
+        }
+
+        Some(seconds_until(response.resources.core.reset))
+    }
+
+    /// 查询当前 `X-RateLimit-Reset`，返回距离重置还需要等待的时长
+    async fn rate_limit_reset_delay(&self) -> Option<Duration> {
+        let response: RateLimitResponse = self
+            .client
+            .get("/rate_limit", None::<&()>)
+            .await
+            .ok()?;
+
+        Some(seconds_until(response.resources.core.reset))
+    }
+}
+
+/// `reset` 是 unix 时间戳；返回距离该时刻还剩多久（已过期则为 0），
+/// 并以 [`MAX_BACKOFF`] 封顶，避免一次等待挂起太久
+fn seconds_until(reset: i64) -> Duration {
+    let now = chrono::Utc::now().timestamp();
+    let remaining = (reset - now).max(0) as u64;
+    Duration::from_secs(remaining).min(MAX_BACKOFF)
+}
+
+fn is_rate_limited(error: &octocrab::Error) -> bool {
+    match error {
+        octocrab::Error::GitHub { source, .. } => {
+            let status = source.status_code.as_u16();
+            status == 403 || status == 429
+        }
+        _ => false,
+    }
+}
+
+fn jitter() -> Duration {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_millis())
+        .unwrap_or(0);
+    Duration::from_millis((nanos % 250) as u64)
+}
+
+/// 从一棵（未截断的）git tree 响应中在内存里找出所有 skill 目录：
+/// 含有一个路径为 `<dir>/SKILL.md`（大小写不敏感）的 blob 条目即视为 skill
+fn skill_dirs_from_tree(tree: &GitTreeResponse) -> Vec<String> {
+    tree.tree
+        .iter()
+        .filter(|entry| {
+            entry.entry_type == "blob"
+                && entry
+                    .path
+                    .rsplit('/')
+                    .next()
+                    .map(|name| name.eq_ignore_ascii_case("SKILL.md"))
+                    .unwrap_or(false)
+        })
+        .map(|entry| {
+            entry
+                .path
+                .rsplit_once('/')
+                .map(|(dir, _)| dir)
+                .unwrap_or("")
+                .to_string()
+        })
+        .filter(|dir| dir.split('/').filter(|s| !s.is_empty()).count() <= MAX_SCAN_DEPTH)
+        .collect()
+}
+
+/// 下载内容的 SHA-256，十六进制小写表示
+fn sha256_hex(content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+fn skill_name_from_path(path: &str) -> String {
+    path.rsplit('/')
+        .next()
+        .filter(|s| !s.is_empty())
+        .unwrap_or(path)
+        .to_string()
 }
 
 impl Default for GitHubService {
@@ -1,7 +1,19 @@
 pub mod github;
 pub mod skill_manager;
 pub mod database;
+pub mod plugin_backend;
+pub mod claude_backend;
+pub mod plugin_manager;
+pub mod plugin_source;
+pub mod claude_cli;
+pub mod operation_log;
+pub mod plugin_store;
+pub mod featured_marketplace_watcher;
 
 pub use github::GitHubService;
 pub use skill_manager::SkillManager;
 pub use database::Database;
+pub use plugin_manager::PluginManager;
+pub use operation_log::OperationLogger;
+pub use plugin_store::PluginStore;
+pub use featured_marketplace_watcher::spawn_featured_marketplaces_watcher;
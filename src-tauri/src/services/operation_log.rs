@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// 进程内单调递增的操作序号，拼进日志 id 里保证同一毫秒内并发发起的多个
+/// 操作（例如批量卸载时 rayon 线程池里的多个线程）也不会撞上同一个日志
+/// 文件名——仅靠毫秒级时间戳在 `BULK_PLUGIN_OP_PARALLELISM` 个线程近乎同时
+/// 调用 `start` 时是可以撞车的
+static OPERATION_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// 为 plugin/marketplace 相关的 Claude CLI 操作（安装/卸载/更新/移除）生成
+/// 按时间戳命名的日志文件，记录完整命令行、流式 stdout/stderr 与最终退出状态，
+/// 失败时调用方可以把日志路径拼进错误信息，供 UI 引导用户查看。
+pub struct OperationLogger {
+    log_dir: PathBuf,
+}
+
+impl OperationLogger {
+    pub fn new(log_dir: PathBuf) -> Self {
+        Self { log_dir }
+    }
+
+    /// 开始记录一次操作，返回绑定了日志文件的句柄
+    pub fn start(&self, operation: &str) -> Result<LoggedOperation> {
+        fs::create_dir_all(&self.log_dir)
+            .with_context(|| format!("无法创建操作日志目录: {:?}", self.log_dir))?;
+
+        let sequence = OPERATION_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+        let id = format!(
+            "{}_{}_{:06}",
+            sanitize_operation_name(operation),
+            Utc::now().format("%Y%m%dT%H%M%S%.3fZ"),
+            sequence,
+        );
+        let path = self.log_dir.join(format!("{}.log", id));
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("无法创建操作日志文件: {:?}", path))?;
+
+        Ok(LoggedOperation {
+            id,
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// 按 operation_id 读取日志内容，供 `get_operation_log` 命令使用
+    pub fn read_log(&self, operation_id: &str) -> Result<String> {
+        let path = self.log_dir.join(format!("{}.log", operation_id));
+        fs::read_to_string(&path)
+            .with_context(|| format!("无法读取操作日志文件: {:?}", path))
+    }
+}
+
+/// 文件名中不允许出现路径分隔符等字符，统一替换为下划线
+fn sanitize_operation_name(operation: &str) -> String {
+    operation
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect()
+}
+
+/// 绑定了日志文件的单次操作句柄
+pub struct LoggedOperation {
+    id: String,
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl LoggedOperation {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 记录即将执行的完整命令行
+    pub fn log_command(&self, command_line: &str) {
+        self.write_line(&format!("$ {}", command_line));
+    }
+
+    /// 追加一段流式捕获到的 stdout/stderr（PTY 合并了两者，与 CLI 实际输出一致）
+    pub fn log_output(&self, chunk: &str) {
+        if chunk.is_empty() {
+            return;
+        }
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_all(chunk.as_bytes());
+        }
+    }
+
+    /// 记录一条命令最终的退出状态（已归一化，见 [`super::claude_cli`]）
+    pub fn log_exit_status(&self, summary: &str) {
+        self.write_line(&format!("[exit] {}", summary));
+    }
+
+    /// 组装「失败原因 + 日志路径」风格的错误信息，供调用方 `anyhow::bail!`/`anyhow!` 使用
+    pub fn fail_message(&self, reason: &str) -> String {
+        format!("{}\n完整日志: {}", reason, self.path.display())
+    }
+
+    fn write_line(&self, line: &str) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn tempdir(label: &str) -> PathBuf {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!(
+            "agent-skills-guard-op-log-test-{}-{}-{}",
+            std::process::id(),
+            label,
+            OPERATION_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+        ));
+        dir
+    }
+
+    #[test]
+    fn concurrent_starts_for_the_same_operation_name_get_distinct_log_files() {
+        // 这复现了批量安装/卸载时 rayon 线程池并发调用 `start` 的场景：
+        // 多个线程几乎同时为同一个 operation 名字各自开一个日志，必须落到
+        // 不同的文件，否则两次操作的输出会互相覆盖/交织
+        let logger = Arc::new(OperationLogger::new(tempdir("concurrent")));
+
+        let handles: Vec<_> = (0..16)
+            .map(|_| {
+                let logger = Arc::clone(&logger);
+                std::thread::spawn(move || logger.start("uninstall_plugin").unwrap().id().to_string())
+            })
+            .collect();
+
+        let ids: Vec<String> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        let mut unique = ids.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), ids.len(), "expected every concurrent start() call to get a unique id, got {:?}", ids);
+    }
+
+    #[test]
+    fn log_output_from_one_operation_does_not_leak_into_another() {
+        let logger = OperationLogger::new(tempdir("isolated"));
+
+        let a = logger.start("install_plugin").unwrap();
+        let b = logger.start("install_plugin").unwrap();
+        a.log_output("plugin-a output\n");
+        b.log_output("plugin-b output\n");
+
+        let log_a = logger.read_log(a.id()).unwrap();
+        let log_b = logger.read_log(b.id()).unwrap();
+
+        assert!(log_a.contains("plugin-a output"));
+        assert!(!log_a.contains("plugin-b output"));
+        assert!(log_b.contains("plugin-b output"));
+        assert!(!log_b.contains("plugin-a output"));
+    }
+}
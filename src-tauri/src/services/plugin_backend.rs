@@ -0,0 +1,144 @@
+use crate::models::Plugin;
+use crate::services::claude_backend::ClaudeBackend;
+use crate::services::operation_log::OperationLogger;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Outcome of a single backend-driven action (marketplace add, plugin install/remove/update),
+/// independent of which CLI or mechanism actually performed it.
+#[derive(Debug, Clone)]
+pub struct BackendActionOutcome {
+    pub success: bool,
+    pub already: bool,
+    /// 面向 UI/DB 的状态字符串，例如 `"installed"` / `"already_installed"` / `"failed"`
+    pub status: String,
+    pub raw_log: String,
+    pub output: String,
+}
+
+/// 某个 backend 视角下的一个已安装条目
+#[derive(Debug, Clone)]
+pub struct BackendInstalledPlugin {
+    /// 该 plugin 在 backend 内部的唯一标识（Claude 下即 `name@marketplace`）
+    pub backend_plugin_id: String,
+    pub name: String,
+    pub marketplace_name: String,
+    pub marketplace_repository_url: Option<String>,
+    pub version: Option<String>,
+    pub scope: Option<String>,
+    pub enabled: Option<bool>,
+    pub install_path: Option<String>,
+    pub installed_at: Option<DateTime<Utc>>,
+    pub last_updated: Option<DateTime<Utc>>,
+}
+
+/// 某个 backend 视角下的一个可安装/可更新条目
+#[derive(Debug, Clone)]
+pub struct BackendAvailablePlugin {
+    pub backend_plugin_id: String,
+    pub name: Option<String>,
+    pub marketplace_name: Option<String>,
+    pub version: Option<String>,
+}
+
+/// 插件安装/卸载/更新/列举的扩展点，使 `PluginManager` 不再直接硬编码
+/// Claude Code CLI：未来可以新增一个不同的 CLI，或一个纯文件系统的 skills
+/// backend，只要实现这个 trait 即可接入现有的安装状态机和安全扫描流程
+/// （`SecurityScanner::scan_directory_with_options` 本身只需要一个安装路径，
+/// 与具体 backend 无关）。
+pub trait PluginBackend: Send + Sync {
+    /// 存入 `Plugin::backend`、供 [`BackendRegistry::resolve`] 查找的标识
+    fn id(&self) -> &'static str;
+
+    /// 确认该 backend 的 CLI/工具在当前机器上可用
+    fn prepare(&self, cli_command: &str) -> Result<()>;
+
+    /// 执行一次完整安装：添加 marketplace（如适用）+ 安装指定 plugin，
+    /// 返回两步各自的结果
+    fn install(
+        &self,
+        cli_command: &str,
+        plugin: &Plugin,
+        marketplace_repo: &str,
+        operation_logger: &OperationLogger,
+        on_output: Option<&mut dyn FnMut(&str)>,
+    ) -> Result<(BackendActionOutcome, BackendActionOutcome)>;
+
+    fn remove(
+        &self,
+        cli_command: &str,
+        plugin: &Plugin,
+        operation_logger: &OperationLogger,
+    ) -> Result<BackendActionOutcome>;
+
+    fn update(
+        &self,
+        cli_command: &str,
+        plugin: &Plugin,
+        operation_logger: &OperationLogger,
+    ) -> Result<BackendActionOutcome>;
+
+    /// 移除一个 marketplace（不负责卸载其下的 plugins，那由调用方先逐个走
+    /// [`Self::remove`]；这里只对应 CLI 的 `marketplace remove` 子命令本身）
+    fn marketplace_remove(
+        &self,
+        cli_command: &str,
+        marketplace_name: &str,
+        operation_logger: &OperationLogger,
+    ) -> Result<BackendActionOutcome>;
+
+    /// 列出该 backend 当前已安装的 plugins（用于同步本地状态）
+    fn list(&self, cli_command: &str) -> Result<Vec<BackendInstalledPlugin>>;
+
+    /// 列出该 backend 当前可安装/可更新的 plugins（用于检测更新）
+    fn check_updates(&self, cli_command: &str) -> Result<Vec<BackendAvailablePlugin>>;
+
+    /// 同步已安装状态；默认等价于 [`Self::list`]，因为对大多数 backend 来说
+    /// “查询当前已安装” 与 “同步到本地状态” 是同一次调用
+    fn sync_installed_state(&self, cli_command: &str) -> Result<Vec<BackendInstalledPlugin>> {
+        self.list(cli_command)
+    }
+}
+
+/// 按 `Plugin::backend` 字段选择具体 backend 实现（不根据 marketplace source 推断）
+pub struct BackendRegistry {
+    backends: HashMap<&'static str, Arc<dyn PluginBackend>>,
+    default_id: &'static str,
+}
+
+impl BackendRegistry {
+    pub fn new() -> Self {
+        let mut backends: HashMap<&'static str, Arc<dyn PluginBackend>> = HashMap::new();
+        let claude: Arc<dyn PluginBackend> = Arc::new(ClaudeBackend::new());
+        backends.insert(claude.id(), claude);
+
+        Self {
+            backends,
+            default_id: "claude",
+        }
+    }
+
+    /// 根据 `plugin.backend` 显式字段选择 backend；未设置时目前统一回退到
+    /// Claude Code CLI（这也是唯一的 marketplace source 今天支持的 backend）。
+    pub fn resolve(&self, plugin: &Plugin) -> Arc<dyn PluginBackend> {
+        let key = plugin.backend_id();
+        self.backends
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| self.default())
+    }
+
+    pub fn default(&self) -> Arc<dyn PluginBackend> {
+        self.backends
+            .get(self.default_id)
+            .cloned()
+            .expect("默认 backend 必须已注册")
+    }
+
+    /// 所有已注册的 backend，供全局性操作（如定期同步、检查更新）遍历
+    pub fn all(&self) -> impl Iterator<Item = &Arc<dyn PluginBackend>> {
+        self.backends.values()
+    }
+}
@@ -1,22 +1,54 @@
 use crate::models::{
+    DiagnosticCheck,
+    DiagnosticStatus,
+    ExportedMarketplace,
+    ExportedPlugin,
     FeaturedMarketplacesConfig,
     FeaturedMarketplace,
     FeaturedMarketplaceOwner,
+    IssueCategory,
+    IssueSeverity,
     LocalizedText,
+    LockfileAuditReport,
+    LockfileDriftEntry,
+    MarketplaceReachability,
+    MarketplaceTrustConfig,
     Plugin,
+    PluginCapabilities,
+    PluginEnvironmentReport,
+    PluginScanReport,
+    PluginStateImportItem,
+    PluginStateImportResult,
+    PluginStateManifest,
     Repository,
+    ResolvedPluginLockEntry,
+    ResolvedPluginLockfile,
+    SecurityIssue,
     SecurityLevel,
     SecurityReport,
+    SignatureVerification,
     Skill,
+    UpdateStatus,
 };
 use crate::i18n::validate_locale;
-use crate::security::{ScanOptions, SecurityScanner};
-use crate::services::claude_cli::{ClaudeCli, ClaudeCommand};
-use crate::services::{Database, GitHubService};
+use crate::security::{ScanOptions, ScannerBackend, SecurityScanner};
+use crate::services::claude_backend::{
+    marketplace_repo_url, parse_claude_plugin_id, parse_claude_plugin_list_with_available,
+    parse_json_command_result, parse_json_output, parse_marketplace_add_output,
+    parse_marketplace_list_text, parse_marketplace_update_output, ClaudeAvailablePluginEntry,
+    ClaudeInstalledPluginEntry, ClaudeMarketplaceListEntry, CommandResult,
+};
+use crate::services::claude_cli::{ClaudeCli, ClaudeCliResult, ClaudeCommand};
+use crate::services::plugin_backend::BackendRegistry;
+use crate::services::plugin_source::{PluginSourceRegistry, PluginSourceRequest};
+use crate::services::{Database, GitHubService, OperationLogger};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 use std::path::{Component, Path, PathBuf};
@@ -40,6 +72,9 @@ struct MarketplacePluginEntry {
     version: Option<String>,
     source: String,
     author: Option<AuthorField>,
+    /// 依赖名 → semver 版本要求（如 `"^1.2"`），见 [`Plugin::dependencies`]
+    #[serde(default)]
+    dependencies: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -48,6 +83,42 @@ struct PluginManifest {
     description: Option<String>,
     version: Option<String>,
     author: Option<AuthorField>,
+    /// 依赖名 → semver 版本要求（如 `"^1.2"`），见 [`Plugin::dependencies`]
+    #[serde(default)]
+    dependencies: Option<HashMap<String, String>>,
+    /// 生命周期钩子：阶段名（如 `preinstall`/`postinstall`/`preuninstall`/
+    /// `postuninstall`）→ 相对于插件目录的脚本路径，路径同样要经过
+    /// [`resolve_source_path`] 的 `../` 转义校验。见
+    /// [`scan_lifecycle_hook_issues`] 与 [`PluginManager::run_plugin_lifecycle_hook`]
+    #[serde(default)]
+    hooks: Option<HashMap<String, String>>,
+    /// 插件声明会用到哪些能力（文件系统路径/联网 host/shell 执行），解析后存入
+    /// [`Plugin::capabilities`]，供安装前审查和扫描器做「声明外能力」比对，
+    /// 见 [`PluginCapabilities::undeclared`]
+    #[serde(default)]
+    permissions: Option<PluginPermissionsManifest>,
+}
+
+/// `plugin.json` 里 `permissions` 字段的原始形状
+#[derive(Debug, Clone, Deserialize, Default)]
+struct PluginPermissionsManifest {
+    #[serde(default)]
+    filesystem: Vec<String>,
+    #[serde(default)]
+    network: Vec<String>,
+    #[serde(default)]
+    shell: bool,
+}
+
+impl From<PluginPermissionsManifest> for PluginCapabilities {
+    fn from(permissions: PluginPermissionsManifest) -> Self {
+        PluginCapabilities {
+            filesystem_paths: permissions.filesystem,
+            network_hosts: permissions.network,
+            shell_execution: permissions.shell,
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -72,9 +143,9 @@ impl AuthorField {
 }
 
 #[derive(Debug)]
-struct ResolvedPlugin {
-    plugin: Plugin,
-    source_path: PathBuf,
+pub(crate) struct ResolvedPlugin {
+    pub(crate) plugin: Plugin,
+    pub(crate) source_path: PathBuf,
 }
 
 #[derive(Debug, Serialize)]
@@ -120,6 +191,42 @@ pub struct ClaudeMarketplace {
     pub install_location: Option<String>,
 }
 
+/// 一次版本比较判定出的升级幅度；双方有任意一侧不是合法 semver 时退化为
+/// `Unknown`（只知道两者不同，不知道方向/幅度），供 UI 区分"可以放心升级"
+/// 与"升级前需要确认"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionBump {
+    Major,
+    Minor,
+    Patch,
+    Unknown,
+}
+
+/// 比较 `installed` 与 `latest`：两侧都能解析为 semver 时，只在 `latest > installed`
+/// 时返回 `Some(bump)`（跳过相同版本与降级，区分 major/minor/patch 幅度）；
+/// 允许裸版本号前缀一个 `v`（`v1.2.0` 等价于 `1.2.0`）。任意一侧解析失败（非
+/// semver 插件）时退回原有的字符串不等比较，返回 `Some(Unknown)`
+fn semver_update_bump(installed: &str, latest: &str) -> Option<VersionBump> {
+    let strip_v = |s: &str| s.trim().strip_prefix('v').unwrap_or(s.trim()).to_string();
+
+    match (Version::parse(&strip_v(installed)), Version::parse(&strip_v(latest))) {
+        (Ok(installed_ver), Ok(latest_ver)) => {
+            if latest_ver <= installed_ver {
+                return None;
+            }
+            Some(if latest_ver.major != installed_ver.major {
+                VersionBump::Major
+            } else if latest_ver.minor != installed_ver.minor {
+                VersionBump::Minor
+            } else {
+                VersionBump::Patch
+            })
+        }
+        _ => (installed != latest).then_some(VersionBump::Unknown),
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct PluginUpdateResult {
     pub plugin_id: String,
@@ -128,6 +235,15 @@ pub struct PluginUpdateResult {
     pub raw_log: String,
 }
 
+/// [`PluginManager::run_plugin_lifecycle_hook`] 的执行结果
+#[derive(Debug, Serialize)]
+pub struct PluginLifecycleHookResult {
+    pub plugin_id: String,
+    pub phase: String,
+    pub status: String,
+    pub raw_log: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct MarketplaceUpdateResult {
     pub marketplace_name: String,
@@ -148,61 +264,154 @@ pub struct SkillPluginUpgradeCandidate {
     pub reason: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct ClaudeMarketplaceListEntry {
-    name: String,
-    #[allow(dead_code)]
-    source: Option<String>,
-    repo: Option<String>,
-    #[serde(rename = "installLocation", alias = "install_location")]
-    install_location: Option<String>,
-}
-
-#[derive(Debug, Deserialize, Clone)]
-struct ClaudeInstalledPluginEntry {
-    id: String,
-    version: Option<String>,
-    scope: Option<String>,
-    enabled: Option<bool>,
-    #[serde(rename = "installPath", alias = "install_path")]
-    install_path: Option<String>,
-    #[serde(rename = "installedAt", alias = "installed_at")]
-    installed_at: Option<String>,
-    #[serde(rename = "lastUpdated", alias = "last_updated")]
-    last_updated: Option<String>,
-}
+/// 同一版本的更新提醒在此窗口内只发送一次，避免每次后台检查都重复打扰用户
+const UPDATE_NOTIFICATION_SUPPRESS_WINDOW: chrono::Duration = chrono::Duration::days(14);
 
-#[derive(Debug, Deserialize)]
-struct ClaudeAvailablePluginEntry {
-    #[serde(rename = "pluginId", alias = "plugin_id")]
-    plugin_id: String,
-    name: Option<String>,
-    #[serde(rename = "marketplaceName", alias = "marketplace_name", alias = "marketplace")]
-    marketplace_name: Option<String>,
-    version: Option<String>,
-}
+/// [`crate::security::guard::scan_plugin_tree`] 的 `risk_score` 低于此值时，
+/// 在跑 `SecurityScanner` 之前就直接拒绝安装——这是比完整扫描更快的第一道关卡
+const GUARD_MIN_RISK_SCORE: i32 = 40;
 
-#[derive(Debug, Deserialize)]
-struct ClaudePluginListWithAvailable {
-    #[serde(default, alias = "installedPlugins")]
-    installed: Vec<ClaudeInstalledPluginEntry>,
-    #[serde(default, alias = "availablePlugins")]
-    available: Vec<ClaudeAvailablePluginEntry>,
-}
+/// [`PluginManager::remove_marketplace`] 批量卸载 / [`PluginManager::import_state`]
+/// 批量安装时的默认并发度：每个 plugin 都要起一个带超时的 CLI 子进程，开太大
+/// 并没有实际收益（瓶颈是子进程本身的延迟而非本机资源），但完全串行在插件多的
+/// marketplace 上会很慢
+const BULK_PLUGIN_OP_PARALLELISM: usize = 4;
+const MAX_BULK_PLUGIN_OP_PARALLELISM: usize = 8;
 
 pub struct PluginManager {
     db: Arc<Database>,
     github: GitHubService,
     scanner: SecurityScanner,
+    /// 额外注册的 out-of-process 扫描器（见 [`ScannerBackend`]），默认为空，
+    /// 只跑内置的 `scanner`；其发现的问题会在安装前合并进同一份 [`SecurityReport`]
+    scanner_backends: Vec<Box<dyn ScannerBackend>>,
+    operation_logger: OperationLogger,
+    backends: BackendRegistry,
+    /// 按 plugin 的 `discovery_source` 调度到对应的 [`crate::services::plugin_source::PluginSourceBackend`]，
+    /// 决定插件源码从 marketplace 仓库、本地目录还是任意 git 仓库里来
+    source_backends: PluginSourceRegistry,
+    /// 按 marketplace 名称索引的受信任签名者列表，默认为空（所有 marketplace
+    /// 都不启用签名校验）；通过 [`Self::set_trust_config`] 按需加载
+    trust_config: std::sync::RwLock<MarketplaceTrustConfig>,
 }
 
 impl PluginManager {
-    pub fn new(db: Arc<Database>) -> Self {
+    pub fn new(db: Arc<Database>, operation_log_dir: PathBuf) -> Self {
         Self {
             db,
             github: GitHubService::new(),
             scanner: SecurityScanner::new(),
+            scanner_backends: Vec::new(),
+            operation_logger: OperationLogger::new(operation_log_dir),
+            backends: BackendRegistry::new(),
+            source_backends: PluginSourceRegistry::new(),
+            trust_config: std::sync::RwLock::new(MarketplaceTrustConfig::default()),
+        }
+    }
+
+    /// 加载一份 `trusted_signers` 配置（见 [`MarketplaceTrustConfig`]），替换
+    /// 当前生效的配置；未出现在配置里的 marketplace 视为未启用签名校验，
+    /// [`Self::confirm_plugin_installation`] 会照常放行而不是当作校验失败
+    pub fn set_trust_config(&self, config: MarketplaceTrustConfig) {
+        if let Ok(mut guard) = self.trust_config.write() {
+            *guard = config;
+        }
+    }
+
+    /// 清除已加载的签名校验配置，恢复为不对任何 marketplace 做签名校验
+    pub fn clear_trust_config(&self) {
+        if let Ok(mut guard) = self.trust_config.write() {
+            *guard = MarketplaceTrustConfig::default();
+        }
+    }
+
+    /// 若 `marketplace_name` 配置了受信任签名者，对 `repo_dir` 里的
+    /// `commit_sha` 跑一次 [`crate::security::signing::verify_commit_signature`]；
+    /// 未配置时返回 `None`——调用方应将其视为"未启用校验、照常放行"，而不是
+    /// 校验失败
+    fn verify_marketplace_signature(
+        &self,
+        marketplace_name: &str,
+        repo_dir: &Path,
+        commit_sha: &str,
+    ) -> Option<SignatureVerification> {
+        let config = self.trust_config.read().ok()?;
+        let signers = config.signers_for(marketplace_name)?;
+        match crate::security::signing::verify_commit_signature(repo_dir, commit_sha, signers) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                log::warn!("校验 marketplace {} 的提交签名失败: {}", marketplace_name, e);
+                Some(SignatureVerification {
+                    verified: false,
+                    signer: None,
+                    detail: e.to_string(),
+                })
+            }
+        }
+    }
+
+    /// 执行一组 Claude CLI 命令并把完整命令行、流式输出、最终退出状态写入
+    /// 按时间戳命名的操作日志文件；失败时把日志路径拼进错误信息，方便 UI
+    /// 引导用户直接打开对应日志而不是只看到一句扁平的错误文案。
+    ///
+    /// 仅 marketplace 相关操作（尚未纳入 [`crate::services::plugin_backend::PluginBackend`]）
+    /// 还直接使用这个方法；plugin 安装/卸载/更新已经改为经由 backend 调度。
+    fn run_logged(
+        &self,
+        operation: &str,
+        claude_cli: &ClaudeCli,
+        commands: &[ClaudeCommand],
+        on_chunk: Option<&mut dyn FnMut(&str)>,
+    ) -> Result<ClaudeCliResult> {
+        crate::services::claude_backend::run_logged(
+            &self.operation_logger,
+            operation,
+            claude_cli,
+            commands,
+            on_chunk,
+        )
+    }
+
+    /// 读取某次操作留下的日志内容，供 `get_operation_log` 命令使用
+    pub fn read_operation_log(&self, operation_id: &str) -> Result<String> {
+        self.operation_logger.read_log(operation_id)
+    }
+
+    /// 注册一个额外的 out-of-process 扫描器（见 [`ScannerBackend`]）。供调用方
+    /// （例如应用启动时按配置文件接入组织自有扫描器）在构造 `PluginManager`
+    /// 之后按需添加；默认没有任何 backend，只跑内置的 `SecurityScanner`。
+    pub fn add_scanner_backend(&mut self, backend: Box<dyn ScannerBackend>) {
+        self.scanner_backends.push(backend);
+    }
+
+    /// 依次跑完所有已注册的 [`ScannerBackend`]；单个 backend 出错只记一条
+    /// 警告并跳过，不影响其他 backend 或内置扫描器的结果。
+    fn run_scanner_backends(&self, source_path: &Path, plugin_id: &str, options: ScanOptions) -> Vec<(String, SecurityReport)> {
+        let mut reports = Vec::new();
+        for backend in &self.scanner_backends {
+            match backend.scan(source_path, plugin_id, options) {
+                Ok(report) => reports.push((backend.id().to_string(), report)),
+                Err(e) => log::warn!("外部扫描器 {} 执行失败，跳过: {}", backend.id(), e),
+            }
+        }
+        reports
+    }
+
+    /// 在跑 [`SecurityScanner`] 之前，对解压/克隆到本地的插件（或 marketplace
+    /// 仓库）目录先跑一遍 [`crate::security::guard::scan_plugin_tree`]；
+    /// `risk_score` 不达标（见 [`GUARD_MIN_RISK_SCORE`]）时直接拒绝安装，
+    /// 否则把报告透传给调用方落到对应 plugin 记录上
+    fn guard_scan_or_bail(&self, root: &Path) -> Result<PluginScanReport> {
+        let report = crate::security::guard::scan_plugin_tree(root)?;
+        if !report.passes_threshold(GUARD_MIN_RISK_SCORE) {
+            let mut error_msg = "安全检测发现严重威胁，已禁止安装。\n\n检测到以下高危操作：\n".to_string();
+            for (idx, finding) in report.findings.iter().enumerate() {
+                error_msg.push_str(&format!("{}. [{}] {}\n", idx + 1, finding.file_path, finding.description));
+            }
+            error_msg.push_str("\n这些操作可能对您的系统造成严重危害，强烈建议不要安装此插件。");
+            anyhow::bail!(error_msg);
         }
+        Ok(report)
     }
 
     pub fn scan_cached_repository_plugins(&self, cache_path: &Path, repo_url: &str) -> Result<Vec<Plugin>> {
@@ -292,141 +501,98 @@ impl PluginManager {
         Ok(plugins)
     }
 
-    /// 同步 Claude Code CLI 的本地安装状态（用于识别非本程序安装的 plugins/marketplaces）。
+    /// 同步各 [`crate::services::plugin_backend::PluginBackend`] 的本地安装状态
+    /// （用于识别非本程序安装的 plugins）。目前只注册了 Claude Code CLI 一个
+    /// backend，但遍历 `self.backends.all()` 可以让未来接入的其他 backend
+    /// 自动纳入同一条同步路径。
     ///
-    /// 原则：不直接读写 Claude 的缓存目录，仅通过 CLI `list --json` 获取状态并落库。
+    /// 原则：不直接读写 backend 的缓存目录，仅通过其 `sync_installed_state` 获取状态并落库。
     pub async fn sync_claude_installed_state(&self, claude_command: Option<String>) -> Result<()> {
         let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
-        if which(&cli_command).is_err() {
-            // 未安装 Claude CLI 时，跳过同步，保持 DB 原样
-            log::debug!("未找到 Claude Code CLI: {}，跳过 plugins 同步", cli_command);
-            return Ok(());
-        }
-
-        let claude_cli = ClaudeCli::new(cli_command);
-        let commands = vec![
-            ClaudeCommand {
-                args: vec![
-                    "plugin".to_string(),
-                    "marketplace".to_string(),
-                    "list".to_string(),
-                    "--json".to_string(),
-                ],
-                timeout: Duration::from_secs(15),
-            },
-            ClaudeCommand {
-                args: vec![
-                    "plugin".to_string(),
-                    "list".to_string(),
-                    "--json".to_string(),
-                ],
-                timeout: Duration::from_secs(15),
-            },
-        ];
 
-        let cli_result = claude_cli.run(&commands)?;
-        let marketplace_output = cli_result.outputs.get(0).map(|o| o.output.as_str()).unwrap_or_default();
-        let plugins_output = cli_result.outputs.get(1).map(|o| o.output.as_str()).unwrap_or_default();
+        let mut installed_backend_ids: HashSet<String> = HashSet::new();
 
-        let marketplaces: Vec<ClaudeMarketplaceListEntry> = match parse_json_output(marketplace_output) {
-            Ok(v) => v,
-            Err(e) => {
-                log::warn!(
-                    "解析 `claude plugin marketplace list --json` 失败，尝试解析文本输出: {}",
-                    e
-                );
-                parse_marketplace_list_text(marketplace_output)
-                    .into_iter()
-                    .map(|m| ClaudeMarketplaceListEntry {
-                        name: m.name,
-                        source: m.source,
-                        repo: m.repo,
-                        install_location: m.install_location,
-                    })
-                    .collect()
+        for backend in self.backends.all() {
+            if backend.prepare(&cli_command).is_err() {
+                // backend 所需的 CLI/工具不可用时，跳过同步，保持 DB 原样
+                log::debug!("backend {} 不可用，跳过 plugins 同步", backend.id());
+                continue;
             }
-        };
-        let installed_plugins: Vec<ClaudeInstalledPluginEntry> = parse_json_output(plugins_output)
-            .context("解析 `claude plugin list --json` 输出失败")?;
 
-        let mut marketplace_repo_url_by_name: HashMap<String, String> = HashMap::new();
-        for entry in marketplaces {
-            if let Some(repo_url) = marketplace_repo_url(&entry) {
-                marketplace_repo_url_by_name.insert(entry.name, repo_url);
-            }
-        }
+            let installed_plugins = backend.sync_installed_state(&cli_command)?;
 
-        let existing_plugins = self.db.get_plugins().unwrap_or_default();
-        let mut plugins_by_claude_id: HashMap<String, Plugin> = HashMap::new();
-        for plugin in existing_plugins {
-            if let Some(claude_id) = plugin.claude_id.clone() {
-                plugins_by_claude_id.insert(claude_id, plugin);
+            let existing_plugins = self.db.get_plugins().unwrap_or_default();
+            let mut plugins_by_backend_id: HashMap<String, Plugin> = HashMap::new();
+            for plugin in existing_plugins {
+                if let Some(claude_id) = plugin.claude_id.clone() {
+                    plugins_by_backend_id.insert(claude_id, plugin);
+                }
             }
-        }
-
-        let mut installed_claude_ids: HashSet<String> = HashSet::new();
 
-        for entry in installed_plugins {
-            installed_claude_ids.insert(entry.id.clone());
-
-            let (plugin_name, marketplace_name) = match parse_claude_plugin_id(&entry.id) {
-                Some(v) => v,
-                None => {
-                    log::warn!("无法解析 Claude plugin id: {}", entry.id);
-                    continue;
+            for entry in installed_plugins {
+                installed_backend_ids.insert(entry.backend_plugin_id.clone());
+
+                let repository_url = entry
+                    .marketplace_repository_url
+                    .clone()
+                    .unwrap_or_else(|| "local".to_string());
+
+                let mut plugin = plugins_by_backend_id
+                    .get(&entry.backend_plugin_id)
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        let mut p = Plugin::new(
+                            entry.name.clone(),
+                            repository_url.clone(),
+                            entry.marketplace_name.clone(),
+                            "external".to_string(),
+                        );
+                        p.discovery_source = Some("claude_cli".to_string());
+                        p.backend = Some(backend.id().to_string());
+                        p
+                    });
+
+                plugin.claude_id = Some(entry.backend_plugin_id.clone());
+                plugin.installed = true;
+                plugin.installed_version = entry.version.clone();
+                plugin.claude_scope = entry.scope.clone();
+                plugin.claude_enabled = entry.enabled;
+                plugin.claude_install_path = entry.install_path.clone();
+                plugin.claude_last_updated = entry.last_updated;
+                if plugin.installed_at.is_none() {
+                    plugin.installed_at = entry.installed_at;
                 }
-            };
-
-            let repository_url = marketplace_repo_url_by_name
-                .get(&marketplace_name)
-                .cloned()
-                .unwrap_or_else(|| "local".to_string());
-
-            let mut plugin = plugins_by_claude_id
-                .get(&entry.id)
-                .cloned()
-                .unwrap_or_else(|| {
-                    let mut p = Plugin::new(
-                        plugin_name.clone(),
-                        repository_url.clone(),
-                        marketplace_name.clone(),
-                        "external".to_string(),
-                    );
-                    p.discovery_source = Some("claude_cli".to_string());
-                    p
-                });
 
-            plugin.claude_id = Some(entry.id.clone());
-            plugin.installed = true;
-            plugin.installed_version = entry.version.clone();
-            plugin.claude_scope = entry.scope.clone();
-            plugin.claude_enabled = entry.enabled;
-            plugin.claude_install_path = entry.install_path.clone();
-            plugin.claude_last_updated = parse_datetime(&entry.last_updated);
-            if plugin.installed_at.is_none() {
-                plugin.installed_at = parse_datetime(&entry.installed_at);
-            }
+                // 让 UI 可以展示 marketplace 归属（外部安装时 DB 里没有可用清单）
+                if plugin.marketplace_name.is_empty() {
+                    plugin.marketplace_name = entry.marketplace_name.clone();
+                }
+                if plugin.name.is_empty() {
+                    plugin.name = entry.name.clone();
+                }
 
-            // 让 UI 可以展示 marketplace 归属（外部安装时 DB 里没有可用清单）
-            if plugin.marketplace_name.is_empty() {
-                plugin.marketplace_name = marketplace_name.clone();
-            }
-            if plugin.name.is_empty() {
-                plugin.name = plugin_name.clone();
+                self.db.save_plugin(&plugin)?;
             }
-
-            self.db.save_plugin(&plugin)?;
         }
 
-        // 反向同步：DB 标记为 installed 但 CLI 已不存在 -> 标记为未安装
+        // 反向同步：DB 标记为 installed 但所有 backend 都已不再报告它 -> 标记为未安装
         let current_plugins = self.db.get_plugins().unwrap_or_default();
-        for plugin in current_plugins {
+        for plugin in &current_plugins {
             let claude_id = match plugin.claude_id.as_deref() {
                 Some(v) => v,
                 None => continue,
             };
 
-            if plugin.installed && !installed_claude_ids.contains(claude_id) {
+            if plugin.installed && !installed_backend_ids.contains(claude_id) {
+                let dependents = installed_dependents(plugin, &current_plugins, None);
+                if !dependents.is_empty() {
+                    let names = dependents.iter().map(|p| p.name.as_str()).collect::<Vec<_>>().join(", ");
+                    log::warn!(
+                        "插件 {} 被外部卸载，但以下已安装插件仍依赖它，可能已被破坏: {}",
+                        plugin.name, names
+                    );
+                }
+
                 let mut updated = plugin.clone();
                 updated.installed = false;
                 updated.installed_at = None;
@@ -492,71 +658,52 @@ impl PluginManager {
             .collect())
     }
 
-    /// 检查已安装 plugins 的更新：返回 Vec<(plugin_db_id, latest_version)>
-    pub async fn check_plugins_updates(&self, claude_command: Option<String>) -> Result<Vec<(String, String)>> {
+    /// 检查已安装 plugins 的更新：返回 Vec<(plugin_db_id, latest_version, bump)>。
+    /// `bump` 按 semver 比较得出（见 [`semver_update_bump`]），非 semver 插件
+    /// 退回字符串不等比较，幅度标记为 `Unknown`
+    pub async fn check_plugins_updates(&self, claude_command: Option<String>) -> Result<Vec<(String, String, VersionBump)>> {
         self.sync_claude_installed_state(claude_command.clone()).await?;
 
         let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
-        if which(&cli_command).is_err() {
-            return Ok(Vec::new());
-        }
-
-        let claude_cli = ClaudeCli::new(cli_command);
-        let commands = vec![ClaudeCommand {
-            args: vec![
-                "plugin".to_string(),
-                "list".to_string(),
-                "--json".to_string(),
-                "--available".to_string(),
-            ],
-            timeout: Duration::from_secs(30),
-        }];
-
-        let cli_result = claude_cli.run(&commands)?;
-        let output = cli_result.outputs.first().map(|o| o.output.as_str()).unwrap_or_default();
-        let payload = parse_claude_plugin_list_with_available(output)
-            .context("解析 `claude plugin list --json --available` 输出失败")?;
-
-        let mut available_versions: HashMap<String, String> = HashMap::new();
-        for entry in payload.available {
-            if let Some(version) = entry.version {
-                if !version.trim().is_empty() {
-                    available_versions.insert(entry.plugin_id, version);
-                }
-            }
-        }
 
         let current_plugins = self.db.get_plugins().unwrap_or_default();
         let mut plugins_by_claude_id: HashMap<String, Plugin> = HashMap::new();
-        for plugin in current_plugins {
+        for plugin in &current_plugins {
             if let Some(claude_id) = plugin.claude_id.clone() {
-                plugins_by_claude_id.insert(claude_id, plugin);
+                plugins_by_claude_id.insert(claude_id, plugin.clone());
             }
         }
 
         let mut updates = Vec::new();
-        for installed in payload.installed {
-            let installed_version = installed.version.unwrap_or_default();
-            let latest = match available_versions.get(&installed.id) {
-                Some(v) => v,
-                None => continue,
-            };
-
-            if latest.trim().is_empty() || installed_version.trim().is_empty() {
+        for backend in self.backends.all() {
+            if backend.prepare(&cli_command).is_err() {
                 continue;
             }
 
-            if latest != &installed_version {
-                if let Some(plugin) = plugins_by_claude_id.get(&installed.id) {
-                    updates.push((plugin.id.clone(), latest.clone()));
+            let available = backend.check_updates(&cli_command)?;
+            for entry in available {
+                let Some(version) = entry.version.filter(|v| !v.trim().is_empty()) else {
+                    continue;
+                };
+                let Some(plugin) = plugins_by_claude_id.get(&entry.backend_plugin_id) else {
+                    continue;
+                };
+                let installed_version = plugin.installed_version.clone().unwrap_or_default();
+                if installed_version.trim().is_empty() {
+                    continue;
                 }
+                let Some(bump) = semver_update_bump(&installed_version, &version) else {
+                    continue;
+                };
+
+                updates.push((plugin.id.clone(), version, bump));
             }
         }
 
         Ok(updates)
     }
 
-    /// 更新单个 plugin（调用 Claude Code CLI），并写回日志/状态
+    /// 更新单个 plugin，并写回日志/状态
     pub async fn update_plugin(&self, plugin_id: &str, claude_command: Option<String>) -> Result<PluginUpdateResult> {
         let plugin = self
             .db
@@ -570,35 +717,15 @@ impl PluginManager {
         }
 
         let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
-        if which(&cli_command).is_err() {
-            anyhow::bail!("未找到 Claude Code CLI: {}", cli_command);
-        }
-
-        let scope = plugin.claude_scope.clone().unwrap_or_else(|| "user".to_string());
-        let plugin_spec = plugin
-            .claude_id
-            .clone()
-            .unwrap_or_else(|| plugin.plugin_spec());
-
-        let claude_cli = ClaudeCli::new(cli_command);
-        let commands = vec![ClaudeCommand {
-            args: vec![
-                "plugin".to_string(),
-                "update".to_string(),
-                "--scope".to_string(),
-                scope,
-                plugin_spec,
-            ],
-            timeout: Duration::from_secs(180),
-        }];
+        let backend = self.backends.resolve(&plugin);
+        backend.prepare(&cli_command)?;
 
-        let cli_result = claude_cli.run(&commands)?;
-        let output = cli_result.outputs.first().map(|o| o.output.clone()).unwrap_or_default();
-        let status = parse_plugin_update_output(&output);
+        let outcome = backend.update(&cli_command, &plugin, &self.operation_logger)?;
+        let status = outcome.status;
 
         // 写回日志与状态；并再次同步以获取最新 installed_version 等字段
         let mut updated = plugin.clone();
-        updated.install_log = Some(cli_result.raw_log.clone());
+        updated.install_log = Some(outcome.raw_log.clone());
         updated.install_status = Some(status.clone());
         self.db.save_plugin(&updated)?;
 
@@ -608,7 +735,7 @@ impl PluginManager {
             plugin_id: updated.id,
             plugin_name: updated.name,
             status,
-            raw_log: cli_result.raw_log,
+            raw_log: outcome.raw_log,
         })
     }
 
@@ -658,6 +785,54 @@ impl PluginManager {
         Ok(updates)
     }
 
+    /// 采集一份运行环境健康报告：Claude CLI / git 是否可用及版本号、各
+    /// marketplace 的远端可达性（`git ls-remote`）、DB 中 plugin/marketplace
+    /// 的数量和发现来源。效仿 Tauri/Millennium `info` 命令的思路，把"为什么
+    /// 同步/更新悄无声息地返回空"（例如 Claude CLI 不在 PATH、marketplace
+    /// 仓库不可达）从散落的 `log::debug!`/`log::warn!` 里收敛成一份结构化结果。
+    pub async fn diagnostics(&self, claude_command: Option<String>) -> Result<PluginEnvironmentReport> {
+        let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
+
+        let claude_cli = probe_cli_version("Claude CLI", &cli_command, &["--version"]);
+        let git_cli = probe_cli_version("Git", "git", &["--version"]);
+
+        let marketplaces = self
+            .get_claude_marketplaces(Some(cli_command))
+            .await
+            .unwrap_or_default();
+        let marketplaces_known = marketplaces.len();
+        let marketplaces_missing_install_location = marketplaces
+            .iter()
+            .filter(|m| m.install_location.as_deref().map(str::trim).unwrap_or("").is_empty())
+            .map(|m| m.name.clone())
+            .collect();
+        let marketplace_reachability = marketplaces
+            .iter()
+            .map(probe_marketplace_reachability)
+            .collect();
+
+        let plugins = self.db.get_plugins().unwrap_or_default();
+        let plugins_known = plugins.len();
+        let plugins_installed = plugins.iter().filter(|p| p.installed).count();
+        let plugins_from_claude_cli = plugins
+            .iter()
+            .filter(|p| p.discovery_source.as_deref() == Some("claude_cli"))
+            .count();
+        let plugins_from_app = plugins_known - plugins_from_claude_cli;
+
+        Ok(PluginEnvironmentReport {
+            claude_cli,
+            git_cli,
+            marketplaces_known,
+            marketplaces_missing_install_location,
+            marketplace_reachability,
+            plugins_known,
+            plugins_installed,
+            plugins_from_claude_cli,
+            plugins_from_app,
+        })
+    }
+
     /// 更新单个 marketplace（调用 Claude Code CLI）
     pub async fn update_marketplace(&self, marketplace_name: &str, claude_command: Option<String>) -> Result<MarketplaceUpdateResult> {
         let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
@@ -672,13 +847,20 @@ impl PluginManager {
                 "marketplace".to_string(),
                 "update".to_string(),
                 marketplace_name.to_string(),
+                "--json".to_string(),
             ],
             timeout: Duration::from_secs(60),
         }];
 
-        let cli_result = claude_cli.run(&commands)?;
-        let output = cli_result.outputs.first().map(|o| o.output.clone()).unwrap_or_default();
-        let success = parse_marketplace_update_output(&output);
+        let cli_result = self.run_logged("update_marketplace", &claude_cli, &commands, None)?;
+        let success = cli_result
+            .outputs
+            .first()
+            .map(parse_marketplace_update_output)
+            .unwrap_or(CommandResult::Failed {
+                reason: "未捕获到 marketplace update 命令的输出".to_string(),
+            })
+            .is_success();
 
         Ok(MarketplaceUpdateResult {
             marketplace_name: marketplace_name.to_string(),
@@ -687,47 +869,303 @@ impl PluginManager {
         })
     }
 
-    /// 检测：已安装 skills 中，哪些“也存在 Claude Code Plugin 版本”，用于提示用户升级为 plugin 完整安装。
-    ///
-    /// 说明：此处仅做“提示候选”，不自动安装/不移除 skill；安装仍建议走本应用的安全扫描 + plugin 安装流程。
-    pub async fn get_skill_plugin_upgrade_candidates(
+    /// 把当前已知的 marketplaces（含各自安装目录的 git HEAD）和已安装 plugins
+    /// 导出成一份可移植的清单，供用户在另一台机器上复现环境，或在一次误操作
+    /// 后回滚。`head_sha` 与 [`Self::check_marketplaces_updates`] 读取的是
+    /// 同一个值，`import_state` 据此把 marketplace 锁定到导出时的那个提交，
+    /// 而不是 floating 在对方仓库此后变化的默认分支上。
+    pub async fn export_state(&self, claude_command: Option<String>) -> Result<PluginStateManifest> {
+        let marketplaces = self.get_claude_marketplaces(claude_command).await.unwrap_or_default();
+        let marketplaces = marketplaces
+            .into_iter()
+            .map(|m| {
+                let head_sha = m
+                    .install_location
+                    .as_deref()
+                    .and_then(|loc| git_output(&["-C", loc, "rev-parse", "HEAD"]).ok())
+                    .map(|sha| sha.trim().to_string())
+                    .filter(|sha| !sha.is_empty());
+                ExportedMarketplace {
+                    name: m.name,
+                    repo: m.repo,
+                    head_sha,
+                }
+            })
+            .collect();
+
+        let plugins = self
+            .db
+            .get_plugins()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.installed)
+            .map(|p| ExportedPlugin {
+                name: p.name,
+                marketplace_name: p.marketplace_name,
+                version: p.installed_version,
+                scope: p.claude_scope,
+                repository_url: p.repository_url,
+                commit_sha: p.scanned_commit_sha,
+                manifest_digest: p.manifest_digest,
+            })
+            .collect();
+
+        Ok(PluginStateManifest {
+            exported_at: Utc::now(),
+            marketplaces,
+            plugins,
+        })
+    }
+
+    /// 按一份 [`Self::export_state`] 产出的清单（即 guard.lock）重建环境：对每个
+    /// marketplace 调用 `claude plugin marketplace add`，再 `git checkout` 到
+    /// 记录的 `head_sha`——`allow_update` 为 `false`（默认，可复现安装）时，
+    /// checkout 失败（例如对方仓库已被强推、记录的提交不再可达）会让该条目
+    /// 直接失败，而不是像从前那样只记一条警告就继续在浮动的最新提交上安装；
+    /// `allow_update` 为 `true` 时放弃锁定，接受 `marketplace add` 解析到的
+    /// 最新提交。对每个 plugin 复用
+    /// [`crate::services::plugin_backend::PluginBackend::install`] 安装，这些
+    /// 安装彼此独立，按 [`BULK_PLUGIN_OP_PARALLELISM`] 并发跑而非一个个排队。
+    /// 单个条目失败不会中断整体流程，成功/失败逐条记录在返回结果里。
+    pub async fn import_state(
         &self,
+        manifest: &PluginStateManifest,
         claude_command: Option<String>,
-    ) -> Result<Vec<SkillPluginUpgradeCandidate>> {
+        allow_update: bool,
+    ) -> Result<PluginStateImportResult> {
         let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
         if which(&cli_command).is_err() {
-            return Ok(Vec::new());
+            anyhow::bail!("未找到 Claude Code CLI: {}", cli_command);
         }
 
-        // 拉取 marketplaces（用于给出 marketplace add 的 repo 参数）
-        let marketplaces = self.get_claude_marketplaces(Some(cli_command.clone())).await?;
-        let mut marketplace_repo_by_name: HashMap<String, (Option<String>, Option<String>)> = HashMap::new();
-        for mp in marketplaces {
-            marketplace_repo_by_name.insert(
-                mp.name.clone(),
-                (mp.repo.clone(), mp.repository_url.clone()),
-            );
-        }
+        let mut marketplace_results = Vec::new();
+        for mp in &manifest.marketplaces {
+            let Some(repo) = mp.repo.as_deref().filter(|r| !r.trim().is_empty()) else {
+                marketplace_results.push(PluginStateImportItem {
+                    name: mp.name.clone(),
+                    success: false,
+                    detail: "清单里缺少 repo，无法重新添加该 marketplace".to_string(),
+                });
+                continue;
+            };
 
-        // 拉取 installed plugins（用于过滤：已安装的不提示）
-        let claude_cli = ClaudeCli::new(cli_command.clone());
-        let installed_output = claude_cli.run(&[ClaudeCommand {
-            args: vec!["plugin".to_string(), "list".to_string(), "--json".to_string()],
-            timeout: Duration::from_secs(20),
-        }])?;
-        let installed_text = installed_output.outputs.first().map(|o| o.output.as_str()).unwrap_or_default();
-        let installed_plugins: Vec<ClaudeInstalledPluginEntry> = parse_json_output(installed_text)
-            .context("解析 `claude plugin list --json` 输出失败")?;
-        let installed_ids: HashSet<String> = installed_plugins
-            .into_iter()
-            .map(|p| p.id)
-            .collect();
+            let claude_cli = ClaudeCli::new(cli_command.clone());
+            let commands = vec![ClaudeCommand {
+                args: vec![
+                    "plugin".to_string(),
+                    "marketplace".to_string(),
+                    "add".to_string(),
+                    repo.to_string(),
+                    "--json".to_string(),
+                ],
+                timeout: Duration::from_secs(60),
+            }];
+
+            let (mut success, mut detail) = match self.run_logged("import_marketplace", &claude_cli, &commands, None) {
+                Ok(cli_result) => {
+                    let success = cli_result
+                        .outputs
+                        .first()
+                        .map(parse_marketplace_add_output)
+                        .unwrap_or(CommandResult::Failed {
+                            reason: "未捕获到 marketplace add 命令的输出".to_string(),
+                        })
+                        .is_success();
+                    let output = cli_result.outputs.first().map(|o| o.output.clone()).unwrap_or_default();
+                    (success, output)
+                }
+                Err(e) => (false, e.to_string()),
+            };
 
-        // 拉取 available plugins（用于匹配 skill->plugin）
-        let available_output = claude_cli.run(&[ClaudeCommand {
-            args: vec![
-                "plugin".to_string(),
-                "list".to_string(),
+            if success && !allow_update {
+                if let Some(sha) = mp.head_sha.as_deref() {
+                    if let Some(install_location) = default_marketplace_install_location(&mp.name) {
+                        if let Err(e) = git_output(&["-C", &install_location, "checkout", sha]) {
+                            success = false;
+                            detail = format!(
+                                "无法锁定到清单记录的提交 {}：{}（如需放弃锁定、使用对方仓库当前最新提交，请用 allow_update 重新导入）",
+                                sha, e
+                            );
+                        }
+                    }
+                }
+            }
+
+            marketplace_results.push(PluginStateImportItem {
+                name: mp.name.clone(),
+                success,
+                detail,
+            });
+        }
+
+        // marketplaces 先串行加完（后一个可能依赖前一个已经 add 成功），但各个
+        // plugin 的安装彼此独立，都是各自一次带超时的 CLI 子进程调用，跟
+        // remove_marketplace 的批量卸载是同一个瓶颈——丢进有限并发的 rayon
+        // 线程池并行跑
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(BULK_PLUGIN_OP_PARALLELISM.clamp(1, MAX_BULK_PLUGIN_OP_PARALLELISM))
+            .build()
+            .context("无法创建安装并发线程池")?;
+        let plugin_results: Vec<PluginStateImportItem> = pool.install(|| {
+            manifest
+                .plugins
+                .par_iter()
+                .map(|ep| {
+                    let marketplace_repo = manifest
+                        .marketplaces
+                        .iter()
+                        .find(|m| m.name == ep.marketplace_name)
+                        .and_then(|m| m.repo.clone())
+                        .unwrap_or_default();
+
+                    let mut plugin = Plugin::new(
+                        ep.name.clone(),
+                        "imported".to_string(),
+                        ep.marketplace_name.clone(),
+                        String::new(),
+                    );
+                    plugin.version = ep.version.clone();
+                    plugin.claude_scope = ep.scope.clone();
+
+                    let backend = self.backends.resolve(&plugin);
+                    let (success, detail) = match backend.prepare(&cli_command).and_then(|_| {
+                        backend.install(&cli_command, &plugin, &marketplace_repo, &self.operation_logger, None)
+                    }) {
+                        Ok((_, plugin_outcome)) => (plugin_outcome.success, plugin_outcome.output),
+                        Err(e) => (false, e.to_string()),
+                    };
+
+                    PluginStateImportItem {
+                        name: plugin.plugin_spec(),
+                        success,
+                        detail,
+                    }
+                })
+                .collect()
+        });
+
+        Ok(PluginStateImportResult {
+            marketplaces: marketplace_results,
+            plugins: plugin_results,
+        })
+    }
+
+    /// 后台更新检查：刷新 plugins/marketplaces 的可用版本缓存，并返回这次需要
+    /// 提醒用户的条目。供启动时创建的后台任务周期性调用，不应在任何命令处理
+    /// 路径上同步等待——本方法本身只做 CLI 调用（各自带超时）+ 写库，没有额外阻塞。
+    pub async fn refresh_update_status(&self, claude_command: Option<String>) -> Result<Vec<UpdateStatus>> {
+        let now = Utc::now();
+        let plugin_names: HashMap<String, String> = self.db.get_plugins()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| (p.id, p.name))
+            .collect();
+
+        let mut to_notify = Vec::new();
+
+        let plugin_updates = self.check_plugins_updates(claude_command.clone()).await.unwrap_or_default();
+        for (plugin_id, available_version, _bump) in plugin_updates {
+            let name = plugin_names.get(&plugin_id).cloned().unwrap_or_else(|| plugin_id.clone());
+            if let Some(status) = self.record_update_check("plugin", &plugin_id, &name, &available_version, now)? {
+                to_notify.push(status);
+            }
+        }
+
+        let marketplace_updates = self.check_marketplaces_updates(claude_command).await.unwrap_or_default();
+        for (marketplace_name, available_version) in marketplace_updates {
+            if let Some(status) = self.record_update_check("marketplace", &marketplace_name, &marketplace_name, &available_version, now)? {
+                to_notify.push(status);
+            }
+        }
+
+        Ok(to_notify)
+    }
+
+    /// 缓存一次检查结果，并判断这个条目是否需要（重新）提醒：可用版本较上次
+    /// 提醒时发生变化，或距离上次提醒已超过 [`UPDATE_NOTIFICATION_SUPPRESS_WINDOW`]
+    fn record_update_check(
+        &self,
+        item_type: &str,
+        item_id: &str,
+        item_name: &str,
+        available_version: &str,
+        checked_at: DateTime<Utc>,
+    ) -> Result<Option<UpdateStatus>> {
+        let existing = self.db.get_update_status()?
+            .into_iter()
+            .find(|s| s.item_type == item_type && s.item_id == item_id);
+
+        self.db.upsert_update_check(item_type, item_id, item_name, available_version, checked_at)?;
+
+        let should_notify = match &existing {
+            None => true,
+            Some(status) => {
+                let version_changed = status.last_notified_version.as_deref() != Some(available_version);
+                let window_elapsed = status.last_notified_at
+                    .map(|at| checked_at - at > UPDATE_NOTIFICATION_SUPPRESS_WINDOW)
+                    .unwrap_or(true);
+                version_changed || window_elapsed
+            }
+        };
+
+        if !should_notify {
+            return Ok(None);
+        }
+
+        self.db.mark_update_notified(item_type, item_id, available_version, checked_at)?;
+
+        Ok(Some(UpdateStatus {
+            item_type: item_type.to_string(),
+            item_id: item_id.to_string(),
+            item_name: item_name.to_string(),
+            available_version: available_version.to_string(),
+            checked_at,
+            last_notified_version: Some(available_version.to_string()),
+            last_notified_at: Some(checked_at),
+        }))
+    }
+
+    /// 检测：已安装 skills 中，哪些“也存在 Claude Code Plugin 版本”，用于提示用户升级为 plugin 完整安装。
+    ///
+    /// 说明：此处仅做“提示候选”，不自动安装/不移除 skill；安装仍建议走本应用的安全扫描 + plugin 安装流程。
+    pub async fn get_skill_plugin_upgrade_candidates(
+        &self,
+        claude_command: Option<String>,
+    ) -> Result<Vec<SkillPluginUpgradeCandidate>> {
+        let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
+        if which(&cli_command).is_err() {
+            return Ok(Vec::new());
+        }
+
+        // 拉取 marketplaces（用于给出 marketplace add 的 repo 参数）
+        let marketplaces = self.get_claude_marketplaces(Some(cli_command.clone())).await?;
+        let mut marketplace_repo_by_name: HashMap<String, (Option<String>, Option<String>)> = HashMap::new();
+        for mp in marketplaces {
+            marketplace_repo_by_name.insert(
+                mp.name.clone(),
+                (mp.repo.clone(), mp.repository_url.clone()),
+            );
+        }
+
+        // 拉取 installed plugins（用于过滤：已安装的不提示）
+        let claude_cli = ClaudeCli::new(cli_command.clone());
+        let installed_output = claude_cli.run(&[ClaudeCommand {
+            args: vec!["plugin".to_string(), "list".to_string(), "--json".to_string()],
+            timeout: Duration::from_secs(20),
+        }])?;
+        let installed_text = installed_output.outputs.first().map(|o| o.output.as_str()).unwrap_or_default();
+        let installed_plugins: Vec<ClaudeInstalledPluginEntry> = parse_json_output(installed_text)
+            .context("解析 `claude plugin list --json` 输出失败")?;
+        let installed_ids: HashSet<String> = installed_plugins
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+
+        // 拉取 available plugins（用于匹配 skill->plugin）
+        let available_output = claude_cli.run(&[ClaudeCommand {
+            args: vec![
+                "plugin".to_string(),
+                "list".to_string(),
                 "--json".to_string(),
                 "--available".to_string(),
             ],
@@ -1040,7 +1478,12 @@ impl PluginManager {
         Ok(())
     }
 
-    pub async fn prepare_plugin_installation(&self, plugin_id: &str, locale: &str) -> Result<SecurityReport> {
+    pub async fn prepare_plugin_installation(
+        &self,
+        plugin_id: &str,
+        locale: &str,
+        strict_dependencies: bool,
+    ) -> Result<SecurityReport> {
         let plugin = self.db.get_plugins()?
             .into_iter()
             .find(|p| p.id == plugin_id)
@@ -1064,6 +1507,7 @@ impl PluginManager {
         };
 
         let repo_root = find_repo_root(&cache_path)?;
+        let guard_report = self.guard_scan_or_bail(&repo_root)?;
         let mut resolved_plugins = resolve_marketplace_plugins(
             &repo_root,
             &plugin.repository_url,
@@ -1076,7 +1520,8 @@ impl PluginManager {
 
         let existing_plugins = self.db.get_plugins().unwrap_or_default();
         let existing_map: HashMap<String, Plugin> = existing_plugins
-            .into_iter()
+            .iter()
+            .cloned()
             .map(|plugin| (plugin.id.clone(), plugin))
             .collect();
 
@@ -1096,6 +1541,16 @@ impl PluginManager {
             }
         }
 
+        let resolution = order_resolved_plugins_for_install(resolved_plugins, &existing_plugins)?;
+        if strict_dependencies && !resolution.problems.is_empty() {
+            let details = resolution.problems.iter()
+                .map(|p| p.describe())
+                .collect::<Vec<_>>()
+                .join("; ");
+            anyhow::bail!("插件依赖检查未通过: {}", details);
+        }
+        let resolved_plugins = resolution.ordered;
+
         let marketplace_name = resolved_plugins
             .first()
             .map(|p| p.plugin.marketplace_name.clone())
@@ -1103,17 +1558,37 @@ impl PluginManager {
 
         let mut reports = Vec::new();
         for resolved in &resolved_plugins {
+            let scan_options = ScanOptions { skip_readme: true };
             let report = self.scanner.scan_directory_with_options(
                 resolved.source_path.to_str().context("插件目录路径无效")?,
                 &resolved.plugin.id,
                 locale,
-                ScanOptions { skip_readme: true },
+                scan_options,
+                None,
                 None,
             )?;
+            let backend_reports = self.run_scanner_backends(&resolved.source_path, &resolved.plugin.id, scan_options);
+            let mut report = merge_backend_reports(report, backend_reports);
+
+            let hook_issues = scan_lifecycle_hook_issues(
+                &resolved.source_path,
+                read_plugin_manifest(&resolved.source_path).ok().as_ref(),
+            );
+            if !hook_issues.is_empty() {
+                report.issues.extend(hook_issues);
+                report.score = self.scanner.calculate_score(&report.issues);
+                report.level = SecurityLevel::from_score(report.score);
+            }
+
             reports.push((resolved.plugin.clone(), report));
         }
 
-        let merged_report = merge_reports(&reports, &marketplace_name);
+        let mut merged_report = merge_reports(&reports, &marketplace_name);
+        for problem in &resolution.problems {
+            merged_report.recommendations.push(format!("依赖检查警告: {}", problem.describe()));
+        }
+
+        write_resolved_lockfile(&repo_root, &reports, &merged_report);
 
         let now = Utc::now();
         let blocked = merged_report.blocked;
@@ -1133,6 +1608,7 @@ impl PluginManager {
             );
             updated.scanned_at = Some(now);
             updated.staging_path = Some(repo_root.to_string_lossy().to_string());
+            apply_guard_report(&mut updated, &guard_report);
             if blocked && !updated.installed {
                 updated.install_status = Some("blocked".to_string());
             }
@@ -1151,10 +1627,150 @@ impl PluginManager {
         Ok(merged_report)
     }
 
+    /// 在已安装 plugin 的安装目录里执行一个已批准的生命周期钩子（见
+    /// [`scan_lifecycle_hook_issues`]），把 `phase`（`preinstall`/`postinstall`/
+    /// `preuninstall`/`postuninstall`）作为第一个参数传给脚本。
+    ///
+    /// 这里做的是限制执行面，**不是沙箱**：只允许执行已安装 plugin
+    /// （`claude_install_path` 已知）声明过的钩子，工作目录锁定在插件自身
+    /// 目录内，不经过 shell 展开插件目录之外的任何内容，并且清空继承自本
+    /// 进程的环境变量（避免钩子脚本读到用户的 token/密钥等本不该给它的
+    /// 信息）。这些都只是降低误伤/泄漏面，并不提供真正的隔离——钩子脚本
+    /// 仍然以当前用户权限直接跑在宿主机上，没有 seccomp/namespace/容器/
+    /// 资源限制，可以访问网络、读写 `current_dir` 之外的任何用户可写路径。
+    /// 真正需要强隔离的场景应该把这个钩子丢进一个容器或虚拟机里执行，这里
+    /// 暂不提供。
+    /// 输出经 [`OperationLogger`] 落盘，并复用
+    /// [`crate::services::claude_backend::parse_json_command_result`] 的解析路径
+    /// 判断执行结果，解析不出结构化状态时按退出码回退。
+    pub async fn run_plugin_lifecycle_hook(&self, plugin_id: &str, phase: &str) -> Result<PluginLifecycleHookResult> {
+        if !LIFECYCLE_HOOK_PHASES.contains(&phase) {
+            anyhow::bail!("未知的生命周期钩子阶段: {}", phase);
+        }
+
+        let plugin = self.db.get_plugins()?
+            .into_iter()
+            .find(|p| p.id == plugin_id)
+            .context("未找到该插件")?;
+        let install_path = plugin.claude_install_path.as_ref()
+            .context("该插件尚未安装，无法执行生命周期钩子")?;
+        let source_path = PathBuf::from(install_path);
+
+        let manifest = read_plugin_manifest(&source_path)
+            .context("无法读取插件的 plugin.json")?;
+        let script = manifest.hooks.as_ref()
+            .and_then(|hooks| hooks.get(phase))
+            .with_context(|| format!("该插件未声明 {} 钩子", phase))?;
+        let script_path = resolve_source_path(&source_path, script)?;
+        if !script_path.exists() {
+            anyhow::bail!("钩子脚本不存在: {:?}", script_path);
+        }
+
+        let log = self.operation_logger.start(&format!("lifecycle-hook-{}", phase))?;
+        log.log_command(&format!("sh {:?} {}", script_path, phase));
+
+        let output = std::process::Command::new("sh")
+            .arg(&script_path)
+            .arg(phase)
+            .current_dir(&source_path)
+            // 清空继承的环境变量，只留下脚本运行必需的最小集合，防止钩子
+            // 读到本进程持有的 token/密钥等敏感环境变量
+            .env_clear()
+            .env("PATH", "/usr/bin:/bin:/usr/local/bin")
+            .env("HOME", &source_path)
+            .env("CLAUDE_PLUGIN_DIR", &source_path)
+            .output()
+            .with_context(|| format!("执行 {} 钩子失败", phase))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        log.log_output(&stdout);
+        log.log_output(&stderr);
+        log.log_exit_status(&output.status.to_string());
+
+        let result = parse_json_command_result(&stdout).unwrap_or_else(|| {
+            if output.status.success() {
+                CommandResult::Succeeded
+            } else {
+                CommandResult::Failed {
+                    reason: log.fail_message(&format!("{} 钩子脚本以非零状态退出", phase)),
+                }
+            }
+        });
+
+        let status = match &result {
+            CommandResult::Succeeded => "succeeded",
+            CommandResult::AlreadyPresent => "already_present",
+            CommandResult::NotFound => "not_found",
+            CommandResult::Failed { .. } => "failed",
+        }.to_string();
+
+        Ok(PluginLifecycleHookResult {
+            plugin_id: plugin_id.to_string(),
+            phase: phase.to_string(),
+            status,
+            raw_log: format!("{}\n{}", stdout, stderr),
+        })
+    }
+
+    /// 读取 [`write_resolved_lockfile`] 在上次 `prepare_plugin_installation` 时
+    /// 留下的 `skills-guard.lock`，与当前磁盘上的 manifest 重新跑一遍
+    /// [`resolve_marketplace_plugins`] 做对比，标记出哪些 plugin 的 `version`
+    /// 已经和锁定时不一致（drift）。只读本地缓存，不会触发下载；缓存目录或
+    /// 锁文件缺失时提示先执行一次安装解析。
+    pub async fn get_lockfile_info(&self, plugin_id: &str) -> Result<LockfileAuditReport> {
+        let plugin = self.db.get_plugins()?
+            .into_iter()
+            .find(|p| p.id == plugin_id)
+            .context("未找到该插件")?;
+
+        let repositories = self.db.get_repositories()?;
+        let repo = repositories.iter()
+            .find(|r| r.url == plugin.repository_url)
+            .context("未找到对应的仓库记录")?
+            .clone();
+        let cache_path = repo.cache_path
+            .map(PathBuf::from)
+            .filter(|p| p.exists())
+            .context("未找到本地缓存，请先执行一次安装解析以生成锁文件")?;
+        let repo_root = find_repo_root(&cache_path)?;
+
+        let lockfile_path = repo_root.join(LOCKFILE_FILE_NAME);
+        let lockfile_content = std::fs::read_to_string(&lockfile_path)
+            .context("未找到锁文件，请先执行一次安装解析以生成它")?;
+        let lockfile: ResolvedPluginLockfile = serde_json::from_str(&lockfile_content)
+            .context("锁文件格式无效")?;
+
+        let live_plugins = resolve_marketplace_plugins(&repo_root, &plugin.repository_url, false)?;
+
+        let entries = lockfile.plugins.iter().map(|locked| {
+            let current_version = live_plugins.iter()
+                .find(|r| r.plugin.marketplace_name == locked.marketplace && r.plugin.source == locked.source)
+                .and_then(|r| r.plugin.version.clone());
+            LockfileDriftEntry {
+                name: locked.name.clone(),
+                marketplace: locked.marketplace.clone(),
+                source: locked.source.clone(),
+                drifted: current_version != locked.version,
+                locked_version: locked.version.clone(),
+                current_version,
+            }
+        }).collect();
+
+        Ok(LockfileAuditReport {
+            generated_at: lockfile.generated_at,
+            commit_sha: lockfile.commit_sha,
+            blocked: lockfile.blocked,
+            partial_scan: lockfile.partial_scan,
+            entries,
+        })
+    }
+
     pub async fn confirm_plugin_installation(
         &self,
         plugin_id: &str,
         claude_command: Option<String>,
+        mut on_output: Option<&mut dyn FnMut(&str)>,
     ) -> Result<PluginInstallResult> {
         let plugin = self.db.get_plugins()?
             .into_iter()
@@ -1173,114 +1789,94 @@ impl PluginManager {
             .context("无法解析 marketplace repo")?;
         let marketplace_name = plugin.marketplace_name.clone();
 
-        let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
-        if which(&cli_command).is_err() {
-            let mut message = format!("未找到 Claude Code CLI: {}", cli_command);
-            if which("codex").is_ok() {
-                message.push_str("\n检测到 Codex，但该流程仅支持 Claude Code Plugin。");
-            }
-            if which("opencode").is_ok() {
-                message.push_str("\n检测到 OpenCode，但该流程仅支持 Claude Code Plugin。");
-            }
-            anyhow::bail!(message);
-        }
-        let claude_cli = ClaudeCli::new(cli_command);
-
-        // 构建命令：1. marketplace add，2. 只安装选中的单个 plugin
-        let mut commands = Vec::new();
-        let add_args = plugin
-            .marketplace_add_command
-            .as_deref()
-            .and_then(parse_slash_command_args)
-            .unwrap_or_else(|| {
-                vec![
-                    "plugin".to_string(),
-                    "marketplace".to_string(),
-                    "add".to_string(),
-                    marketplace_repo.clone(),
-                ]
-            });
-
-        commands.push(ClaudeCommand {
-            args: add_args,
-            timeout: Duration::from_secs(60),
-        });
-
-        let install_args = plugin
-            .plugin_install_command
-            .as_deref()
-            .and_then(parse_slash_command_args)
-            .unwrap_or_else(|| {
-                vec![
-                    "plugin".to_string(),
-                    "install".to_string(),
-                    plugin.plugin_spec(),
-                ]
-            });
-
-        // 只安装选中的单个 plugin
-        commands.push(ClaudeCommand {
-            args: install_args,
-            timeout: Duration::from_secs(180),
+        // 该 marketplace 若配置了受信任签名者（见 [`Self::set_trust_config`]），
+        // 在真正调用 CLI 安装之前先校验缓存仓库 HEAD 的 GPG 签名；未通过时
+        // 直接拒绝安装，不拿 `installed = true`，原因记在 `install_status` 里，
+        // 未配置签名校验的 marketplace 照常放行
+        let repo_record = self.db.get_repositories()?
+            .into_iter()
+            .find(|r| r.url == plugin.repository_url);
+        let signature = repo_record.as_ref().and_then(|repo| {
+            let cache_path = repo.cache_path.as_deref()?;
+            let commit_sha = repo.cached_commit_sha.as_deref()?;
+            self.verify_marketplace_signature(&marketplace_name, Path::new(cache_path), commit_sha)
         });
 
-        let cli_result = claude_cli.run(&commands)?;
-        let mut outputs = cli_result.outputs.into_iter();
-
-        let marketplace_output = outputs
-            .next()
-            .map(|o| o.output)
-            .unwrap_or_default();
+        if let Some(verification) = &signature {
+            if !verification.verified {
+                let mut updated = plugin.clone();
+                updated.install_status = Some("signature_failed".to_string());
+                updated.signature_verified = Some(false);
+                updated.signature_signer = None;
+                self.db.save_plugin(&updated)?;
 
-        let marketplace_outcome = parse_marketplace_add_output(&marketplace_output);
-        let marketplace_status = if marketplace_outcome.success {
-            if marketplace_outcome.already {
-                "already_added"
-            } else {
-                "added"
+                let plugin_statuses = vec![PluginInstallStatus {
+                    plugin_id: updated.id,
+                    plugin_name: updated.name,
+                    status: "signature_failed".to_string(),
+                    output: verification.detail.clone(),
+                }];
+
+                return Ok(PluginInstallResult {
+                    marketplace_name,
+                    marketplace_repo,
+                    marketplace_status: "signature_failed".to_string(),
+                    raw_log: verification.detail.clone(),
+                    plugin_statuses,
+                });
             }
-        } else {
-            "failed"
-        };
+        }
+
+        let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
+        let backend = self.backends.resolve(&plugin);
+        backend.prepare(&cli_command)?;
+
+        let (marketplace_outcome, plugin_outcome) = backend.install(
+            &cli_command,
+            &plugin,
+            &marketplace_repo,
+            &self.operation_logger,
+            on_output.as_deref_mut(),
+        )?;
 
         let now = Utc::now();
-        let mut plugin_statuses = Vec::new();
-
-        // 只处理选中的单个 plugin
-        let output = outputs.next().map(|o| o.output).unwrap_or_default();
-        let outcome = parse_plugin_install_output(&output);
-        let status = if outcome.success {
-            if outcome.already {
-                "already_installed"
-            } else {
-                "installed"
-            }
-        } else {
-            "failed"
-        };
+        let raw_log = plugin_outcome.raw_log.clone();
 
         let mut updated = plugin.clone();
-        updated.install_status = Some(status.to_string());
-        updated.install_log = Some(cli_result.raw_log.clone());
+        updated.install_status = Some(plugin_outcome.status.clone());
+        updated.install_log = Some(raw_log.clone());
         updated.staging_path = None;
-        if outcome.success {
+        if let Some(verification) = &signature {
+            updated.signature_verified = Some(verification.verified);
+            updated.signature_signer = verification.signer.clone();
+        }
+        if plugin_outcome.success {
             updated.installed = true;
             updated.installed_at = Some(now);
         }
         self.db.save_plugin(&updated)?;
 
-        plugin_statuses.push(PluginInstallStatus {
+        let mut output = plugin_outcome.output;
+        if let Some(findings) = updated.guard_findings.as_ref().filter(|f| !f.is_empty()) {
+            output.push_str(&format!(
+                "\n\n安装前守卫扫描（风险分 {}）发现以下问题：\n{}",
+                updated.guard_risk_score.unwrap_or_default(),
+                findings.join("\n")
+            ));
+        }
+
+        let plugin_statuses = vec![PluginInstallStatus {
             plugin_id: updated.id,
             plugin_name: updated.name,
-            status: status.to_string(),
+            status: plugin_outcome.status,
             output,
-        });
+        }];
 
         Ok(PluginInstallResult {
             marketplace_name,
             marketplace_repo,
-            marketplace_status: marketplace_status.to_string(),
-            raw_log: cli_result.raw_log,
+            marketplace_status: marketplace_outcome.status,
+            raw_log,
             plugin_statuses,
         })
     }
@@ -1299,11 +1895,244 @@ impl PluginManager {
         Ok(())
     }
 
-    /// 卸载单个 plugin
+    /// 准备安装一个本地开发中的 plugin：直接扫描磁盘上的目录，不经过 marketplace。
+    ///
+    /// 与 [`Self::prepare_plugin_installation`] 一样，在确认安装前先跑一遍
+    /// `SecurityScanner::scan_directory_with_options` 并把结果落库，供前端展示
+    /// 和拦截高危插件；区别在于这里没有 marketplace repo/manifest，plugin 记录
+    /// 以 `repository_url = "local"` 标记（与 [`Plugin::parse_repository_owner`]
+    /// 的 "local" 特判保持一致）。
+    pub async fn prepare_local_plugin_installation(
+        &self,
+        source_path: &str,
+        locale: &str,
+    ) -> Result<SecurityReport> {
+        let source = PathBuf::from(source_path);
+        if !source.exists() || !source.is_dir() {
+            anyhow::bail!("插件目录不存在: {}", source_path);
+        }
+        let source = source.canonicalize().unwrap_or(source);
+
+        let manifest = read_plugin_manifest(&source).ok();
+        let name = manifest
+            .as_ref()
+            .map(|m| m.name.clone())
+            .or_else(|| source.file_name().map(|n| n.to_string_lossy().to_string()))
+            .context("无法确定插件名称")?;
+
+        let existing = self.db.get_plugins()?
+            .into_iter()
+            .find(|p| p.repository_url == "local" && p.name == name);
+
+        let mut plugin = existing.unwrap_or_else(|| {
+            Plugin::new(
+                name.clone(),
+                "local".to_string(),
+                "local".to_string(),
+                source.to_string_lossy().to_string(),
+            )
+        });
+        plugin.source = source.to_string_lossy().to_string();
+        plugin.discovery_source = Some("local_path".to_string());
+        plugin.description = manifest
+            .as_ref()
+            .and_then(|m| m.description.clone())
+            .or(plugin.description.clone());
+        plugin.version = manifest
+            .as_ref()
+            .and_then(|m| m.version.clone())
+            .or(plugin.version.clone());
+        plugin.author = manifest
+            .as_ref()
+            .and_then(|m| m.author.as_ref().and_then(|a| a.to_display()))
+            .or(plugin.author.clone());
+
+        let guard_report = self.guard_scan_or_bail(&source)?;
+
+        let scan_options = ScanOptions { skip_readme: true };
+        let report = self.scanner.scan_directory_with_options(
+            source.to_str().context("插件目录路径无效")?,
+            &plugin.id,
+            locale,
+            scan_options,
+            None,
+            None,
+        )?;
+        let backend_reports = self.run_scanner_backends(&source, &plugin.id, scan_options);
+        let report = merge_backend_reports(report, backend_reports);
+
+        plugin.security_score = Some(report.score);
+        plugin.security_level = Some(report.level.as_str().to_string());
+        plugin.security_issues = Some(
+            report.issues.iter()
+                .map(|i| {
+                    let file_info = i.file_path.as_ref()
+                        .map(|f| format!("[{}] ", f))
+                        .unwrap_or_default();
+                    format!("{}{:?}: {}", file_info, i.severity, i.description)
+                })
+                .collect()
+        );
+        plugin.scanned_at = Some(Utc::now());
+        plugin.staging_path = Some(source.to_string_lossy().to_string());
+        apply_guard_report(&mut plugin, &guard_report);
+        if report.blocked && !plugin.installed {
+            plugin.install_status = Some("blocked".to_string());
+        }
+        self.db.save_plugin(&plugin)?;
+
+        if report.blocked {
+            let mut error_msg = "安全检测发现严重威胁，已禁止安装。\n\n检测到以下高危操作：\n".to_string();
+            for (idx, issue) in report.hard_trigger_issues.iter().enumerate() {
+                error_msg.push_str(&format!("{}. {}\n", idx + 1, issue));
+            }
+            error_msg.push_str("\n这些操作可能对您的系统造成严重危害，强烈建议不要安装此插件。");
+            anyhow::bail!(error_msg);
+        }
+
+        Ok(report)
+    }
+
+    /// 准备安装一个任意 git 仓库里的 plugin：clone（可选 checkout 到某个 ref）到
+    /// 本地缓存目录，把仓库根目录当成单个 plugin 的源码，不要求也不依赖对方仓库
+    /// 提供 `marketplace.json`。与 [`Self::prepare_local_plugin_installation`]
+    /// 共享同一套扫描/落库/确认流程（[`confirm_local_plugin_installation`]
+    /// 按 `staging_path` 落地时不关心插件原本来自哪个 [`crate::services::plugin_source::PluginSourceBackend`]），
+    /// plugin 记录以 `discovery_source = "git_url"` 标记。
+    pub async fn prepare_git_plugin_installation(
+        &self,
+        repository_url: &str,
+        git_ref: Option<&str>,
+        locale: &str,
+    ) -> Result<SecurityReport> {
+        if which("git").is_err() {
+            anyhow::bail!("未找到 git 命令行工具");
+        }
+
+        let backend = self.source_backends.resolve(Some("git_url"));
+        let request = PluginSourceRequest {
+            root: None,
+            repository_url,
+            git_ref,
+        };
+        let mut resolved = backend.resolve(&request)?;
+        let resolved = resolved.pop().context("未能从该仓库解析出插件")?;
+        let (mut plugin, source) = (resolved.plugin, resolved.source_path);
+
+        let existing = self.db.get_plugins()?
+            .into_iter()
+            .find(|p| p.repository_url == repository_url && p.name == plugin.name);
+        if let Some(existing) = existing {
+            plugin.id = existing.id;
+            plugin.claude_id = existing.claude_id.or(plugin.claude_id);
+            plugin.installed = existing.installed;
+            plugin.installed_at = existing.installed_at;
+            plugin.installed_version = existing.installed_version;
+        }
+        plugin.discovery_source = Some("git_url".to_string());
+
+        let guard_report = self.guard_scan_or_bail(&source)?;
+
+        let scan_options = ScanOptions { skip_readme: true };
+        let report = self.scanner.scan_directory_with_options(
+            source.to_str().context("插件目录路径无效")?,
+            &plugin.id,
+            locale,
+            scan_options,
+            None,
+            None,
+        )?;
+        let backend_reports = self.run_scanner_backends(&source, &plugin.id, scan_options);
+        let report = merge_backend_reports(report, backend_reports);
+
+        plugin.security_score = Some(report.score);
+        plugin.security_level = Some(report.level.as_str().to_string());
+        plugin.security_issues = Some(
+            report.issues.iter()
+                .map(|i| {
+                    let file_info = i.file_path.as_ref()
+                        .map(|f| format!("[{}] ", f))
+                        .unwrap_or_default();
+                    format!("{}{:?}: {}", file_info, i.severity, i.description)
+                })
+                .collect()
+        );
+        plugin.scanned_at = Some(Utc::now());
+        plugin.staging_path = Some(source.to_string_lossy().to_string());
+        apply_guard_report(&mut plugin, &guard_report);
+        if report.blocked && !plugin.installed {
+            plugin.install_status = Some("blocked".to_string());
+        }
+        self.db.save_plugin(&plugin)?;
+
+        if report.blocked {
+            let mut error_msg = "安全检测发现严重威胁，已禁止安装。\n\n检测到以下高危操作：\n".to_string();
+            for (idx, issue) in report.hard_trigger_issues.iter().enumerate() {
+                error_msg.push_str(&format!("{}. {}\n", idx + 1, issue));
+            }
+            error_msg.push_str("\n这些操作可能对您的系统造成严重危害，强烈建议不要安装此插件。");
+            anyhow::bail!(error_msg);
+        }
+
+        Ok(report)
+    }
+
+    /// 确认安装一个「直接从磁盘目录暂存」的 plugin：把暂存目录落地到 Claude 插件
+    /// 目录（Unix 下创建软链接，便于开发者修改源码后直接点「重新扫描」生效；
+    /// 其他平台退化为复制），并写入 `claude_install_path` 使
+    /// [`Self::cancel_plugin_installation`] / `scan_installed_plugin` 等既有的
+    /// 已安装流程可以继续工作。只认 `staging_path`，不关心插件原本是
+    /// [`Self::prepare_local_plugin_installation`] 还是
+    /// [`Self::prepare_git_plugin_installation`] 暂存的。
+    pub fn confirm_local_plugin_installation(&self, plugin_id: &str) -> Result<Plugin> {
+        let mut plugin = self.db.get_plugins()?
+            .into_iter()
+            .find(|p| p.id == plugin_id)
+            .context("未找到该插件")?;
+
+        let staging_path = plugin.staging_path.clone().context("插件尚未准备安装")?;
+        let source = PathBuf::from(&staging_path);
+        if !source.exists() {
+            anyhow::bail!("插件目录不存在: {}", staging_path);
+        }
+
+        let install_path = default_local_plugin_install_location(&plugin.name)
+            .context("无法确定插件安装目录")?;
+        let target = PathBuf::from(&install_path);
+
+        link_or_copy_local_plugin(&source, &target)?;
+
+        plugin.claude_install_path = Some(install_path);
+        plugin.installed = true;
+        plugin.installed_at = Some(Utc::now());
+        plugin.install_status = Some("installed".to_string());
+        plugin.staging_path = None;
+        self.db.save_plugin(&plugin)?;
+
+        Ok(plugin)
+    }
+
+    /// 卸载单个 plugin；若有其它已安装 plugin 依赖它，拒绝卸载
     pub async fn uninstall_plugin(
         &self,
         plugin_id: &str,
         claude_command: Option<String>,
+    ) -> Result<PluginUninstallResult> {
+        self.uninstall_plugin_impl(plugin_id, claude_command, None)
+    }
+
+    /// `uninstall_plugin`/[`Self::remove_marketplace`] 共用的实现。
+    /// `skip_dependency_check_within_marketplace` 非空时，忽略该 marketplace
+    /// 内部的依赖者——整个 marketplace 一起卸载时，其内部插件间的相互依赖
+    /// 不应阻塞卸载。
+    ///
+    /// 内部没有真正的 `.await` 点（CLI 调用本身是阻塞的），写成同步函数，
+    /// 这样 [`Self::remove_marketplace`] 才能把它丢进 rayon 线程池并发跑。
+    fn uninstall_plugin_impl(
+        &self,
+        plugin_id: &str,
+        claude_command: Option<String>,
+        skip_dependency_check_within_marketplace: Option<&str>,
     ) -> Result<PluginUninstallResult> {
         let plugin = self.db.get_plugins()?
             .into_iter()
@@ -1314,30 +2143,18 @@ impl PluginManager {
             anyhow::bail!("该插件尚未安装");
         }
 
-        let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
-        if which(&cli_command).is_err() {
-            anyhow::bail!("未找到 Claude Code CLI: {}", cli_command);
+        let all_plugins = self.db.get_plugins().unwrap_or_default();
+        let dependents = installed_dependents(&plugin, &all_plugins, skip_dependency_check_within_marketplace);
+        if !dependents.is_empty() {
+            let names = dependents.iter().map(|p| p.name.as_str()).collect::<Vec<_>>().join(", ");
+            anyhow::bail!("插件 {} 正被以下已安装插件依赖，无法卸载: {}", plugin.name, names);
         }
-        let claude_cli = ClaudeCli::new(cli_command);
 
-        let commands = vec![
-            ClaudeCommand {
-                args: vec![
-                    "plugin".to_string(),
-                    "uninstall".to_string(),
-                    plugin.plugin_spec(),
-                ],
-                timeout: Duration::from_secs(60),
-            },
-        ];
-
-        let cli_result = claude_cli.run(&commands)?;
-        let output = cli_result.outputs
-            .first()
-            .map(|o| o.output.clone())
-            .unwrap_or_default();
+        let cli_command = claude_command.unwrap_or_else(|| "claude".to_string());
+        let backend = self.backends.resolve(&plugin);
+        backend.prepare(&cli_command)?;
 
-        let outcome = parse_plugin_uninstall_output(&output);
+        let outcome = backend.remove(&cli_command, &plugin, &self.operation_logger)?;
 
         let mut updated = plugin.clone();
         if outcome.success {
@@ -1347,14 +2164,14 @@ impl PluginManager {
         } else {
             updated.install_status = Some("uninstall_failed".to_string());
         }
-        updated.install_log = Some(cli_result.raw_log.clone());
+        updated.install_log = Some(outcome.raw_log.clone());
         self.db.save_plugin(&updated)?;
 
         Ok(PluginUninstallResult {
             plugin_id: updated.id,
             plugin_name: updated.name,
             success: outcome.success,
-            raw_log: cli_result.raw_log,
+            raw_log: outcome.raw_log,
         })
     }
 
@@ -1387,44 +2204,37 @@ impl PluginManager {
             .filter(|plugin| plugin.installed)
             .cloned()
             .collect();
-        let mut uninstall_results: HashMap<String, bool> = HashMap::new();
-
-        for plugin in &installed_plugins {
-            match self
-                .uninstall_plugin(&plugin.id, Some(cli_command.clone()))
-                .await
-            {
-                Ok(result) => {
-                    uninstall_results.insert(plugin.id.clone(), result.success);
-                }
-                Err(e) => {
-                    log::warn!("卸载 marketplace 插件失败: {} ({})", plugin.name, e);
-                    uninstall_results.insert(plugin.id.clone(), false);
-                }
-            }
-        }
-
-        let claude_cli = ClaudeCli::new(cli_command);
-
-        let commands = vec![
-            ClaudeCommand {
-                args: vec![
-                    "plugin".to_string(),
-                    "marketplace".to_string(),
-                    "remove".to_string(),
-                    marketplace_name.to_string(),
-                ],
-                timeout: Duration::from_secs(60),
-            },
-        ];
-
-        let cli_result = claude_cli.run(&commands)?;
-        let output = cli_result.outputs
-            .first()
-            .map(|o| o.output.clone())
-            .unwrap_or_default();
+        // 每个 plugin 的卸载都是一次独立的、带 60s 超时的 CLI 子进程调用，
+        // 串行跑的话 marketplace 里插件一多就很慢；丢进一个有限并发的 rayon
+        // 线程池并行跑，同时仍然把每个 plugin 的成功/失败单独收集进
+        // uninstall_results，语义不变。
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(BULK_PLUGIN_OP_PARALLELISM.clamp(1, MAX_BULK_PLUGIN_OP_PARALLELISM))
+            .build()
+            .context("无法创建卸载并发线程池")?;
+        let results: Vec<(String, bool)> = pool.install(|| {
+            installed_plugins
+                .par_iter()
+                .map(|plugin| {
+                    let success = match self.uninstall_plugin_impl(
+                        &plugin.id,
+                        Some(cli_command.clone()),
+                        Some(marketplace_name),
+                    ) {
+                        Ok(result) => result.success,
+                        Err(e) => {
+                            log::warn!("卸载 marketplace 插件失败: {} ({})", plugin.name, e);
+                            false
+                        }
+                    };
+                    (plugin.id.clone(), success)
+                })
+                .collect()
+        });
+        let uninstall_results: HashMap<String, bool> = results.into_iter().collect();
 
-        let outcome = parse_marketplace_remove_output(&output);
+        let backend = self.backends.default();
+        let outcome = backend.marketplace_remove(&cli_command, marketplace_name, &self.operation_logger)?;
 
         // 移除成功后，删除该 marketplace 下的所有 plugin 记录
         let mut removed_count = 0;
@@ -1443,7 +2253,7 @@ impl PluginManager {
             marketplace_repo: marketplace_repo.to_string(),
             success: outcome.success && uninstall_results.values().all(|ok| *ok),
             removed_plugins_count: removed_count,
-            raw_log: cli_result.raw_log,
+            raw_log: outcome.raw_log,
         })
     }
 
@@ -1471,14 +2281,6 @@ impl PluginManager {
     }
 }
 
-fn parse_claude_plugin_id(id: &str) -> Option<(String, String)> {
-    let (name, marketplace) = id.rsplit_once('@')?;
-    if name.is_empty() || marketplace.is_empty() {
-        return None;
-    }
-    Some((name.to_string(), marketplace.to_string()))
-}
-
 fn parse_slash_command_args(command: &str) -> Option<Vec<String>> {
     let trimmed = command.trim();
     if trimmed.is_empty() {
@@ -1504,412 +2306,86 @@ fn extract_marketplace_repo_from_command(command: &str) -> Option<String> {
     None
 }
 
-fn marketplace_repo_url(entry: &ClaudeMarketplaceListEntry) -> Option<String> {
-    let repo = entry.repo.as_deref()?.trim();
-    if repo.is_empty() {
+pub(crate) fn default_marketplace_install_location(name: &str) -> Option<String> {
+    if name.trim().is_empty() {
         return None;
     }
-
-    // Claude CLI 的 github source 通常返回 owner/repo
-    if repo.starts_with("http://") || repo.starts_with("https://") {
-        return Some(repo.to_string());
-    }
-
-    Some(format!("https://github.com/{}", repo))
-}
-
-fn parse_datetime(value: &Option<String>) -> Option<DateTime<Utc>> {
-    value.as_ref().and_then(|s| s.parse().ok())
-}
-
-fn parse_claude_plugin_list_with_available(
-    output: &str,
-) -> Result<ClaudePluginListWithAvailable> {
-    let cleaned = strip_terminal_escapes(output);
-
-    if let Ok(value) = serde_json::from_str::<serde_json::Value>(&cleaned) {
-        if json_has_plugin_list_fields(&value) {
-            return serde_json::from_value(value).context("JSON 解析失败");
-        }
-    }
-
-    if let Some(value) = find_json_value_with_predicate(&cleaned, json_has_plugin_list_fields) {
-        return serde_json::from_value(value).context("JSON 解析失败");
-    }
-
-    parse_json_output(&cleaned).context("JSON 解析失败")
-}
-
-fn parse_json_output<T: for<'de> Deserialize<'de>>(output: &str) -> Result<T> {
-    let cleaned = strip_terminal_escapes(output);
-    // 1) 优先直接解析（输出本身就是纯 JSON 的情况）
-    if let Ok(value) = serde_json::from_str::<T>(&cleaned) {
-        return Ok(value);
-    }
-
-    // 2) 兼容：输出前后混有提示符/日志/ANSI 等，尝试提取一个完整 JSON 值并解析
-    if let Ok(value) = parse_first_json_value::<T>(&cleaned) {
-        return Ok(value);
-    }
-
-    // 3) 兜底：旧逻辑（按首尾括号截取），有助于处理一些更“干净但带前缀”的输出
-    let payload = extract_json_payload(&cleaned).unwrap_or(cleaned.as_str());
-    serde_json::from_str(payload).context("JSON 解析失败")
+    let home = dirs::home_dir()?;
+    Some(
+        home.join(".claude")
+            .join("plugins")
+            .join("marketplaces")
+            .join(name)
+            .to_string_lossy()
+            .to_string(),
+    )
 }
 
-fn extract_json_payload(output: &str) -> Option<&str> {
-    let start = output.find(|c| c == '{' || c == '[')?;
-    let end = output.rfind(|c| c == '}' || c == ']')?;
-    if end < start {
+fn default_local_plugin_install_location(name: &str) -> Option<String> {
+    if name.trim().is_empty() {
         return None;
     }
-    Some(&output[start..=end])
-}
-
-fn parse_first_json_value<T: for<'de> Deserialize<'de>>(output: &str) -> Result<T> {
-    // 通过 serde_json 的流式反序列化能力，从任意位置尝试解析出“第一个匹配的 JSON 值”
-    // 这样可以兼容 PowerShell/Terminal 的提示符、以及 CLI 可能输出的非 JSON 文本或日志。
-    let bytes = output.as_bytes();
-    let mut pos = 0;
-
-    while pos < bytes.len() {
-        let offset = match bytes[pos..].iter().position(|b| *b == b'{' || *b == b'[') {
-            Some(value) => value,
-            None => break,
-        };
-        let start = pos + offset;
-        let slice = &output[start..];
-        let mut stream = serde_json::Deserializer::from_str(slice).into_iter::<serde_json::Value>();
-        let value = match stream.next() {
-            Some(Ok(v)) => v,
-            _ => {
-                pos = start + 1;
-                continue;
-            }
-        };
-
-        let end = stream.byte_offset();
-        if end == 0 || end > slice.len() {
-            pos = start + 1;
-            continue;
-        }
-
-        // 只取 JSON 值本体，忽略后续的任何噪声输出
-        let payload = &slice[..end];
-        match serde_json::from_str::<T>(payload)
-            .or_else(|_| serde_json::from_value::<T>(value))
-        {
-            Ok(parsed) => return Ok(parsed),
-            Err(_) => {
-                pos = start + end;
-                continue;
-            }
-        }
-    }
-
-    anyhow::bail!("JSON 解析失败");
-}
-
-fn json_has_plugin_list_fields(value: &serde_json::Value) -> bool {
-    let Some(obj) = value.as_object() else {
-        return false;
-    };
-
-    obj.contains_key("installed")
-        || obj.contains_key("available")
-        || obj.contains_key("installedPlugins")
-        || obj.contains_key("availablePlugins")
-}
-
-fn find_json_value_with_predicate<F>(output: &str, predicate: F) -> Option<serde_json::Value>
-where
-    F: Fn(&serde_json::Value) -> bool,
-{
-    let bytes = output.as_bytes();
-    let mut pos = 0;
-
-    while pos < bytes.len() {
-        let offset = match bytes[pos..].iter().position(|b| *b == b'{' || *b == b'[') {
-            Some(value) => value,
-            None => break,
-        };
-        let start = pos + offset;
-        let slice = &output[start..];
-        let mut stream = serde_json::Deserializer::from_str(slice).into_iter::<serde_json::Value>();
-        let value = match stream.next() {
-            Some(Ok(v)) => v,
-            _ => {
-                pos = start + 1;
-                continue;
-            }
-        };
-
-        let end = stream.byte_offset();
-        if end == 0 || end > slice.len() {
-            pos = start + 1;
-            continue;
-        }
-
-        if predicate(&value) {
-            return Some(value);
-        }
-
-        pos = start + end;
-    }
-
-    None
-}
-
-fn strip_terminal_escapes(input: &str) -> String {
-    let mut out = String::with_capacity(input.len());
-    let chars: Vec<char> = input.chars().collect();
-    let mut i = 0;
-
-    while i < chars.len() {
-        let ch = chars[i];
-        if ch == '\u{001b}' {
-            // CSI: ESC [
-            if i + 1 < chars.len() && chars[i + 1] == '[' {
-                i += 2;
-                while i < chars.len() {
-                    let c = chars[i];
-                    // CSI sequences typically end with a byte in @..~
-                    if ('@'..='~').contains(&c) {
-                        i += 1;
-                        break;
-                    }
-                    i += 1;
-                }
-                continue;
-            }
-
-            // OSC: ESC ]
-            if i + 1 < chars.len() && chars[i + 1] == ']' {
-                i += 2;
-                while i < chars.len() {
-                    let c = chars[i];
-                    // BEL ends OSC
-                    if c == '\u{0007}' {
-                        i += 1;
-                        break;
-                    }
-                    // ST ends OSC: ESC \
-                    if c == '\u{001b}' && i + 1 < chars.len() && chars[i + 1] == '\\' {
-                        i += 2;
-                        break;
-                    }
-                    i += 1;
-                }
-                continue;
-            }
-
-            // Other ESC sequences: best-effort skip next char
-            i += 1;
-            if i < chars.len() {
-                i += 1;
-            }
-            continue;
-        }
-
-        out.push(ch);
-        i += 1;
-    }
-
-    out
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn parse_claude_plugin_list_with_available_from_powershell_output() {
-        let output = r#"PS C:\Users\Bruce> claude plugin list --json --available
-{
-  "installed": [
-    {
-      "id": "superpowers@superpowers-marketplace",
-      "version": "4.0.3",
-      "scope": "user",
-      "enabled": false,
-      "installPath": "C:\\Users\\Bruce\\.claude\\plugins\\cache\\superpowers-marketplace\\superpowers\\4.0.3",
-      "installedAt": "2025-12-26T01:58:19.521Z",
-      "lastUpdated": "2026-01-14T01:51:11.830Z"
-    }
-  ],
-  "available": [
-    {
-      "pluginId": "superpowers@claude-plugins-official",
-      "name": "superpowers",
-      "marketplaceName": "claude-plugins-official",
-      "version": "4.0.4",
-      "source": {
-        "source": "url",
-        "url": "https://github.com/obra/superpowers.git"
-      },
-      "installCount": 123
-    }
-  ]
+    let home = dirs::home_dir()?;
+    Some(
+        home.join(".claude")
+            .join("plugins")
+            .join("local")
+            .join(name)
+            .to_string_lossy()
+            .to_string(),
+    )
 }
-PS C:\Users\Bruce> "#;
-
-        let payload: ClaudePluginListWithAvailable = parse_json_output(output).unwrap();
-        assert_eq!(payload.installed.len(), 1);
-        assert_eq!(payload.installed[0].id, "superpowers@superpowers-marketplace");
-        assert_eq!(payload.available.len(), 1);
-        assert_eq!(payload.available[0].plugin_id, "superpowers@claude-plugins-official");
-        assert_eq!(
-            payload.available[0].marketplace_name.as_deref(),
-            Some("claude-plugins-official")
-        );
-        assert_eq!(payload.available[0].version.as_deref(), Some("4.0.4"));
-    }
 
-    #[test]
-    fn parse_claude_plugin_list_with_available_accepts_snake_case_fields() {
-        let output = r#"
-noise before json...
-{
-  "installed": [
-    {
-      "id": "foo@bar",
-      "version": "1.0.0",
-      "install_path": "/Users/a/.claude/plugins/cache/bar/foo/1.0.0",
-      "installed_at": "2026-01-01T00:00:00Z",
-      "last_updated": "2026-01-02T00:00:00Z"
-    }
-  ],
-  "available": [
-    {
-      "plugin_id": "foo@bar",
-      "marketplace_name": "bar",
-      "version": "1.0.1"
+/// 把本地插件目录落地到 `target`：Unix 下创建软链接指向 `source`，这样开发者
+/// 修改源码后无需重新安装即可通过「重新扫描」看到最新结果；其他平台没有无需
+/// 权限的软链接 API，退化为整目录复制。
+fn link_or_copy_local_plugin(source: &Path, target: &Path) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("无法创建目标目录: {:?}", parent))?;
     }
-  ]
-}
-noise after json..."#;
 
-        let payload: ClaudePluginListWithAvailable = parse_json_output(output).unwrap();
-        assert_eq!(payload.installed.len(), 1);
-        assert_eq!(payload.installed[0].install_path.as_deref(), Some("/Users/a/.claude/plugins/cache/bar/foo/1.0.0"));
-        assert_eq!(payload.available.len(), 1);
-        assert_eq!(payload.available[0].plugin_id, "foo@bar");
-        assert_eq!(payload.available[0].marketplace_name.as_deref(), Some("bar"));
-        assert_eq!(payload.available[0].version.as_deref(), Some("1.0.1"));
+    if target.is_symlink() || target.is_file() {
+        std::fs::remove_file(target)
+            .with_context(|| format!("无法清理已存在的安装路径: {:?}", target))?;
+    } else if target.is_dir() {
+        std::fs::remove_dir_all(target)
+            .with_context(|| format!("无法清理已存在的安装路径: {:?}", target))?;
     }
 
-    #[test]
-    fn parse_claude_plugin_list_with_available_skips_unrelated_json() {
-        let output = r#"
-{"event":"progress","message":"fetching"}
-{
-  "installed": [
+    #[cfg(unix)]
     {
-      "id": "sample@market",
-      "version": "1.0.0"
+        std::os::unix::fs::symlink(source, target)
+            .with_context(|| format!("无法创建软链接: {:?} -> {:?}", target, source))?;
     }
-  ],
-  "available": [
+    #[cfg(not(unix))]
     {
-      "pluginId": "sample@market",
-      "marketplaceName": "market",
-      "version": "1.1.0"
+        copy_dir_recursive(source, target)
+            .with_context(|| format!("无法复制插件目录: {:?} -> {:?}", source, target))?;
     }
-  ]
-}
-"#;
 
-        let payload = parse_claude_plugin_list_with_available(output).unwrap();
-        assert_eq!(payload.installed.len(), 1);
-        assert_eq!(payload.installed[0].id, "sample@market");
-        assert_eq!(payload.available.len(), 1);
-        assert_eq!(payload.available[0].plugin_id, "sample@market");
-        assert_eq!(payload.available[0].version.as_deref(), Some("1.1.0"));
-    }
+    Ok(())
 }
 
-fn parse_marketplace_list_text(output: &str) -> Vec<ClaudeMarketplace> {
-    let cleaned = strip_terminal_escapes(output);
-    let mut results: Vec<ClaudeMarketplace> = Vec::new();
-    let mut current_index: Option<usize> = None;
-
-    for raw_line in cleaned.lines() {
-        let line = raw_line.trim_end();
-        let trimmed = line.trim_start();
-
-        if let Some(rest) = trimmed.strip_prefix('>') {
-            let name = rest.trim().to_string();
-            if name.is_empty() {
-                continue;
-            }
-            let install_location = default_marketplace_install_location(&name);
-            results.push(ClaudeMarketplace {
-                name,
-                source: None,
-                repo: None,
-                repository_url: None,
-                install_location,
-            });
-            current_index = Some(results.len() - 1);
-            continue;
-        }
-
-        let Some(idx) = current_index else { continue };
-        if !trimmed.to_lowercase().starts_with("source:") {
-            continue;
-        }
-
-        // Example:
-        // Source: GitHub (anthropics/claude-plugins-official)
-        // Source: URL (https://...)
-        let after = trimmed.splitn(2, ':').nth(1).unwrap_or("").trim();
-        if after.is_empty() {
-            continue;
-        }
-
-        let (source_text, paren) = match after.split_once('(') {
-            Some((a, b)) => (a.trim(), Some(b.trim_end_matches(')').trim())),
-            None => (after.trim(), None),
-        };
-
-        if !source_text.is_empty() {
-            results[idx].source = Some(source_text.to_string());
-        }
-
-        if let Some(value) = paren {
-            if !value.is_empty() {
-                // GitHub: owner/repo; URL/Local: value as-is
-                results[idx].repo = Some(value.to_string());
-                results[idx].repository_url = if value.starts_with("http://") || value.starts_with("https://") {
-                    Some(value.to_string())
-                } else if value.contains('/') {
-                    Some(format!("https://github.com/{}", value))
-                } else {
-                    None
-                };
-            }
+#[cfg(not(unix))]
+fn copy_dir_recursive(source: &Path, target: &Path) -> Result<()> {
+    std::fs::create_dir_all(target)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let from = entry.path();
+        let to = target.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_recursive(&from, &to)?;
+        } else {
+            std::fs::copy(&from, &to)?;
         }
     }
-
-    results
-}
-
-fn default_marketplace_install_location(name: &str) -> Option<String> {
-    if name.trim().is_empty() {
-        return None;
-    }
-    let home = dirs::home_dir()?;
-    Some(
-        home.join(".claude")
-            .join("plugins")
-            .join("marketplaces")
-            .join(name)
-            .to_string_lossy()
-            .to_string(),
-    )
+    Ok(())
 }
 
-fn git_output(args: &[&str]) -> Result<String> {
+/// 执行一次 `git` 命令并返回其标准输出；供 [`crate::commands::plugins`]
+/// 做基于 git HEAD 的增量扫描复用，避免重复实现同样的子进程调用。
+pub(crate) fn git_output(args: &[&str]) -> Result<String> {
     let mut cmd = Command::new("git");
     cmd.args(args);
     #[cfg(windows)]
@@ -1925,152 +2401,64 @@ fn git_output(args: &[&str]) -> Result<String> {
     Ok(String::from_utf8_lossy(&out.stdout).to_string())
 }
 
-fn parse_plugin_update_output(output: &str) -> String {
-    let text = output.to_lowercase();
-    if text.contains("already") && text.contains("latest") {
-        return "already_latest".to_string();
-    }
-    if text.contains("success") && text.contains("updated") {
-        return "updated".to_string();
-    }
-    if text.contains("updated") {
-        return "updated".to_string();
-    }
-    "failed".to_string()
-}
-
-fn parse_marketplace_update_output(output: &str) -> bool {
-    let text = output.to_lowercase();
-    text.contains("successfully updated marketplace")
-        || (text.contains("updated") && text.contains("marketplace") && !text.contains("failed"))
-        || text.contains("already up to date")
-}
-
-#[derive(Debug)]
-struct CommandOutcome {
-    success: bool,
-    already: bool,
-}
-
-fn parse_marketplace_add_output(output: &str) -> CommandOutcome {
-    let text = output.to_lowercase();
-
-    // 检查是否已存在（优先判断，因为 Claude Code 输出可能是 "Failed to add: already installed"）
-    let already = text.contains("already") && (text.contains("marketplace") || text.contains("exists") || text.contains("added") || text.contains("installed"));
+/// 探测一个外部 CLI（`claude` 或 `git`）：能否通过 `which` 找到可执行文件，
+/// 以及 `{command} {version_args}` 是否能正常返回版本号，供 [`PluginManager::diagnostics`] 汇总
+fn probe_cli_version(label: &str, command: &str, version_args: &[&str]) -> DiagnosticCheck {
+    let Ok(path) = which(command) else {
+        return DiagnosticCheck::fail(label, format!("未找到可执行文件: {}", command));
+    };
 
-    // 如果已存在，直接视为成功
-    if already {
-        return CommandOutcome { success: true, already: true };
+    match Command::new(command).args(version_args).output() {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            let version = if version.is_empty() { "未知版本".to_string() } else { version };
+            DiagnosticCheck::pass(label, format!("{} ({})", version, path.display()))
+        }
+        Ok(_) => DiagnosticCheck::warn(
+            label,
+            format!("{} 存在，但 {} 返回非零状态", path.display(), version_args.join(" ")),
+        ),
+        Err(e) => DiagnosticCheck::warn(label, format!("{} 存在，但无法执行: {}", path.display(), e)),
     }
-
-    // 检查是否有明确的失败信息
-    let has_error = text.contains("error")
-        || text.contains("failed")
-        || text.contains("failure")
-        || text.contains("unable to")
-        || text.contains("could not");
-
-    // 检查成功情况（排除错误情况）
-    let success = !has_error && (
-        text.contains("marketplace added")
-        || text.contains("added marketplace")
-        || text.contains("successfully added")
-        || (text.contains("marketplace") && text.contains("added") && !text.contains("not added"))
-    );
-
-    CommandOutcome { success, already: false }
 }
 
-fn parse_plugin_install_output(output: &str) -> CommandOutcome {
-    let text = output.to_lowercase();
-
-    // 检查是否有明确的失败信息
-    let has_error = text.contains("error")
-        || text.contains("failed")
-        || text.contains("failure")
-        || text.contains("unable to")
-        || text.contains("could not");
-
-    // 检查是否未安装（否定）
-    let not_installed = text.contains("not installed") || text.contains("not found");
-
-    // 检查是否已存在
-    let already = text.contains("already installed") || text.contains("already exists");
-
-    // 检查成功情况（排除错误和否定情况）
-    let success = !has_error && !not_installed && (
-        already
-        || text.contains("successfully installed")
-        || text.contains("installation complete")
-        || text.contains("install success")
-        || text.contains("plugin installed")
-        // 只有当 "installed" 不是在否定上下文中出现时才算成功
-        || (text.contains("installed") && !text.contains("not installed") && !text.contains("isn't installed"))
-    );
-
-    CommandOutcome { success, already }
-}
-
-fn parse_plugin_uninstall_output(output: &str) -> CommandOutcome {
-    let text = output.to_lowercase();
-
-    // 检查是否本来就未安装（可视为"成功"卸载）
-    // 优先检查这个，因为 "not found" 比一般错误更具体
-    let not_installed = text.contains("not installed")
-        || text.contains("not found")
-        || text.contains("doesn't exist")
-        || (text.contains("not found") && text.contains("installed plugins"));
-
-    // 检查是否有明确的失败信息（排除 "not found" 的情况）
-    let has_error = !not_installed && (
-        text.contains("error")
-        || text.contains("failed")
-        || text.contains("failure")
-        || text.contains("unable to")
-        || text.contains("could not")
-    );
-
-    // 检查成功情况
-    let success = !has_error && (
-        not_installed  // 本来就不存在，视为成功
-        || text.contains("successfully uninstalled")
-        || text.contains("uninstall success")
-        || text.contains("plugin uninstalled")
-        || text.contains("removed")
-        || (text.contains("uninstalled") && !text.contains("not uninstalled"))
-    );
-
-    CommandOutcome { success, already: not_installed }
-}
-
-fn parse_marketplace_remove_output(output: &str) -> CommandOutcome {
-    let text = output.to_lowercase();
-
-    // 检查是否本来就不存在（可视为"成功"移除）
-    let not_found = text.contains("not found")
-        || text.contains("doesn't exist")
-        || (text.contains("marketplace") && text.contains("not found"));
-
-    // 检查是否有明确的失败信息（排除 "not found" 的情况）
-    let has_error = !not_found && (
-        text.contains("error")
-        || text.contains("failed")
-        || text.contains("failure")
-        || text.contains("unable to")
-        || text.contains("could not")
-    );
+/// 探测单个 marketplace 的远端可达性：对其 `repo` 做一次 `git ls-remote HEAD`
+fn probe_marketplace_reachability(mp: &ClaudeMarketplace) -> MarketplaceReachability {
+    let Some(repo) = mp.repo.as_deref().map(str::trim).filter(|r| !r.is_empty()) else {
+        return MarketplaceReachability {
+            name: mp.name.clone(),
+            repo: mp.repo.clone(),
+            status: DiagnosticStatus::Warn,
+            detail: "未知 repo，无法探测远端可达性".to_string(),
+        };
+    };
 
-    // 检查成功情况
-    let success = !has_error && (
-        not_found  // 本来就不存在，视为成功
-        || text.contains("successfully removed")
-        || text.contains("marketplace removed")
-        || text.contains("removed marketplace")
-        || text.contains("uninstalled")
-        || (text.contains("removed") && !text.contains("not removed"))
-    );
+    let remote_url = if repo.starts_with("http://") || repo.starts_with("https://") {
+        repo.to_string()
+    } else {
+        format!("https://github.com/{}.git", repo)
+    };
 
-    CommandOutcome { success, already: not_found }
+    match git_output(&["ls-remote", &remote_url, "HEAD"]) {
+        Ok(output) if !output.trim().is_empty() => MarketplaceReachability {
+            name: mp.name.clone(),
+            repo: mp.repo.clone(),
+            status: DiagnosticStatus::Pass,
+            detail: format!("可达: {}", remote_url),
+        },
+        Ok(_) => MarketplaceReachability {
+            name: mp.name.clone(),
+            repo: mp.repo.clone(),
+            status: DiagnosticStatus::Warn,
+            detail: format!("`git ls-remote HEAD` 返回空结果: {}", remote_url),
+        },
+        Err(e) => MarketplaceReachability {
+            name: mp.name.clone(),
+            repo: mp.repo.clone(),
+            status: DiagnosticStatus::Fail,
+            detail: format!("无法访问 {}: {}", remote_url, e),
+        },
+    }
 }
 
 fn read_marketplace_manifest(repo_root: &Path) -> Result<Option<MarketplaceManifest>> {
@@ -2088,7 +2476,7 @@ fn read_marketplace_manifest(repo_root: &Path) -> Result<Option<MarketplaceManif
     Ok(Some(manifest))
 }
 
-fn read_plugin_manifest(source_path: &Path) -> Result<PluginManifest> {
+pub(crate) fn read_plugin_manifest(source_path: &Path) -> Result<PluginManifest> {
     let manifest_path = source_path.join(".claude-plugin").join("plugin.json");
     let content = std::fs::read_to_string(&manifest_path)
         .with_context(|| format!("无法读取 plugin.json: {:?}", manifest_path))?;
@@ -2115,6 +2503,53 @@ fn normalize_source(source: &str) -> String {
     }
 }
 
+/// 已知的生命周期钩子阶段，对应安装/卸载流程前后各一个时机
+const LIFECYCLE_HOOK_PHASES: &[&str] = &["preinstall", "postinstall", "preuninstall", "postuninstall"];
+
+/// 扫描 `manifest.hooks` 里声明的生命周期钩子脚本：路径经 [`resolve_source_path`]
+/// 同样的 `../` 转义校验，指向 `source_path` 目录内确实存在的文件就记一条
+/// `ProcessExecution` 级别的 issue——这类脚本会在安装/卸载时自动执行，风险明显
+/// 高于普通静态扫描命中的可疑代码片段，值得单独标注出来供用户判断。
+fn scan_lifecycle_hook_issues(source_path: &Path, manifest: Option<&PluginManifest>) -> Vec<SecurityIssue> {
+    let Some(hooks) = manifest.and_then(|m| m.hooks.as_ref()) else {
+        return Vec::new();
+    };
+
+    let mut issues = Vec::new();
+    for (phase, script) in hooks {
+        if !LIFECYCLE_HOOK_PHASES.contains(&phase.as_str()) {
+            continue;
+        }
+        match resolve_source_path(source_path, script) {
+            Ok(resolved) if resolved.exists() => {
+                issues.push(SecurityIssue {
+                    severity: IssueSeverity::Error,
+                    category: IssueCategory::ProcessExecution,
+                    description: format!(
+                        "声明了 {} 生命周期钩子，安装/卸载时会自动执行该脚本，无需用户二次确认",
+                        phase
+                    ),
+                    line_number: None,
+                    code_snippet: None,
+                    file_path: Some(script.clone()),
+                });
+            }
+            Ok(_) => {}
+            Err(e) => {
+                issues.push(SecurityIssue {
+                    severity: IssueSeverity::Error,
+                    category: IssueCategory::ProcessExecution,
+                    description: format!("{} 钩子脚本路径不合法: {}", phase, e),
+                    line_number: None,
+                    code_snippet: None,
+                    file_path: Some(script.clone()),
+                });
+            }
+        }
+    }
+    issues
+}
+
 fn resolve_source_path(repo_root: &Path, source: &str) -> Result<PathBuf> {
     let normalized = normalize_source(source);
     if normalized == "." {
@@ -2134,7 +2569,7 @@ fn resolve_source_path(repo_root: &Path, source: &str) -> Result<PathBuf> {
     Ok(repo_root.join(relative))
 }
 
-fn find_repo_root(extract_dir: &Path) -> Result<PathBuf> {
+pub(crate) fn find_repo_root(extract_dir: &Path) -> Result<PathBuf> {
     for entry in std::fs::read_dir(extract_dir)
         .context("无法读取解压目录")?
     {
@@ -2147,16 +2582,133 @@ fn find_repo_root(extract_dir: &Path) -> Result<PathBuf> {
     anyhow::bail!("未找到仓库根目录")
 }
 
-fn resolve_marketplace_plugins(
+/// 把磁盘上某一个目录直接当成单个 plugin 的源码来解析：读取（若存在）
+/// `.claude-plugin/plugin.json` 补全 name/description/version/author，目录名
+/// 兜底作为 name。供 [`crate::services::plugin_source::LocalPathBackend`]/
+/// [`crate::services::plugin_source::GitUrlBackend`] 复用；
+/// [`PluginManager::prepare_local_plugin_installation`] 不调用它，仍内联自己的
+/// name/description/version/author 解析（不设置 dependencies/capabilities）。
+pub(crate) fn single_plugin_from_dir(
+    source: &Path,
+    repository_url: &str,
+    marketplace_name: &str,
+) -> Result<ResolvedPlugin> {
+    let manifest = read_plugin_manifest(source).ok();
+    let name = manifest
+        .as_ref()
+        .map(|m| m.name.clone())
+        .or_else(|| source.file_name().map(|n| n.to_string_lossy().to_string()))
+        .context("无法确定插件名称")?;
+
+    let mut plugin = Plugin::new(
+        name,
+        repository_url.to_string(),
+        marketplace_name.to_string(),
+        source.to_string_lossy().to_string(),
+    );
+    plugin.description = manifest.as_ref().and_then(|m| m.description.clone());
+    plugin.version = manifest.as_ref().and_then(|m| m.version.clone());
+    plugin.author = manifest
+        .as_ref()
+        .and_then(|m| m.author.as_ref().and_then(|a| a.to_display()));
+    plugin.dependencies = manifest.as_ref().and_then(|m| m.dependencies.clone());
+    plugin.capabilities = manifest
+        .as_ref()
+        .and_then(|m| m.permissions.clone())
+        .map(PluginCapabilities::from);
+
+    Ok(ResolvedPlugin { plugin, source_path: source.to_path_buf() })
+}
+
+/// 没有 marketplace.json 时，兜底用来给发现到的插件命名 marketplace 的名字：
+/// repo 根目录名，再兜底成 `"unknown"`
+fn fallback_marketplace_name(repo_root: &Path) -> String {
+    repo_root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// 递归遍历 `repo_root`，把每一个包含 `.claude-plugin/plugin.json` 的目录都
+/// 当作一个独立插件解析（移植自 cargo `read_packages` 的目录发现策略）——
+/// 很多仓库并不维护 marketplace.json，只是把插件散落在子目录里。命中插件目录
+/// 后不再往其子孙目录继续找（嵌套插件不是预期形态，也避免把插件自身依赖目录
+/// 误判成插件）；跳过点号开头的子目录（`.git` 等）。单个目录的 manifest 解析
+/// 失败不会中断整个遍历，`strict` 为真时汇总这些错误并整体返回失败，否则只是
+/// 跳过该目录。
+fn discover_plugins_by_walk(
     repo_root: &Path,
     repo_url: &str,
+    marketplace_name: &str,
     strict: bool,
 ) -> Result<Vec<ResolvedPlugin>> {
-    let manifest = read_marketplace_manifest(repo_root)?
-        .context("未找到 marketplace.json，无法自动安装插件")?;
+    let mut resolved = Vec::new();
+    let mut errors = Vec::new();
+    let mut pending = vec![repo_root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let is_hidden = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with('.'))
+                .unwrap_or(false);
+            if is_hidden {
+                continue;
+            }
+
+            if path.join(".claude-plugin").join("plugin.json").exists() {
+                match read_plugin_manifest(&path) {
+                    Ok(manifest) => {
+                        let relative = path.strip_prefix(repo_root).unwrap_or(&path);
+                        let source = relative.to_string_lossy().replace('\\', "/");
+                        let mut plugin = Plugin::new(
+                            manifest.name.clone(),
+                            repo_url.to_string(),
+                            marketplace_name.to_string(),
+                            source,
+                        );
+                        plugin.description = manifest.description.clone();
+                        plugin.version = manifest.version.clone();
+                        plugin.author = manifest.author.as_ref().and_then(|a| a.to_display());
+                        plugin.dependencies = manifest.dependencies.clone();
+                        plugin.capabilities = manifest.permissions.clone().map(PluginCapabilities::from);
+                        resolved.push(ResolvedPlugin { plugin, source_path: path });
+                    }
+                    Err(e) => errors.push(format!("{}: {}", path.to_string_lossy(), e)),
+                }
+                continue;
+            }
+
+            pending.push(path);
+        }
+    }
+
+    if strict && !errors.is_empty() {
+        anyhow::bail!("以下目录的 plugin.json 解析失败:\n{}", errors.join("\n"));
+    }
+
+    Ok(resolved)
+}
+
+pub(crate) fn resolve_marketplace_plugins(
+    repo_root: &Path,
+    repo_url: &str,
+    strict: bool,
+) -> Result<Vec<ResolvedPlugin>> {
+    let manifest = read_marketplace_manifest(repo_root)?;
+    let marketplace_name = manifest
+        .as_ref()
+        .map(|m| m.name.clone())
+        .unwrap_or_else(|| fallback_marketplace_name(repo_root));
 
     let mut resolved = Vec::new();
-    for entry in manifest.plugins {
+    for entry in manifest.map(|m| m.plugins).unwrap_or_default() {
         let source = normalize_source(&entry.source);
         let source_path = resolve_source_path(repo_root, &source)?;
         if !source_path.exists() {
@@ -2181,7 +2733,7 @@ fn resolve_marketplace_plugins(
         let mut plugin = Plugin::new(
             name,
             repo_url.to_string(),
-            manifest.name.clone(),
+            marketplace_name.clone(),
             source.clone(),
         );
 
@@ -2197,13 +2749,223 @@ fn resolve_marketplace_plugins(
             .as_ref()
             .and_then(|m| m.author.as_ref().and_then(|a| a.to_display()))
             .or(entry.author.as_ref().and_then(|a| a.to_display()));
+        plugin.dependencies = plugin_manifest
+            .as_ref()
+            .and_then(|m| m.dependencies.clone())
+            .or(entry.dependencies.clone());
+        plugin.capabilities = plugin_manifest
+            .as_ref()
+            .and_then(|m| m.permissions.clone())
+            .map(PluginCapabilities::from);
 
         resolved.push(ResolvedPlugin { plugin, source_path });
     }
 
+    // marketplace.json 之外，再兜底递归发现散落在子目录里的插件；已经在上面
+    // 解析过的目录（按 source_path 去重）不重复加入
+    let mut seen: HashSet<PathBuf> = resolved.iter().map(|r| r.source_path.clone()).collect();
+    for discovered in discover_plugins_by_walk(repo_root, repo_url, &marketplace_name, strict)? {
+        if seen.insert(discovered.source_path.clone()) {
+            resolved.push(discovered);
+        }
+    }
+
+    if resolved.is_empty() {
+        anyhow::bail!("未找到 marketplace.json，且未在目录中发现任何插件");
+    }
+
     Ok(resolved)
 }
 
+/// [`order_resolved_plugins_for_install`] 发现的一个依赖问题：缺失的依赖，或
+/// 已找到依赖但版本不满足 `dependencies` 里声明的 semver 要求
+#[derive(Debug, Clone)]
+pub(crate) enum DependencyProblem {
+    Missing {
+        plugin: String,
+        dependency: String,
+    },
+    VersionConflict {
+        plugin: String,
+        dependency: String,
+        requirement: String,
+        found: String,
+    },
+}
+
+impl DependencyProblem {
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            DependencyProblem::Missing { plugin, dependency } => {
+                format!("{} 依赖的 {} 未找到", plugin, dependency)
+            }
+            DependencyProblem::VersionConflict { plugin, dependency, requirement, found } => {
+                format!(
+                    "{} 依赖的 {} 要求版本 {}，但当前是 {}",
+                    plugin, dependency, requirement, found
+                )
+            }
+        }
+    }
+}
+
+/// [`order_resolved_plugins_for_install`] 的结果：按依赖关系排好序的安装列表，
+/// 以及排序过程中发现但未导致失败的依赖问题（缺失依赖 / 版本不兼容）
+#[derive(Debug, Default)]
+pub(crate) struct DependencyResolution {
+    pub(crate) ordered: Vec<ResolvedPlugin>,
+    pub(crate) problems: Vec<DependencyProblem>,
+}
+
+/// 检查 `found_version` 是否满足 `requirement`（semver 版本要求，如 `"^1.2"`）。
+/// `requirement` 本身无法解析成合法的 semver 要求时视为没有约束，直接放行——
+/// 避免 marketplace.json 里一个写错的 `dependencies` 值导致整体安装失败。
+fn check_dependency_version(requirement: &str, found_version: Option<&str>) -> bool {
+    let strip_v = |s: &str| s.trim().strip_prefix('v').unwrap_or(s.trim()).to_string();
+
+    let Ok(req) = VersionReq::parse(&strip_v(requirement)) else {
+        return true;
+    };
+    match found_version.and_then(|v| Version::parse(&strip_v(v)).ok()) {
+        Some(version) => req.matches(&version),
+        None => false,
+    }
+}
+
+/// 按依赖关系对本次要安装的 plugins 排序：依赖必须先于依赖它的 plugin 出现。
+/// 依赖边优先在传入的这一批 `resolved` 内部解析（用 [`Plugin::plugin_spec`] 或
+/// 同 marketplace 内的裸名匹配）；批内找不到时再到 `installed`（当前已安装的
+/// plugin）里按同样规则查找版本号做兼容性校验。用 Kahn 算法实现：反复取出
+/// 入度为 0 的节点，再把它的后继入度减一；结束后如果还有节点没被取出，说明
+/// 剩下的节点构成了一个环，这种情况下无法确定安装顺序，直接返回错误。
+/// 缺失依赖与版本冲突不会中断排序，而是收集进返回值的 `problems` 里，由调用方
+/// 根据是否 strict 决定拦截还是仅作警告。
+pub(crate) fn order_resolved_plugins_for_install(
+    resolved: Vec<ResolvedPlugin>,
+    installed: &[Plugin],
+) -> Result<DependencyResolution> {
+    let specs: HashMap<String, usize> = resolved
+        .iter()
+        .enumerate()
+        .map(|(idx, r)| (r.plugin.plugin_spec(), idx))
+        .collect();
+
+    let find_in_batch = |dep: &str| -> Option<usize> {
+        specs.get(dep).copied().or_else(|| {
+            // 同一批内允许只写裸名，但要求在这批里唯一，避免跨 marketplace 时认错依赖
+            let matches: Vec<usize> = resolved
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| !dep.contains('@') && r.plugin.name == *dep)
+                .map(|(i, _)| i)
+                .collect();
+            (matches.len() == 1).then(|| matches[0])
+        })
+    };
+
+    // successors[i]：i 安装完成后，哪些下标的 plugin 因此少了一个未满足依赖
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); resolved.len()];
+    let mut in_degree = vec![0usize; resolved.len()];
+    let mut problems = Vec::new();
+
+    for (idx, r) in resolved.iter().enumerate() {
+        for (dep, requirement) in r.plugin.dependencies.iter().flatten() {
+            if let Some(dep_idx) = find_in_batch(dep) {
+                if dep_idx != idx {
+                    successors[dep_idx].push(idx);
+                    in_degree[idx] += 1;
+                }
+                let found_version = resolved[dep_idx].plugin.version.as_deref();
+                if !check_dependency_version(requirement, found_version) {
+                    problems.push(DependencyProblem::VersionConflict {
+                        plugin: r.plugin.name.clone(),
+                        dependency: dep.clone(),
+                        requirement: requirement.clone(),
+                        found: found_version.unwrap_or("未知").to_string(),
+                    });
+                }
+                continue;
+            }
+
+            // 批外依赖不参与排序，只用已安装的 plugin 做版本校验
+            match installed.iter().find(|p| p.installed && dependency_matches(dep, p)) {
+                Some(found) if !check_dependency_version(requirement, found.version.as_deref()) => {
+                    problems.push(DependencyProblem::VersionConflict {
+                        plugin: r.plugin.name.clone(),
+                        dependency: dep.clone(),
+                        requirement: requirement.clone(),
+                        found: found.version.clone().unwrap_or_else(|| "未知".to_string()),
+                    });
+                }
+                Some(_) => {}
+                None => problems.push(DependencyProblem::Missing {
+                    plugin: r.plugin.name.clone(),
+                    dependency: dep.clone(),
+                }),
+            }
+        }
+    }
+
+    let mut queue: VecDeque<usize> = in_degree
+        .iter()
+        .enumerate()
+        .filter(|(_, deg)| **deg == 0)
+        .map(|(idx, _)| idx)
+        .collect();
+
+    let mut order = Vec::with_capacity(resolved.len());
+    while let Some(idx) = queue.pop_front() {
+        order.push(idx);
+        for &next in &successors[idx] {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    if order.len() != resolved.len() {
+        let ordered: HashSet<usize> = order.iter().copied().collect();
+        let cycle_members = resolved
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !ordered.contains(idx))
+            .map(|(_, r)| r.plugin.name.clone())
+            .collect::<Vec<_>>()
+            .join(", ");
+        anyhow::bail!("插件依赖关系存在环，无法确定安装顺序: {}", cycle_members);
+    }
+
+    let mut slots: Vec<Option<ResolvedPlugin>> = resolved.into_iter().map(Some).collect();
+    let ordered = order.into_iter().map(|idx| slots[idx].take().unwrap()).collect();
+    Ok(DependencyResolution { ordered, problems })
+}
+
+/// `dep`（来自某个 plugin 的 `dependencies` 列表）是否指向 `plugin`：要么精确
+/// 匹配 `plugin_spec()`（`name@marketplace`），要么 `dep` 是裸名且与 `plugin.name`
+/// 相同（裸名只在同一 marketplace 内的依赖里使用）
+fn dependency_matches(dep: &str, plugin: &Plugin) -> bool {
+    dep == plugin.plugin_spec() || (!dep.contains('@') && dep == plugin.name)
+}
+
+/// 当前已安装、且在 `dependencies` 中引用了 `plugin` 的那些 plugin。
+/// `exclude_marketplace` 非空时忽略该 marketplace 内部的依赖者。
+fn installed_dependents<'a>(
+    plugin: &Plugin,
+    all_plugins: &'a [Plugin],
+    exclude_marketplace: Option<&str>,
+) -> Vec<&'a Plugin> {
+    all_plugins
+        .iter()
+        .filter(|candidate| candidate.installed && candidate.id != plugin.id)
+        .filter(|candidate| exclude_marketplace != Some(candidate.marketplace_name.as_str()))
+        .filter(|candidate| {
+            candidate.dependencies.as_ref()
+                .is_some_and(|deps| deps.keys().any(|dep| dependency_matches(dep, plugin)))
+        })
+        .collect()
+}
+
 fn merge_reports(reports: &[(Plugin, SecurityReport)], marketplace_name: &str) -> SecurityReport {
     let mut issues = Vec::new();
     let mut hard_triggers = Vec::new();
@@ -2213,6 +2975,7 @@ fn merge_reports(reports: &[(Plugin, SecurityReport)], marketplace_name: &str) -
     let mut score = 100;
     let mut blocked = false;
     let mut partial_scan = false;
+    let mut capabilities = crate::models::security::CapabilityManifest::default();
 
     for (plugin, report) in reports {
         if report.score < score {
@@ -2248,8 +3011,22 @@ fn merge_reports(reports: &[(Plugin, SecurityReport)], marketplace_name: &str) -
         for rec in &report.recommendations {
             recommendations.insert(rec.clone());
         }
+
+        capabilities.filesystem_outside_skill |= report.capabilities.filesystem_outside_skill;
+        capabilities.network_access |= report.capabilities.network_access;
+        capabilities.shell_execution |= report.capabilities.shell_execution;
+        capabilities.env_var_access |= report.capabilities.env_var_access;
+        capabilities.details.extend(
+            report
+                .capabilities
+                .details
+                .iter()
+                .map(|d| format!("{}: {}", plugin.name, d)),
+        );
     }
 
+    let advisory_db_version = reports.iter().filter_map(|(_, r)| r.advisory_db_version).min();
+
     SecurityReport {
         skill_id: format!("marketplace::{}", marketplace_name),
         score,
@@ -2258,9 +3035,116 @@ fn merge_reports(reports: &[(Plugin, SecurityReport)], marketplace_name: &str) -
         recommendations: recommendations.into_iter().collect(),
         blocked,
         hard_trigger_issues: hard_triggers,
+        capabilities,
         scanned_files,
         partial_scan: partial_scan || !skipped_files.is_empty(),
         skipped_files,
+        advisory_db_version,
+    }
+}
+
+/// 把 [`PluginManager::guard_scan_or_bail`] 的结果写到 `plugin` 对应的三个
+/// guard 字段上，格式与 `security_issues` 的落库方式保持一致
+fn apply_guard_report(plugin: &mut Plugin, report: &PluginScanReport) {
+    plugin.manifest_digest = Some(report.manifest_digest.clone());
+    plugin.guard_risk_score = Some(report.risk_score);
+    plugin.guard_findings = Some(
+        report.findings.iter()
+            .map(|f| format!("[{}] {:?}: {}", f.file_path, f.severity, f.description))
+            .collect()
+    );
+}
+
+/// 把 [`PluginManager::run_scanner_backends`] 收集到的各外部扫描器报告合并进
+/// 内置扫描器产出的 `primary` 报告：等级取两者中最差的一个（见
+/// [`SecurityLevel`] 的 `Ord`），分数取较低者，issue/建议按 `[backend_id]`
+/// 前缀、已扫描文件按 `backend_id:` 前缀归并，避免和内置扫描器的结果混在一起
+/// 分不清来源。
+fn merge_backend_reports(mut primary: SecurityReport, backend_reports: Vec<(String, SecurityReport)>) -> SecurityReport {
+    for (backend_id, report) in backend_reports {
+        if report.score < primary.score {
+            primary.score = report.score;
+        }
+        if report.level > primary.level {
+            primary.level = report.level;
+        }
+        if report.blocked {
+            primary.blocked = true;
+        }
+        if report.partial_scan {
+            primary.partial_scan = true;
+        }
+
+        for issue in &report.issues {
+            let mut updated = issue.clone();
+            updated.description = format!("[{}] {}", backend_id, issue.description);
+            primary.issues.push(updated);
+        }
+        for item in &report.hard_trigger_issues {
+            primary.hard_trigger_issues.push(format!("[{}] {}", backend_id, item));
+        }
+        for rec in &report.recommendations {
+            primary.recommendations.push(format!("[{}] {}", backend_id, rec));
+        }
+        for file in &report.scanned_files {
+            primary.scanned_files.push(format!("{}:{}", backend_id, file));
+        }
+
+        primary.capabilities.filesystem_outside_skill |= report.capabilities.filesystem_outside_skill;
+        primary.capabilities.network_access |= report.capabilities.network_access;
+        primary.capabilities.shell_execution |= report.capabilities.shell_execution;
+        primary.capabilities.env_var_access |= report.capabilities.env_var_access;
+        primary.capabilities.details.extend(
+            report
+                .capabilities
+                .details
+                .iter()
+                .map(|d| format!("[{}] {}", backend_id, d)),
+        );
+    }
+
+    primary
+}
+
+/// [`write_resolved_lockfile`] 落盘的锁文件固定文件名，与 `repo_root` 放在一起
+const LOCKFILE_FILE_NAME: &str = "skills-guard.lock";
+
+/// `resolve_marketplace_plugins` 解析并扫描完一批 plugin 之后，把这次实际钉住
+/// 的内容写成 [`ResolvedPluginLockfile`] 落盘到 `repo_root/skills-guard.lock`，
+/// 供之后 [`PluginManager::get_lockfile_info`] 做 drift 检测。`reports` 与
+/// `merged_report` 来自同一次 [`prepare_plugin_installation`] 调用；写入失败
+/// 不影响安装本身，只记录日志
+fn write_resolved_lockfile(
+    repo_root: &Path,
+    reports: &[(Plugin, SecurityReport)],
+    merged_report: &SecurityReport,
+) {
+    let commit_sha = git_output(&["-C", &repo_root.to_string_lossy(), "rev-parse", "HEAD"]).ok();
+    let lockfile = ResolvedPluginLockfile {
+        generated_at: Utc::now(),
+        commit_sha,
+        blocked: merged_report.blocked,
+        partial_scan: merged_report.partial_scan,
+        plugins: reports
+            .iter()
+            .map(|(plugin, report)| ResolvedPluginLockEntry {
+                name: plugin.name.clone(),
+                version: plugin.version.clone(),
+                repo_url: plugin.repository_url.clone(),
+                marketplace: plugin.marketplace_name.clone(),
+                source: plugin.source.clone(),
+                security_score: report.score,
+            })
+            .collect(),
+    };
+
+    match serde_json::to_string_pretty(&lockfile) {
+        Ok(content) => {
+            if let Err(e) = std::fs::write(repo_root.join(LOCKFILE_FILE_NAME), content) {
+                log::warn!("写入锁文件失败: {}", e);
+            }
+        }
+        Err(e) => log::warn!("序列化锁文件失败: {}", e),
     }
 }
 
@@ -2280,3 +3164,104 @@ fn author_to_display(author: &FeaturedMarketplaceOwner) -> Option<String> {
         (None, None) => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin_with_deps(name: &str, marketplace: &str, deps: &[(&str, &str)]) -> Plugin {
+        let mut plugin = Plugin::new(
+            name.to_string(),
+            format!("github.com/acme/{}", name),
+            marketplace.to_string(),
+            "repository_scan".to_string(),
+        );
+        plugin.version = Some("1.0.0".to_string());
+        if !deps.is_empty() {
+            plugin.dependencies = Some(
+                deps.iter()
+                    .map(|(dep, req)| (dep.to_string(), req.to_string()))
+                    .collect(),
+            );
+        }
+        plugin
+    }
+
+    fn resolved(plugin: Plugin) -> ResolvedPlugin {
+        ResolvedPlugin {
+            source_path: PathBuf::from(format!("/tmp/{}", plugin.name)),
+            plugin,
+        }
+    }
+
+    #[test]
+    fn orders_dependency_before_dependent() {
+        // b depends on a；拓扑排序必须保证 a 先于 b 出现
+        let a = plugin_with_deps("a", "mp", &[]);
+        let b = plugin_with_deps("b", "mp", &[("a", "^1.0")]);
+        let resolved_plugins = vec![resolved(b), resolved(a)];
+
+        let result = order_resolved_plugins_for_install(resolved_plugins, &[]).unwrap();
+
+        let names: Vec<&str> = result.ordered.iter().map(|r| r.plugin.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert!(result.problems.is_empty());
+    }
+
+    #[test]
+    fn detects_dependency_cycle() {
+        // a -> b -> a 构成一个环，排序必须报错而不是静默丢弃成员
+        let a = plugin_with_deps("a", "mp", &[("b", "*")]);
+        let b = plugin_with_deps("b", "mp", &[("a", "*")]);
+
+        let result = order_resolved_plugins_for_install(vec![resolved(a), resolved(b)], &[]);
+
+        assert!(result.is_err());
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("环"));
+    }
+
+    #[test]
+    fn reports_missing_dependency_without_failing_the_batch() {
+        let a = plugin_with_deps("a", "mp", &[("missing", "^1.0")]);
+
+        let result = order_resolved_plugins_for_install(vec![resolved(a)], &[]).unwrap();
+
+        assert_eq!(result.ordered.len(), 1);
+        assert_eq!(result.problems.len(), 1);
+        assert!(matches!(
+            result.problems[0],
+            DependencyProblem::Missing { .. }
+        ));
+    }
+
+    #[test]
+    fn reports_version_conflict_against_already_installed_dependency() {
+        let mut installed_dep = plugin_with_deps("a", "mp", &[]);
+        installed_dep.installed = true;
+        installed_dep.version = Some("0.9.0".to_string());
+
+        let b = plugin_with_deps("b", "mp", &[("a", "^1.0")]);
+
+        let result = order_resolved_plugins_for_install(vec![resolved(b)], &[installed_dep]).unwrap();
+
+        assert_eq!(result.problems.len(), 1);
+        assert!(matches!(
+            result.problems[0],
+            DependencyProblem::VersionConflict { .. }
+        ));
+    }
+
+    #[test]
+    fn check_dependency_version_matches_semver_requirement() {
+        assert!(check_dependency_version("^1.2", Some("1.3.0")));
+        assert!(!check_dependency_version("^1.2", Some("2.0.0")));
+        assert!(!check_dependency_version("^1.2", None));
+    }
+
+    #[test]
+    fn check_dependency_version_allows_unparseable_requirement_through() {
+        // 写错的 dependencies 值不应该让整体安装失败，而是视为无约束放行
+        assert!(check_dependency_version("not-a-semver-requirement", Some("1.0.0")));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::services::plugin_manager::{
+    find_repo_root, git_output, resolve_marketplace_plugins, single_plugin_from_dir, ResolvedPlugin,
+};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 描述一次"从哪里拿到 plugin 源码"的请求；字段随 backend 不同而解释不同：
+/// - [`GithubMarketplaceBackend`] 把 `root` 当作已经下载好的 marketplace 仓库根目录；
+/// - [`LocalPathBackend`] 把 `root` 当作 plugin 本身所在的目录，忽略 `git_ref`；
+/// - [`GitUrlBackend`] 忽略 `root`，自己把 `repository_url` clone 到缓存目录，
+///   可选 `git_ref`（分支/tag/commit）决定 checkout 到哪个版本。
+pub struct PluginSourceRequest<'a> {
+    pub root: Option<&'a Path>,
+    pub repository_url: &'a str,
+    pub git_ref: Option<&'a str>,
+}
+
+/// plugin 内容来源的扩展点：原先「读取 manifest、定位插件目录」的逻辑和「这是一个
+/// Claude GitHub marketplace」绑死在一起；这个 trait 把它拆出来，使本地目录、
+/// 任意 git 仓库等其它来源可以复用同一套扫描/落库/安装流程。与
+/// [`crate::services::plugin_backend::PluginBackend`] 是两条正交的轴：那个 trait
+/// 管「装/卸/更新用哪个 CLI」，这个 trait 管「插件代码从哪来」。
+///
+/// `scan_cached_repository_plugins` 里对同一个 marketplace 仓库一次性解析出一批
+/// plugin 的逻辑没有经过这层抽象——那是一对多的解析，跟这里一个请求对应零或一个
+/// plugin 的形状不同，勉强统一反而会让两边都变复杂；这里只让
+/// [`GithubMarketplaceBackend`] 委托给同一份底层实现（[`resolve_marketplace_plugins`]），
+/// 避免出现两份互相漂移的解析逻辑。
+pub trait PluginSourceBackend: Send + Sync {
+    /// 存入 [`crate::models::Plugin::discovery_source`] 的标识
+    fn id(&self) -> &'static str;
+
+    /// 解析出该来源下可安装的 plugin 及其本地源码目录
+    fn resolve(&self, request: &PluginSourceRequest) -> Result<Vec<ResolvedPlugin>>;
+}
+
+/// 现有的 Claude GitHub marketplace 安装路径，包装成该 trait 的一个实现
+pub struct GithubMarketplaceBackend;
+
+impl PluginSourceBackend for GithubMarketplaceBackend {
+    fn id(&self) -> &'static str {
+        "repository_scan"
+    }
+
+    fn resolve(&self, request: &PluginSourceRequest) -> Result<Vec<ResolvedPlugin>> {
+        let root = request.root.context("marketplace backend 需要已下载好的仓库根目录")?;
+        let repo_root = find_repo_root(root)?;
+        resolve_marketplace_plugins(&repo_root, request.repository_url, false)
+    }
+}
+
+/// 直接从磁盘上的某个目录安装插件，跳过 marketplace add
+pub struct LocalPathBackend;
+
+impl PluginSourceBackend for LocalPathBackend {
+    fn id(&self) -> &'static str {
+        "local_path"
+    }
+
+    fn resolve(&self, request: &PluginSourceRequest) -> Result<Vec<ResolvedPlugin>> {
+        let source = request.root.context("local path backend 需要插件所在目录")?;
+        if !source.exists() || !source.is_dir() {
+            anyhow::bail!("插件目录不存在: {}", source.to_string_lossy());
+        }
+        let source = source.canonicalize().unwrap_or_else(|_| source.to_path_buf());
+        Ok(vec![single_plugin_from_dir(&source, "local", "local")?])
+    }
+}
+
+/// 克隆任意 git 仓库（可选指定 ref），把仓库根目录当成单个 plugin 的源码，
+/// 不要求对方仓库提供 `marketplace.json`
+pub struct GitUrlBackend;
+
+impl GitUrlBackend {
+    /// 同一个 repo url 固定映射到同一个缓存目录；每次 `resolve` 都会先删掉该
+    /// 目录里已有的克隆，再重新 clone 一份
+    fn cache_dir_for(repository_url: &str) -> Result<PathBuf> {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(repository_url.as_bytes());
+        let digest = format!("{:x}", hasher.finalize());
+
+        Ok(dirs::cache_dir()
+            .context("无法获取系统缓存目录")?
+            .join("agent-skills-guard")
+            .join("git-plugins")
+            .join(&digest[..16]))
+    }
+}
+
+impl PluginSourceBackend for GitUrlBackend {
+    fn id(&self) -> &'static str {
+        "git_url"
+    }
+
+    fn resolve(&self, request: &PluginSourceRequest) -> Result<Vec<ResolvedPlugin>> {
+        let dest = Self::cache_dir_for(request.repository_url)?;
+
+        if dest.exists() {
+            std::fs::remove_dir_all(&dest).context("清理旧的 git 插件缓存目录失败")?;
+        }
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).context("创建 git 插件缓存目录失败")?;
+        }
+
+        let dest_str = dest.to_string_lossy().to_string();
+        git_output(&["clone", "--quiet", request.repository_url, &dest_str])
+            .with_context(|| format!("clone 仓库失败: {}", request.repository_url))?;
+
+        if let Some(git_ref) = request.git_ref {
+            git_output(&["-C", &dest_str, "checkout", "--quiet", git_ref])
+                .with_context(|| format!("checkout 到 {} 失败", git_ref))?;
+        }
+
+        Ok(vec![single_plugin_from_dir(&dest, request.repository_url, "git")?])
+    }
+}
+
+/// 按 [`crate::models::Plugin::discovery_source`] 调度到对应的 [`PluginSourceBackend`]，
+/// 镜像 [`crate::services::plugin_backend::BackendRegistry`] 的形状。未知/缺失的
+/// discovery_source 回退到 [`GithubMarketplaceBackend`]——这是历史上唯一的来源，
+/// 也是新增插件记录默认带上的 `discovery_source`
+pub struct PluginSourceRegistry {
+    backends: HashMap<&'static str, Box<dyn PluginSourceBackend>>,
+    default_id: &'static str,
+}
+
+impl PluginSourceRegistry {
+    pub fn new() -> Self {
+        let mut backends: HashMap<&'static str, Box<dyn PluginSourceBackend>> = HashMap::new();
+        let github = GithubMarketplaceBackend;
+        let default_id = github.id();
+        backends.insert(github.id(), Box::new(github));
+        backends.insert(LocalPathBackend.id(), Box::new(LocalPathBackend));
+        backends.insert(GitUrlBackend.id(), Box::new(GitUrlBackend));
+
+        Self { backends, default_id }
+    }
+
+    pub fn resolve(&self, discovery_source: Option<&str>) -> &dyn PluginSourceBackend {
+        discovery_source
+            .and_then(|id| self.backends.get(id))
+            .or_else(|| self.backends.get(self.default_id))
+            .expect("默认 source backend 必须已注册")
+            .as_ref()
+    }
+}
+
+impl Default for PluginSourceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,99 @@
+use crate::models::Plugin;
+use anyhow::{Context, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Wry};
+use tauri_plugin_store::StoreExt;
+
+/// 合并写入窗口：这段时间内对 [`PluginStore`] 的重复 `save`/`save_all` 调用只会
+/// 落盘一次，避免 `scan_all_installed_plugins` 这类逐个 plugin 调用的场景里
+/// 每个 plugin 都触发一次磁盘 IO
+const DEBOUNCE: Duration = Duration::from_millis(800);
+
+/// 基于 `tauri-plugin-store` 的 `Plugin` 快照缓存，键为 [`Plugin::id`]。
+///
+/// 和 [`crate::services::Database`] 的关系：`Database`（SQLite）始终是权威数据源，
+/// 负责全文检索、迁移和所有写入的最终落地；这里只是它的一份 JSON 快照，专门
+/// 用来让前端在 `get_plugins` 重新跑一遍 `claude plugin list --json` 同步、
+/// 拿到权威结果之前，先展示上一次已知状态，不必每次启动都等 CLI 往返。写入
+/// 是防抖的（见 [`DEBOUNCE`]），这份快照丢失也没关系，下次同步会重新填满。
+pub struct PluginStore {
+    store: Arc<tauri_plugin_store::Store<Wry>>,
+    generation: Arc<AtomicU64>,
+}
+
+impl PluginStore {
+    const FILE_NAME: &'static str = "plugins-snapshot.json";
+
+    pub fn new(app: &AppHandle) -> Result<Self> {
+        let store = app
+            .store(Self::FILE_NAME)
+            .context("Failed to open plugin snapshot store")?;
+        Ok(Self {
+            store,
+            generation: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// 写入/更新一条 plugin 快照（防抖落盘）
+    pub fn save(&self, plugin: &Plugin) {
+        self.set_entry(plugin);
+        self.schedule_flush();
+    }
+
+    /// 批量写入，用于一次同步/扫描之后整份刷新快照
+    pub fn save_all(&self, plugins: &[Plugin]) {
+        for plugin in plugins {
+            self.set_entry(plugin);
+        }
+        self.schedule_flush();
+    }
+
+    /// 删除一条快照（插件卸载/marketplace 移除时调用）
+    pub fn delete(&self, plugin_id: &str) {
+        let _ = self.store.delete(plugin_id);
+        self.schedule_flush();
+    }
+
+    /// 读取全部缓存的 plugin 快照；反序列化失败的条目会被跳过并记录警告，
+    /// 不会让整个加载失败——快照只是一份辅助缓存，权威数据始终来自 `Database`
+    pub fn load_all(&self) -> Vec<Plugin> {
+        self.store
+            .entries()
+            .into_iter()
+            .filter_map(|(key, value)| match serde_json::from_value::<Plugin>(value) {
+                Ok(plugin) => Some(plugin),
+                Err(e) => {
+                    log::warn!("插件快照缓存中的 {} 解析失败，已忽略: {}", key, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn set_entry(&self, plugin: &Plugin) {
+        match serde_json::to_value(plugin) {
+            Ok(value) => self.store.set(plugin.id.clone(), value),
+            Err(e) => log::warn!("序列化 plugin 快照失败，已跳过: {}", e),
+        }
+    }
+
+    /// 调度一次延迟落盘：每次调用都会把 `generation` 推高一格，延迟到期后只有
+    /// 持有最新 `generation` 的那个任务才会真正调用 `Store::save()`，期间发生
+    /// 的其它调度都会在醒来时发现自己已经过期，直接放弃——实现合并写入
+    fn schedule_flush(&self) {
+        let target_generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let generation = Arc::clone(&self.generation);
+        let store = Arc::clone(&self.store);
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(DEBOUNCE).await;
+            if generation.load(Ordering::SeqCst) != target_generation {
+                return;
+            }
+            if let Err(e) = store.save() {
+                log::warn!("插件快照落盘失败: {}", e);
+            }
+        });
+    }
+}
@@ -1,4 +1,5 @@
-use crate::models::Skill;
+use crate::models::security::CapabilityPolicy;
+use crate::models::{IntegrityIssueKind, IntegrityReport, RepairPolicy, RepairReport, Skill, SkillIntegrityIssue, SkillInstallation, SkillStatus};
 use crate::security::SecurityScanner;
 use crate::services::{Database, GitHubService};
 use anyhow::{Result, Context};
@@ -11,17 +12,24 @@ pub struct SkillManager {
     github: GitHubService,
     scanner: SecurityScanner,
     skills_dir: PathBuf,
+    /// 能力访问策略：install_skill 会在此策略之外的能力请求上阻断安装，
+    /// 除非调用方显式传入 `force = true`。
+    capability_policy: CapabilityPolicy,
 }
 
 impl SkillManager {
     pub fn new(db: Arc<Database>) -> Self {
         let skills_dir = Self::get_skills_directory();
 
+        // 可选的 GitHub personal access token：提升限流额度并支持私有仓库
+        let token = std::env::var("AGENT_SKILLS_GUARD_GITHUB_TOKEN").ok();
+
         Self {
             db,
-            github: GitHubService::new(),
+            github: GitHubService::with_token(token),
             scanner: SecurityScanner::new(),
             skills_dir,
+            capability_policy: CapabilityPolicy::default(),
         }
     }
 
@@ -33,25 +41,32 @@ impl SkillManager {
 
     /// 下载并分析 skill
     pub async fn download_and_analyze(&self, skill: &mut Skill) -> Result<Vec<u8>> {
-        // 构建下载 URL
         let (owner, repo) = crate::models::Repository::from_github_url(&skill.repository_url)?;
 
-        // 下载 SKILL.md 文件
-        let download_url = format!(
-            "https://raw.githubusercontent.com/{}/{}/main/{}/SKILL.md",
-            owner, repo, skill.file_path
-        );
-
-        log::info!("Downloading SKILL.md from: {}", download_url);
+        // 仓库可能固定了分支/tag/commit SHA，也可能指向一个非 github.com 的主机
+        // （GitHub Enterprise Server、Gitee 等）；两者都记录在 `Repository` 上
+        let repo_record = self.db.get_repository(&skill.repository_url)?;
+        let pinned_ref = repo_record.as_ref().and_then(|r| r.git_ref.clone());
+        let github = match &repo_record {
+            Some(r) => self.github.scoped_to(r),
+            None => crate::services::github::ScopedGitHubService::Borrowed(&self.github),
+        };
+        let git_ref = github.resolve_ref(&owner, &repo, pinned_ref.as_deref()).await?;
 
-        // 下载文件内容
-        let content = self.github.download_file(&download_url).await?;
-
-        // 解析 frontmatter 更新 skill 元数据
-        let (name, description) = self.github.fetch_skill_metadata(&owner, &repo, &skill.file_path).await?;
+        // 解析 frontmatter 更新 skill 元数据（复用同一条 ref 解析路径）
+        let (name, description) = github
+            .fetch_skill_metadata(&owner, &repo, &skill.file_path, Some(git_ref.as_str()))
+            .await?;
         skill.name = name;
         skill.description = description;
 
+        // 下载 SKILL.md 文件内容
+        let download_url = github.raw_file_url(&owner, &repo, &git_ref, &format!("{}/SKILL.md", skill.file_path));
+        let content = github.download_file(&download_url).await?;
+
+        // 记录本次安装实际使用的 ref，便于日后复现该次安装
+        skill.installed_commit_sha = Some(git_ref);
+
         // 安全扫描
         let content_str = String::from_utf8_lossy(&content);
         let report = self.scanner.scan_file(&content_str, "SKILL.md")?;
@@ -64,12 +79,19 @@ impl SkillManager {
                 .collect()
         );
         skill.checksum = Some(self.scanner.calculate_checksum(&content));
+        skill.capability_manifest = Some(report.capabilities);
 
         Ok(content)
     }
 
-    /// 安装 skill 到本地
-    pub async fn install_skill(&self, skill_id: &str) -> Result<()> {
+    /// 安装 skill 到本地。
+    ///
+    /// `force` 为 `false` 时，若 skill 请求的能力超出 [`CapabilityPolicy`]
+    /// 允许的范围（例如访问网络或执行 shell），将拒绝安装并在错误信息中
+    /// 列出具体超出的能力，交由前端展示确认对话框；调用方确认后应以
+    /// `force = true` 重新调用以完成安装。安全评分过低则始终阻断，不受
+    /// `force` 影响。
+    pub async fn install_skill(&self, skill_id: &str, force: bool) -> Result<()> {
         // 从数据库获取 skill
         let mut skill = self.db.get_skills()?
             .into_iter()
@@ -89,6 +111,20 @@ impl SkillManager {
             }
         }
 
+        // 检查能力清单是否超出允许的策略范围
+        if !force {
+            if let Some(manifest) = &skill.capability_manifest {
+                let violations = self.capability_policy.violations(manifest);
+                if !violations.is_empty() {
+                    anyhow::bail!(
+                        "Skill requests capabilities outside the allowed policy: {}. \
+                         Confirm explicitly to proceed with installation.",
+                        violations.join(", ")
+                    );
+                }
+            }
+        }
+
         // 确保目标目录存在
         std::fs::create_dir_all(&self.skills_dir)
             .context("Failed to create skills directory")?;
@@ -105,22 +141,188 @@ impl SkillManager {
         std::fs::create_dir_all(&skill_dir)
             .context("Failed to create skill directory")?;
 
-        // 写入 SKILL.md 文件
-        let skill_file_path = skill_dir.join("SKILL.md");
-        std::fs::write(&skill_file_path, content)
-            .context("Failed to write SKILL.md file")?;
+        // 下载整个 skill 目录（SKILL.md 及其附带的脚本/模板/资源文件），
+        // 而不只是已经在内存里的 SKILL.md 内容
+        let (owner, repo) = crate::models::Repository::from_github_url(&skill.repository_url)?;
+        let repo_record = self.db.get_repository(&skill.repository_url)?;
+        let github = match &repo_record {
+            Some(r) => self.github.scoped_to(r),
+            None => crate::services::github::ScopedGitHubService::Borrowed(&self.github),
+        };
+        let git_ref = skill.installed_commit_sha.clone().context("Missing resolved git ref")?;
+        let (local_paths, aggregate_checksum) = self
+            .download_skill_directory(&github, &owner, &repo, &git_ref, &skill, &skill_dir)
+            .await?;
+        drop(content); // 已被 download_skill_directory 重新下载并落盘校验，内存副本不再需要
 
         // 更新数据库
         skill.installed = true;
         skill.installed_at = Some(Utc::now());
         skill.local_path = Some(skill_dir.to_string_lossy().to_string());
+        skill.local_paths = Some(local_paths.clone());
 
         self.db.save_skill(&skill)?;
+        self.db.save_installation(&SkillInstallation {
+            skill_id: skill.id.clone(),
+            installed_at: Utc::now(),
+            version: git_ref,
+            local_path: skill_dir.to_string_lossy().to_string(),
+            checksum: aggregate_checksum,
+        })?;
 
         log::info!("Skill installed successfully: {}", skill.name);
         Ok(())
     }
 
+    /// 递归下载整个 skill 目录到本地：先递归列出远端目录下的全部文件，再逐个
+    /// 下载；已存在且校验和与本次下载内容一致的本地文件跳过重写，写入后立即
+    /// 读回校验，确保落盘内容与远端一致。每次调用都会重新下载全部文件，已在
+    /// 磁盘上的文件不会因此少下载一次；中途失败（网络错误等）后重新调用时，
+    /// 已经正确落盘的文件只是跳过写入，不会被截断或重复写入破坏。
+    ///
+    /// 返回写入的全部本地文件路径，以及按相对路径排序后拼接各文件校验和
+    /// 得到的目录聚合校验和（供 [`SkillInstallation::checksum`] 使用）。
+    async fn download_skill_directory(
+        &self,
+        github: &crate::services::github::ScopedGitHubService<'_>,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+        skill: &Skill,
+        skill_dir: &std::path::Path,
+    ) -> Result<(Vec<String>, String)> {
+        let remote_files = self
+            .list_skill_directory_recursive(github, owner, repo, &skill.file_path, git_ref)
+            .await?;
+
+        let mut local_paths = Vec::new();
+        let mut file_checksums: Vec<(String, String)> = Vec::new();
+
+        for remote_path in &remote_files {
+            let relative = remote_path
+                .strip_prefix(&skill.file_path)
+                .unwrap_or(remote_path)
+                .trim_start_matches('/');
+            let local_file_path = skill_dir.join(relative);
+            if let Some(parent) = local_file_path.parent() {
+                std::fs::create_dir_all(parent).context("Failed to create skill subdirectory")?;
+            }
+
+            let download_url = github.raw_file_url(owner, repo, git_ref, remote_path);
+            // SKILL.md 本身在 `download_and_analyze` 里已经独立下载过一次并
+            // 记录了 `skill.checksum`；这里是同一个 ref 的第二次下载（落盘的
+            // 那一次），用 `download_file_verified` 把它跟第一次的摘要比对，
+            // 能发现两次下载之间内容被篡改（中间人、被污染的 CDN/缓存等）——
+            // 而不仅仅是「写盘内容等于刚下载的内容」这种自证式的校验
+            let content = if relative == "SKILL.md" {
+                github
+                    .download_file_verified(&download_url, skill.checksum.as_deref())
+                    .await?
+            } else {
+                github.download_file(&download_url).await?
+            };
+            let expected_checksum = self.scanner.calculate_checksum(&content);
+
+            let already_present = std::fs::read(&local_file_path)
+                .map(|existing| self.scanner.calculate_checksum(&existing) == expected_checksum)
+                .unwrap_or(false);
+            if !already_present {
+                std::fs::write(&local_file_path, &content)
+                    .with_context(|| format!("Failed to write skill file {}", local_file_path.display()))?;
+            }
+
+            let written = std::fs::read(&local_file_path)
+                .with_context(|| format!("Failed to read back {}", local_file_path.display()))?;
+            let actual_checksum = self.scanner.calculate_checksum(&written);
+            if actual_checksum != expected_checksum {
+                anyhow::bail!(
+                    "Checksum mismatch after writing {}: expected {}, got {}",
+                    local_file_path.display(),
+                    expected_checksum,
+                    actual_checksum
+                );
+            }
+
+            local_paths.push(local_file_path.to_string_lossy().to_string());
+            file_checksums.push((relative.to_string(), expected_checksum));
+        }
+
+        file_checksums.sort_by(|a, b| a.0.cmp(&b.0));
+        let aggregate_input = file_checksums
+            .iter()
+            .map(|(path, checksum)| format!("{}:{}", path, checksum))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let aggregate_checksum = self.scanner.calculate_checksum(aggregate_input.as_bytes());
+
+        Ok((local_paths, aggregate_checksum))
+    }
+
+    /// 递归列出一个 skill 目录下的全部文件路径（不含子目录本身）
+    async fn list_skill_directory_recursive(
+        &self,
+        github: &crate::services::github::ScopedGitHubService<'_>,
+        owner: &str,
+        repo: &str,
+        dir: &str,
+        git_ref: &str,
+    ) -> Result<Vec<String>> {
+        let mut files = Vec::new();
+        let mut queue = std::collections::VecDeque::new();
+        queue.push_back(dir.to_string());
+
+        while let Some(current_dir) = queue.pop_front() {
+            let entries = github
+                .list_skill_files(owner, repo, &current_dir, Some(git_ref))
+                .await?;
+            for entry in entries {
+                if entry.content_type == "dir" {
+                    queue.push_back(entry.path);
+                } else {
+                    files.push(entry.path);
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// 检测某个已安装 skill 是否有可用更新：重新下载远端 SKILL.md，计算其
+    /// SHA-256 并与安装时记录的 [`Skill::checksum`] 比对，内容不同即视为
+    /// 上游已更新。未安装则直接返回 `NotInstalled`，不发起网络请求
+    pub async fn check_skill_update(&self, skill_id: &str) -> Result<SkillStatus> {
+        let skill = self.db.get_skills()?
+            .into_iter()
+            .find(|s| s.id == skill_id)
+            .context("Skill not found")?;
+
+        if !skill.installed {
+            return Ok(SkillStatus::NotInstalled);
+        }
+
+        let Some(installed_checksum) = &skill.checksum else {
+            return Ok(SkillStatus::Installed);
+        };
+
+        let (owner, repo) = crate::models::Repository::from_github_url(&skill.repository_url)?;
+        let repo_record = self.db.get_repository(&skill.repository_url)?;
+        let pinned_ref = repo_record.as_ref().and_then(|r| r.git_ref.clone());
+        let github = match &repo_record {
+            Some(r) => self.github.scoped_to(r),
+            None => crate::services::github::ScopedGitHubService::Borrowed(&self.github),
+        };
+        let git_ref = github.resolve_ref(&owner, &repo, pinned_ref.as_deref()).await?;
+        let download_url = github.raw_file_url(&owner, &repo, &git_ref, &format!("{}/SKILL.md", skill.file_path));
+        let content = github.download_file(&download_url).await?;
+        let remote_checksum = self.scanner.calculate_checksum(&content);
+
+        if &remote_checksum == installed_checksum {
+            Ok(SkillStatus::Installed)
+        } else {
+            Ok(SkillStatus::UpdateAvailable)
+        }
+    }
+
     /// 卸载 skill
     pub fn uninstall_skill(&self, skill_id: &str) -> Result<()> {
         // 从数据库获取 skill
@@ -159,4 +361,104 @@ impl SkillManager {
         let skills = self.db.get_skills()?;
         Ok(skills.into_iter().filter(|s| s.installed).collect())
     }
+
+    /// 校验已安装 skill 的完整性：按 `local_paths`（没有则退回 `local_path`）
+    /// 重新计算每个 SKILL.md 的 SHA-256，与安装时记录的 `checksum` 比对，并
+    /// 列出 `installations` 表中已失去对应 `skills` 记录的孤儿行。只读，不修改
+    /// 任何状态；实际修复交由 [`Self::repair_installations`] 完成。
+    pub fn verify_installations(&self) -> Result<IntegrityReport> {
+        let installed_skills: Vec<Skill> = self.db.get_skills()?
+            .into_iter()
+            .filter(|s| s.installed)
+            .collect();
+
+        let mut issues = Vec::new();
+        let mut checked_paths = 0usize;
+
+        for skill in &installed_skills {
+            for dir_path in Self::installed_paths(skill) {
+                checked_paths += 1;
+                // local_path(s) 是目录路径，需要拼接 SKILL.md 文件名
+                let skill_file_path = PathBuf::from(&dir_path).join("SKILL.md");
+
+                if !skill_file_path.exists() {
+                    issues.push(SkillIntegrityIssue {
+                        skill_id: skill.id.clone(),
+                        skill_name: skill.name.clone(),
+                        path: dir_path,
+                        kind: IntegrityIssueKind::MissingFile,
+                    });
+                    continue;
+                }
+
+                let Some(expected_checksum) = &skill.checksum else {
+                    continue;
+                };
+
+                match std::fs::read(&skill_file_path) {
+                    Ok(content) => {
+                        let actual_checksum = self.scanner.calculate_checksum(&content);
+                        if &actual_checksum != expected_checksum {
+                            issues.push(SkillIntegrityIssue {
+                                skill_id: skill.id.clone(),
+                                skill_name: skill.name.clone(),
+                                path: dir_path,
+                                kind: IntegrityIssueKind::ChecksumMismatch,
+                            });
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to read {} for integrity check: {}", dir_path, e);
+                    }
+                }
+            }
+        }
+
+        let orphaned_installations = self.db.get_orphaned_installations()?;
+
+        Ok(IntegrityReport {
+            checked_skills: installed_skills.len(),
+            checked_paths,
+            missing_count: issues.iter().filter(|i| i.kind == IntegrityIssueKind::MissingFile).count(),
+            mismatch_count: issues.iter().filter(|i| i.kind == IntegrityIssueKind::ChecksumMismatch).count(),
+            orphaned_count: orphaned_installations.len(),
+            issues,
+            orphaned_installations,
+        })
+    }
+
+    /// 根据 `policy` 对 [`Self::verify_installations`] 发现的问题做修复：清除孤儿
+    /// `installations` 行、把文件缺失/校验和不一致的 skill 标记为 `needs_redownload`
+    pub fn repair_installations(&self, policy: RepairPolicy) -> Result<RepairReport> {
+        let report = self.verify_installations()?;
+        let mut result = RepairReport::default();
+
+        if policy.purge_orphaned_installations {
+            for orphan in &report.orphaned_installations {
+                self.db.delete_orphaned_installation(&orphan.skill_id)?;
+                result.purged_orphaned_installations += 1;
+            }
+        }
+
+        if policy.flag_drifted_skills_for_redownload {
+            let mut flagged = std::collections::HashSet::new();
+            for issue in &report.issues {
+                if flagged.insert(issue.skill_id.clone()) {
+                    self.db.flag_skill_needs_redownload(&issue.skill_id)?;
+                    result.flagged_skills_for_redownload += 1;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// 某个 skill 安装时落盘的目录路径列表：优先使用 `local_paths`，否则退回
+    /// 只有单个安装路径的旧字段 `local_path`
+    fn installed_paths(skill: &Skill) -> Vec<String> {
+        match &skill.local_paths {
+            Some(paths) if !paths.is_empty() => paths.clone(),
+            _ => skill.local_path.clone().into_iter().collect(),
+        }
+    }
 }